@@ -0,0 +1,910 @@
+/* Copyright © 2021 Randy Barlow
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, version 3 of the License.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
+//! # Availability
+//!
+//! Choices can say when they can be picked, such as a restaurant's opening hours or the days a
+//! teammate is on vacation. This module defines the [`Window`]'s of time that availability is
+//! described with, and the [`Availability`] that every choice has, which the
+//! [`crate::engine::Engine`] uses to leave out the choices that can't be picked at the time of a
+//! pick.
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Weekday};
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use thiserror::Error;
+
+/// When a choice can be picked.
+///
+/// In the config, these are written alongside the choice's name.
+///
+/// # Attributes
+///
+/// * `available` - If there are any, the choice can only be picked during one of these windows,
+///   such as a restaurant's opening hours.
+/// * `unavailable` - The choice can't be picked during any of these windows, such as when someone
+///   is on vacation.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Availability {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub available: Vec<Window>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable: Vec<Window>,
+}
+
+impl Availability {
+    /// Return `true` if the choice can be picked at any time.
+    pub fn is_empty(&self) -> bool {
+        self.available.is_empty() && self.unavailable.is_empty()
+    }
+
+    /// Return why the choice can't be picked at the given time, or `None` if it can.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time of the pick.
+    pub fn unavailable_reason(&self, now: DateTime<Local>) -> Option<String> {
+        if let Some(window) = self.unavailable.iter().find(|w| w.contains(now)) {
+            return Some(format!("unavailable {}", window));
+        }
+        if !self.available.is_empty() && !self.available.iter().any(|w| w.contains(now)) {
+            let windows = self
+                .available
+                .iter()
+                .map(Window::to_string)
+                .collect::<Vec<_>>();
+            return Some(format!("only available {}", windows.join(" or ")));
+        }
+        None
+    }
+}
+
+/// A window of time, such as weekdays from 11:30 to 14:00, or every other Tuesday.
+///
+/// A time is in the window if it matches every rule the window has, and a window without any rules
+/// contains every time. In the config, a window is a map of its rules, and a window that is a single
+/// day can be written as just a date, like ```2021-06-01```. Windows are always saved as maps, since
+/// TOML does not allow a list to mix strings with tables, nor a choice's plain values to follow
+/// its tables.
+///
+/// # Attributes
+///
+/// * `from` - The first day of the window.
+/// * `until` - The last day of the window.
+/// * `days` - The days of the week in the window, written like ```[mon, wed, fri]```.
+/// * `times` - The times of day in the window, written like ```["11:30-14:00", "17:00-22:00"]```.
+///   A range that ends before it starts, like ```22:00-02:00```, wraps around midnight, and each
+///   time is checked against the rest of the window's rules on the day it falls on.
+/// * `rrule` - An iCalendar recurrence rule for the days in the window, such as
+///   ```FREQ=WEEKLY;INTERVAL=2;BYDAY=TU```. It counts from the `from` day. See [`Recurrence`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Window {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<NaiveDate>,
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_days"
+    )]
+    pub days: Vec<Weekday>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub times: Vec<TimeRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rrule: Option<Recurrence>,
+}
+
+impl Window {
+    /// Return `true` if the given time is in this window.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time to look for.
+    pub fn contains(&self, now: DateTime<Local>) -> bool {
+        let date = now.date_naive();
+        self.from.is_none_or(|from| from <= date)
+            && self.until.is_none_or(|until| date <= until)
+            && (self.days.is_empty() || self.days.contains(&date.weekday()))
+            && (self.times.is_empty() || self.times.iter().any(|t| t.contains(now.time())))
+            && self
+                .rrule
+                .as_ref()
+                .is_none_or(|rrule| rrule.matches(date, self.from))
+    }
+
+    /// Return `true` if the window ends before it starts, so that it contains no time at all.
+    pub fn is_backwards(&self) -> bool {
+        matches!((self.from, self.until), (Some(from), Some(until)) if until < from)
+    }
+}
+
+impl From<NaiveDate> for Window {
+    fn from(date: NaiveDate) -> Self {
+        Window {
+            from: Some(date),
+            until: Some(date),
+            ..Window::default()
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Window {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// The fields of a window that is written out in full.
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Fields {
+            #[serde(default)]
+            from: Option<NaiveDate>,
+            #[serde(default)]
+            until: Option<NaiveDate>,
+            #[serde(default)]
+            days: Vec<Weekday>,
+            #[serde(default)]
+            times: Vec<TimeRange>,
+            #[serde(default)]
+            rrule: Option<Recurrence>,
+        }
+
+        struct WindowVisitor;
+
+        impl<'de> Visitor<'de> for WindowVisitor {
+            type Value = Window;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a date, or a map with from, until, days, times, and rrule"
+                )
+            }
+
+            fn visit_str<E: de::Error>(self, date: &str) -> Result<Window, E> {
+                date.parse::<NaiveDate>()
+                    .map(Window::from)
+                    .map_err(E::custom)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Window, A::Error> {
+                let fields = Fields::deserialize(MapAccessDeserializer::new(map))?;
+                if let Some(rrule) = &fields.rrule {
+                    if rrule.needs_start() && fields.from.is_none() {
+                        return Err(de::Error::custom(AvailabilityError::MissingStart(
+                            rrule.to_string(),
+                        )));
+                    }
+                }
+                Ok(Window {
+                    from: fields.from,
+                    until: fields.until,
+                    days: fields.days,
+                    times: fields.times,
+                    rrule: fields.rrule,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(WindowVisitor)
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = vec![];
+        match (self.from, self.until) {
+            (Some(from), Some(until)) if from == until => parts.push(format!("on {}", from)),
+            (Some(from), Some(until)) => parts.push(format!("from {} until {}", from, until)),
+            (Some(from), None) => parts.push(format!("from {}", from)),
+            (None, Some(until)) => parts.push(format!("until {}", until)),
+            (None, None) => {}
+        }
+        if !self.days.is_empty() {
+            let days = self.days.iter().map(|d| day_name(*d)).collect::<Vec<_>>();
+            parts.push(format!("on {}", days.join(", ")));
+        }
+        if !self.times.is_empty() {
+            let times = self
+                .times
+                .iter()
+                .map(TimeRange::to_string)
+                .collect::<Vec<_>>();
+            parts.push(format!("at {}", times.join(", ")));
+        }
+        if let Some(rrule) = &self.rrule {
+            parts.push(format!("repeating {}", rrule));
+        }
+        if parts.is_empty() {
+            write!(f, "at any time")
+        } else {
+            write!(f, "{}", parts.join(" "))
+        }
+    }
+}
+
+/// A range of times of day, which includes its start but not its end.
+///
+/// In the config, a range is written like ```11:30-14:00```. A range that ends before it starts,
+/// like ```22:00-02:00```, runs past midnight.
+///
+/// # Attributes
+///
+/// * `start` - The first time in the range.
+/// * `end` - The time the range ends at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeRange {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeRange {
+    /// Return `true` if the given time of day is in this range.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time to look for.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            self.start <= time || time < self.end
+        }
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = AvailabilityError;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let invalid = || AvailabilityError::InvalidTimeRange(range.to_string());
+        let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+        let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M");
+        let parsed = TimeRange {
+            start: parse(start).map_err(|_| invalid())?,
+            end: parse(end).map_err(|_| invalid())?,
+        };
+        if parsed.start == parsed.end {
+            return Err(AvailabilityError::EmptyTimeRange(range.to_string()));
+        }
+        Ok(parsed)
+    }
+}
+
+impl fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl Serialize for TimeRange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeRange {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// How often an iCalendar recurrence rule repeats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A day of the week in a recurrence rule's ```BYDAY``` part, such as ```TU```, or ```-1FR``` for
+/// the last Friday of the month.
+///
+/// # Attributes
+///
+/// * `ordinal` - Which of the matching days in the month, or in the year, is meant. Negative
+///   numbers count from the end. If this is `None`, every matching day is meant.
+/// * `weekday` - The day of the week.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecurrenceDay {
+    pub ordinal: Option<i32>,
+    pub weekday: Weekday,
+}
+
+/// The days that an iCalendar recurrence rule (RFC 5545) repeats on, such as
+/// ```FREQ=MONTHLY;BYDAY=1MO``` for the first Monday of every month.
+///
+/// Only the parts of a rule that pick days are supported, since times of day are given by a
+/// [`Window`]'s times: ```FREQ```, ```INTERVAL```, ```BYDAY```, ```BYMONTHDAY```, ```BYMONTH```,
+/// and ```UNTIL```. Parts of the day that a rule doesn't give come from the day it starts on, as
+/// in iCalendar.
+///
+/// # Attributes
+///
+/// * `frequency` - How often the rule repeats.
+/// * `interval` - How many frequency periods pass between repeats. For example, a weekly rule with
+///   an interval of 2 repeats every other week.
+/// * `by_day` - The days of the week the rule repeats on.
+/// * `by_month_day` - The days of the month the rule repeats on. Negative days count from the end
+///   of the month.
+/// * `by_month` - The months the rule repeats in, from 1 to 12.
+/// * `until` - The last day the rule repeats on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<RecurrenceDay>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+    pub until: Option<NaiveDate>,
+}
+
+impl Recurrence {
+    /// Return `true` if the rule repeats on the given day.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The day to look for.
+    /// * `start` - The day the rule starts on, which it counts its interval from.
+    pub fn matches(&self, date: NaiveDate, start: Option<NaiveDate>) -> bool {
+        if self.until.is_some_and(|until| until < date) || start.is_some_and(|start| date < start) {
+            return false;
+        }
+        if !self.by_month.is_empty() && !self.by_month.contains(&date.month()) {
+            return false;
+        }
+        if !self.by_month_day.is_empty()
+            && !self
+                .by_month_day
+                .iter()
+                .any(|day| month_day_matches(date, *day))
+        {
+            return false;
+        }
+        if !self.by_day.is_empty() && !self.by_day.iter().any(|day| self.day_matches(date, day)) {
+            return false;
+        }
+
+        let start = match start {
+            Some(start) => start,
+            None => return true,
+        };
+        let same_day = match self.frequency {
+            Frequency::Daily => true,
+            Frequency::Weekly => !self.by_day.is_empty() || date.weekday() == start.weekday(),
+            Frequency::Monthly => {
+                !self.by_day.is_empty()
+                    || !self.by_month_day.is_empty()
+                    || date.day() == start.day()
+            }
+            Frequency::Yearly => {
+                !self.by_day.is_empty()
+                    || !self.by_month_day.is_empty()
+                    || (date.day() == start.day()
+                        && (!self.by_month.is_empty() || date.month() == start.month()))
+            }
+        };
+        let periods = match self.frequency {
+            Frequency::Daily => (date - start).num_days(),
+            Frequency::Weekly => (week_start(date) - week_start(start)).num_days() / 7,
+            Frequency::Monthly => {
+                (date.year() as i64 * 12 + date.month0() as i64)
+                    - (start.year() as i64 * 12 + start.month0() as i64)
+            }
+            Frequency::Yearly => (date.year() - start.year()) as i64,
+        };
+        same_day && periods % self.interval as i64 == 0
+    }
+
+    /// Return `true` if the rule can only be followed from the day it starts on, because it
+    /// repeats less often than every period or it takes some of its days from that day.
+    pub fn needs_start(&self) -> bool {
+        self.interval > 1
+            || match self.frequency {
+                Frequency::Daily => false,
+                Frequency::Weekly => self.by_day.is_empty(),
+                Frequency::Monthly | Frequency::Yearly => {
+                    self.by_day.is_empty() && self.by_month_day.is_empty()
+                }
+            }
+    }
+
+    /// Return `true` if the given day is the given ```BYDAY``` day.
+    fn day_matches(&self, date: NaiveDate, day: &RecurrenceDay) -> bool {
+        if date.weekday() != day.weekday {
+            return false;
+        }
+        let ordinal = match day.ordinal {
+            Some(ordinal) => ordinal,
+            None => return true,
+        };
+        // An ordinal counts within the month, unless a yearly rule has no months to count within.
+        let (position, length) = if self.frequency == Frequency::Yearly && self.by_month.is_empty()
+        {
+            (date.ordinal() as i32, days_in_year(date) as i32)
+        } else {
+            (date.day() as i32, days_in_month(date) as i32)
+        };
+        if ordinal > 0 {
+            (position - 1) / 7 + 1 == ordinal
+        } else {
+            (length - position) / 7 + 1 == -ordinal
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = AvailabilityError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| AvailabilityError::InvalidRecurrence {
+            rule: rule.to_string(),
+            reason,
+        };
+        let parts = rule.trim();
+        let parts = match parts.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &parts[6..],
+            _ => parts,
+        };
+
+        let mut frequency = None;
+        let mut recurrence = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            by_day: vec![],
+            by_month_day: vec![],
+            by_month: vec![],
+            until: None,
+        };
+        let mut seen = vec![];
+        for part in parts.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("`{}` is not like KEY=VALUE", part)))?;
+            let key = key.trim().to_ascii_uppercase();
+            let value = value.trim();
+            if seen.contains(&key) {
+                return Err(invalid(format!("{} is given more than once", key)));
+            }
+            seen.push(key.clone());
+            let bad_value = || invalid(format!("`{}` is not a valid {}", value, key));
+            match key.as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(bad_value()),
+                    })
+                }
+                "INTERVAL" => {
+                    recurrence.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(bad_value)?
+                }
+                "BYDAY" => {
+                    recurrence.by_day = value
+                        .split(',')
+                        .map(|day| parse_recurrence_day(day.trim()).ok_or_else(bad_value))
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => {
+                    recurrence.by_month_day = value
+                        .split(',')
+                        .map(|day| {
+                            day.trim()
+                                .parse::<i32>()
+                                .ok()
+                                .filter(|day| *day != 0 && day.abs() <= 31)
+                                .ok_or_else(bad_value)
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTH" => {
+                    recurrence.by_month = value
+                        .split(',')
+                        .map(|month| {
+                            month
+                                .trim()
+                                .parse::<u32>()
+                                .ok()
+                                .filter(|month| (1..=12).contains(month))
+                                .ok_or_else(bad_value)
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                "UNTIL" => {
+                    recurrence.until = Some(
+                        value
+                            .get(..8)
+                            .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+                            .ok_or_else(bad_value)?,
+                    )
+                }
+                "COUNT" => return Err(invalid("COUNT is not supported, use UNTIL".to_string())),
+                _ => return Err(invalid(format!("{} is not supported", key))),
+            }
+        }
+
+        recurrence.frequency = frequency.ok_or_else(|| invalid("FREQ is missing".to_string()))?;
+        if matches!(recurrence.frequency, Frequency::Daily | Frequency::Weekly)
+            && recurrence.by_day.iter().any(|day| day.ordinal.is_some())
+        {
+            return Err(invalid(
+                "numbered BYDAY days need a MONTHLY or YEARLY FREQ".to_string(),
+            ));
+        }
+        Ok(recurrence)
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days = self
+                .by_day
+                .iter()
+                .map(|day| {
+                    let code = day_name(day.weekday)[..2].to_ascii_uppercase();
+                    match day.ordinal {
+                        Some(ordinal) => format!("{}{}", ordinal, code),
+                        None => code,
+                    }
+                })
+                .collect::<Vec<_>>();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days = self
+                .by_month_day
+                .iter()
+                .map(i32::to_string)
+                .collect::<Vec<_>>();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        if !self.by_month.is_empty() {
+            let months = self.by_month.iter().map(u32::to_string).collect::<Vec<_>>();
+            write!(f, ";BYMONTH={}", months.join(","))?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Recurrence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Recurrence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Define the errors that can be returned when parsing the parts of a [`Window`].
+#[derive(Debug, Error, PartialEq)]
+pub enum AvailabilityError {
+    #[error("Unable to parse `{0}` as a range of times like 11:30-14:00.")]
+    InvalidTimeRange(String),
+    #[error("The range of times `{0}` starts and ends at the same time.")]
+    EmptyTimeRange(String),
+    #[error("The recurrence rule `{rule}` is invalid: {reason}.")]
+    InvalidRecurrence { rule: String, reason: String },
+    #[error("The recurrence rule `{0}` needs a `from` date to count from.")]
+    MissingStart(String),
+}
+
+/// Return the name that days of the week are written with in the config, such as ```mon```.
+fn day_name(day: Weekday) -> String {
+    day.to_string().to_lowercase()
+}
+
+/// Parse a day in a recurrence rule's ```BYDAY``` part, such as ```TU``` or ```-1FR```.
+fn parse_recurrence_day(day: &str) -> Option<RecurrenceDay> {
+    let split = day.len().checked_sub(2)?;
+    let (ordinal, code) = (day.get(..split)?, day.get(split..)?);
+    let weekday = match code.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let ordinal = match ordinal {
+        "" => None,
+        ordinal => Some(
+            ordinal
+                .parse::<i32>()
+                .ok()
+                .filter(|n| *n != 0 && n.abs() <= 53)?,
+        ),
+    };
+    Some(RecurrenceDay { ordinal, weekday })
+}
+
+/// Return `true` if the given date is the given day of its month, counting from the end of the
+/// month if the day is negative.
+fn month_day_matches(date: NaiveDate, day: i32) -> bool {
+    if day > 0 {
+        date.day() as i32 == day
+    } else {
+        days_in_month(date) as i32 + day + 1 == date.day() as i32
+    }
+}
+
+/// Return the number of days in the given date's month.
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = match date.month() {
+        12 => (date.year() + 1, 1),
+        month => (date.year(), month + 1),
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|first| first.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+/// Return the number of days in the given date's year.
+fn days_in_year(date: NaiveDate) -> u32 {
+    if NaiveDate::from_ymd_opt(date.year(), 2, 29).is_some() {
+        366
+    } else {
+        365
+    }
+}
+
+/// Return the Monday of the given date's week.
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Save days of the week the way they are written in the config, such as ```mon```.
+fn serialize_days<S: Serializer>(days: &[Weekday], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(days.iter().map(|day| day_name(*day)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_time_range() {
+        let lunch: TimeRange = "11:30-14:00".parse().unwrap();
+        let late: TimeRange = "22:00-02:00".parse().unwrap();
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+
+        assert!(lunch.contains(time(11, 30)));
+        assert!(lunch.contains(time(13, 59)));
+        assert!(!lunch.contains(time(14, 0)));
+        assert!(late.contains(time(23, 0)));
+        assert!(late.contains(time(1, 0)));
+        assert!(!late.contains(time(12, 0)));
+        assert_eq!(lunch.to_string(), "11:30-14:00");
+        assert_eq!(
+            "noon".parse::<TimeRange>(),
+            Err(AvailabilityError::InvalidTimeRange("noon".to_string()))
+        );
+        assert_eq!(
+            "12:00-12:00".parse::<TimeRange>(),
+            Err(AvailabilityError::EmptyTimeRange("12:00-12:00".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_window_contains() {
+        let window: Window = serde_yaml::from_str(
+            "from: 2021-06-01\nuntil: 2021-06-30\ndays: [mon, Friday]\ntimes: [\"11:30-14:00\"]",
+        )
+        .unwrap();
+
+        // 2021-06-04 is a Friday, and 2021-06-05 is a Saturday.
+        assert!(window.contains(at(2021, 6, 4, 12, 0)));
+        assert!(!window.contains(at(2021, 6, 4, 18, 0)));
+        assert!(!window.contains(at(2021, 6, 5, 12, 0)));
+        assert!(!window.contains(at(2021, 7, 2, 12, 0)));
+        assert!(Window::default().contains(at(2021, 7, 2, 12, 0)));
+        assert_eq!(
+            window.to_string(),
+            "from 2021-06-01 until 2021-06-30 on mon, fri at 11:30-14:00"
+        );
+        assert_eq!(Window::default().to_string(), "at any time");
+    }
+
+    #[test]
+    fn test_recurrence() {
+        let rule = |rule: &str| rule.parse::<Recurrence>().unwrap();
+        let start = Some(date(2021, 6, 1));
+
+        // Every other week, on the day of the week it starts on, which is a Tuesday.
+        let fortnightly = rule("FREQ=WEEKLY;INTERVAL=2");
+        assert!(fortnightly.matches(date(2021, 6, 1), start));
+        assert!(!fortnightly.matches(date(2021, 6, 8), start));
+        assert!(fortnightly.matches(date(2021, 6, 15), start));
+        assert!(!fortnightly.matches(date(2021, 6, 16), start));
+        assert!(!fortnightly.matches(date(2021, 5, 18), start));
+
+        let last_friday = rule("RRULE:FREQ=MONTHLY;BYDAY=-1FR");
+        assert!(last_friday.matches(date(2021, 6, 25), None));
+        assert!(!last_friday.matches(date(2021, 6, 18), None));
+        assert!(last_friday.matches(date(2021, 7, 30), None));
+
+        let first_monday = rule("freq=monthly;byday=1MO;until=20210801");
+        assert!(first_monday.matches(date(2021, 6, 7), None));
+        assert!(!first_monday.matches(date(2021, 6, 14), None));
+        assert!(!first_monday.matches(date(2021, 8, 2), None));
+
+        let month_end = rule("FREQ=MONTHLY;BYMONTHDAY=-1");
+        assert!(month_end.matches(date(2021, 2, 28), None));
+        assert!(month_end.matches(date(2024, 2, 29), None));
+        assert!(!month_end.matches(date(2024, 2, 28), None));
+
+        let birthday = rule("FREQ=YEARLY");
+        assert!(birthday.matches(date(2025, 6, 1), start));
+        assert!(!birthday.matches(date(2025, 6, 2), start));
+
+        let summer_weekends = rule("FREQ=DAILY;BYDAY=SA,SU;BYMONTH=7,8");
+        assert!(summer_weekends.matches(date(2021, 7, 3), None));
+        assert!(!summer_weekends.matches(date(2021, 7, 5), None));
+        assert!(!summer_weekends.matches(date(2021, 6, 5), None));
+
+        assert_eq!(
+            rule("freq=monthly;interval=3;byday=1mo,-1fr;until=20211231T000000Z").to_string(),
+            "FREQ=MONTHLY;INTERVAL=3;BYDAY=1MO,-1FR;UNTIL=20211231"
+        );
+        assert!(fortnightly.needs_start());
+        assert!(!last_friday.needs_start());
+        assert!(birthday.needs_start());
+    }
+
+    #[test]
+    fn test_recurrence_errors() {
+        let error = |rule: &str| rule.parse::<Recurrence>().unwrap_err().to_string();
+
+        assert_eq!(
+            error("INTERVAL=2"),
+            "The recurrence rule `INTERVAL=2` is invalid: FREQ is missing."
+        );
+        assert_eq!(
+            error("FREQ=HOURLY"),
+            "The recurrence rule `FREQ=HOURLY` is invalid: `HOURLY` is not a valid FREQ."
+        );
+        assert_eq!(
+            error("FREQ=DAILY;COUNT=3"),
+            "The recurrence rule `FREQ=DAILY;COUNT=3` is invalid: COUNT is not supported, use UNTIL."
+        );
+        assert_eq!(
+            error("FREQ=WEEKLY;BYDAY=2TU"),
+            "The recurrence rule `FREQ=WEEKLY;BYDAY=2TU` is invalid: numbered BYDAY days need a \
+             MONTHLY or YEARLY FREQ."
+        );
+        assert_eq!(
+            error("FREQ=DAILY;FREQ=WEEKLY"),
+            "The recurrence rule `FREQ=DAILY;FREQ=WEEKLY` is invalid: FREQ is given more than once."
+        );
+        assert!(error("FREQ=MONTHLY;BYMONTHDAY=0").contains("`0` is not a valid BYMONTHDAY"));
+        assert!(error("FREQ=DAILY;INTERVAL=0").contains("`0` is not a valid INTERVAL"));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let windows: Vec<Window> = serde_yaml::from_str(
+            "- 2021-06-01\n- from: 2021-06-10\n  rrule: FREQ=WEEKLY;INTERVAL=2\n",
+        )
+        .unwrap();
+        assert_eq!(windows[0], Window::from(date(2021, 6, 1)));
+        assert_eq!(windows[1].from, Some(date(2021, 6, 10)));
+        assert_eq!(windows[1].rrule.as_ref().unwrap().interval, 2);
+
+        let error = serde_yaml::from_str::<Window>("rrule: FREQ=WEEKLY").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("The recurrence rule `FREQ=WEEKLY` needs a `from` date to count from."),
+            "Unexpected error: {}",
+            error
+        );
+        assert!(serde_yaml::from_str::<Window>("hours: [\"11:30-14:00\"]").is_err());
+    }
+
+    #[test]
+    fn test_unavailable_reason() {
+        let availability: Availability = serde_yaml::from_str(
+            "available:\n  - times: [\"11:30-14:00\"]\n  - days: [sat]\nunavailable:\n  - 2021-06-04\n",
+        )
+        .unwrap();
+
+        assert_eq!(availability.unavailable_reason(at(2021, 6, 3, 12, 0)), None);
+        assert_eq!(availability.unavailable_reason(at(2021, 6, 5, 20, 0)), None);
+        assert_eq!(
+            availability.unavailable_reason(at(2021, 6, 3, 20, 0)),
+            Some("only available at 11:30-14:00 or on sat".to_string())
+        );
+        assert_eq!(
+            availability.unavailable_reason(at(2021, 6, 4, 12, 0)),
+            Some("unavailable on 2021-06-04".to_string())
+        );
+        assert!(Availability::default().is_empty());
+        assert_eq!(
+            Availability::default().unavailable_reason(at(2021, 6, 4, 12, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_serialize() {
+        let days = Availability {
+            available: vec![],
+            unavailable: vec![Window::from(date(2021, 6, 1))],
+        };
+        assert_eq!(
+            serde_yaml::to_string(&days).unwrap(),
+            "---\nunavailable:\n  - from: 2021-06-01\n    until: 2021-06-01\n"
+        );
+
+        let windows: Availability = serde_yaml::from_str(
+            "available:\n  - 2021-06-01\n  - days: [mon]\n    times: [\"11:30-14:00\"]\n    \
+             rrule: FREQ=MONTHLY;BYDAY=1MO\n",
+        )
+        .unwrap();
+        let saved = serde_yaml::to_string(&windows).unwrap();
+        assert_eq!(
+            saved,
+            "---\navailable:\n  - from: 2021-06-01\n    until: 2021-06-01\n  - days:\n      - mon\n    \
+             times:\n      - \"11:30-14:00\"\n    rrule: FREQ=MONTHLY;BYDAY=1MO\n"
+        );
+        assert_eq!(
+            serde_yaml::from_str::<Availability>(&saved).unwrap(),
+            windows
+        );
+    }
+}
@@ -16,12 +16,16 @@ use std::io::{self, BufRead, Write};
 
 use prettytable::{format, Cell, Row, Table};
 
+use rpick::config::ChoiceDetails;
+use rpick::group::Vote;
 use rpick::ui;
 
 /// This implements the Ui trait for the rpick engine.
 pub struct Cli {
     /// If true, print out the chance tables.
     verbose: bool,
+    /// Set once an answer could not be read, after which no more prompts can be answered.
+    aborted: std::cell::Cell<bool>,
 }
 
 impl Cli {
@@ -31,7 +35,10 @@ impl Cli {
     ///
     /// * `verbose`: If true, the Cli will print out chance tables.
     pub fn new(verbose: bool) -> Self {
-        Cli { verbose }
+        Cli {
+            verbose,
+            aborted: std::cell::Cell::new(false),
+        }
     }
 
     /// Convert a slice of Cells into a [`prettytable::Row`].
@@ -57,6 +64,30 @@ impl Cli {
 
         r
     }
+
+    /// Read the user's answer to a prompt from stdin.
+    ///
+    /// If stdin has been closed or cannot be read, there is nobody to answer the question, so
+    /// `None` is returned and the Cli is marked as aborted. The engine then gives up on the pick,
+    /// without touching the config.
+    fn read_answer(&self) -> Option<String> {
+        let line = match io::stdout().flush() {
+            Ok(()) => io::stdin().lock().lines().next(),
+            Err(_) => None,
+        };
+        match line {
+            Some(Ok(line)) => Some(line),
+            _ => {
+                println!();
+                println!(
+                    "Unable to read an answer from stdin. Use --yes to accept the first choice \
+                     without prompting."
+                );
+                self.aborted.set(true);
+                None
+            }
+        }
+    }
 }
 
 impl ui::Ui for Cli {
@@ -75,7 +106,9 @@ impl ui::Ui for Cli {
         for row in &table.rows {
             t.add_row(Cli::convert_row(&row.cells, row.chosen));
         }
-        t.add_row(Cli::convert_row(&table.footer, false));
+        if !table.footer.is_empty() {
+            t.add_row(Cli::convert_row(&table.footer, false));
+        }
 
         println!();
         t.printstd();
@@ -90,11 +123,43 @@ impl ui::Ui for Cli {
     /// Ask the user if they accept the given choice and return their answer.
     fn prompt_choice(&self, choice: &str) -> bool {
         print!("Choice is {}. Accept? (Y/n) ", choice);
-        io::stdout().flush().unwrap();
-        let line = io::stdin().lock().lines().next().unwrap().unwrap();
-        if ["", "y", "Y"].contains(&line.as_str()) {
-            return true;
+        match self.read_answer() {
+            Some(line) => ["", "y", "Y"].contains(&line.as_str()),
+            None => false,
         }
-        false
+    }
+
+    /// Print the given choice's details, then ask the user if they accept it.
+    fn prompt_choice_with_details(&self, choice: &str, details: &ChoiceDetails) -> bool {
+        match &details.description {
+            Some(description) => println!("{} - {}", choice, description),
+            None => println!("{}", choice),
+        }
+        if let Some(url) = &details.url {
+            println!("  {}", url);
+        }
+        for (attribute, value) in &details.attributes {
+            println!("  {}: {}", attribute, value);
+        }
+        self.prompt_choice(choice)
+    }
+
+    /// Ask the given participant how they vote on the given choice. Anything other than accepting
+    /// or abstaining is a veto, as is a vote that could not be read.
+    fn prompt_vote(&self, participant: &str, choice: &str) -> Vote {
+        print!(
+            "{}: Choice is {}. Accept, abstain, or veto? (Y/a/v) ",
+            participant, choice
+        );
+        match self.read_answer().as_deref() {
+            Some("" | "y" | "Y") => Vote::Accept,
+            Some("a" | "A") => Vote::Abstain,
+            _ => Vote::Veto,
+        }
+    }
+
+    /// Return `true` once an answer could not be read from stdin.
+    fn is_aborted(&self) -> bool {
+        self.aborted.get()
     }
 }
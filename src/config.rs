@@ -15,57 +15,607 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
 //! This module defines the rpick configuration.
 //!
 //! The configuration defines the pick categories, their algorithms, and their choices.
-use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
 use std::error;
-use std::fs::{File, OpenOptions};
-use std::io::{BufReader, Write};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
 
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Local};
+use fs2::FileExt;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::availability::{Availability, Window};
+use crate::group::Group;
+
+mod edit;
+mod layered;
+
+pub use layered::{find_project_config, read_layered, ConfigSources, LayeredConfig};
 
 /// Return the user's config as a BTreeMap.
 ///
+/// The format of the file is chosen by its extension, as [`Format::from_path`] does. Only this one
+/// file is read, and any files it includes are ignored; see [`read_layered`] to read those too.
+///
 /// # Arguments
 ///
-/// * `config_file_path` - A filesystem path to a YAML file that should be read.
+/// * `config_file_path` - A filesystem path to a config file that should be read.
 ///
 /// # Returns
 ///
-/// Returns a mapping of YAML to [`ConfigCategory`]'s, or an Error.
+/// Returns a mapping of category names to [`ConfigCategory`]'s, or an Error.
 pub fn read_config(
     config_file_path: &str,
 ) -> Result<BTreeMap<String, ConfigCategory>, Box<dyn error::Error>> {
-    let f = File::open(&config_file_path)?;
-    let reader = BufReader::new(f);
+    read_config_as(config_file_path, Format::from_path(config_file_path))
+}
 
-    let config: BTreeMap<String, ConfigCategory> = serde_yaml::from_reader(reader)?;
-    Ok(config)
+/// Return the user's config as a BTreeMap, reading it in the given format.
+///
+/// # Arguments
+///
+/// * `config_file_path` - A filesystem path to a config file that should be read.
+/// * `format` - The format the config file is written in.
+///
+/// # Returns
+///
+/// Returns a mapping of category names to [`ConfigCategory`]'s, or an Error.
+pub fn read_config_as(
+    config_file_path: &str,
+    format: Format,
+) -> Result<BTreeMap<String, ConfigCategory>, Box<dyn error::Error>> {
+    format.parse(&fs::read_to_string(config_file_path)?)
 }
 
 /// Save the data from the given BTreeMap to the user's config file.
 ///
+/// The format of the file is chosen by its extension, as [`Format::from_path`] does. The config is
+/// written to a temporary file which then replaces the config file, so the config file is never
+/// left half written.
+///
 /// # Arguments
 ///
 /// * `config_file_path` - A filesystem path that the config should be written to.
-/// * `config` - The config that should be serialized as YAML.
+/// * `config` - The config that should be serialized.
 pub fn write_config(
     config_file_path: &str,
     config: BTreeMap<String, ConfigCategory>,
 ) -> Result<(), Box<dyn error::Error>> {
-    let mut f = OpenOptions::new()
+    write_config_as(
+        config_file_path,
+        config,
+        Format::from_path(config_file_path),
+    )
+}
+
+/// Save the data from the given BTreeMap to the user's config file, in the given format.
+///
+/// # Arguments
+///
+/// * `config_file_path` - A filesystem path that the config should be written to.
+/// * `config` - The config that should be serialized.
+/// * `format` - The format the config should be written in.
+pub fn write_config_as(
+    config_file_path: &str,
+    config: BTreeMap<String, ConfigCategory>,
+    format: Format,
+) -> Result<(), Box<dyn error::Error>> {
+    let contents = format.serialize(&config)?;
+
+    write_atomically(config_file_path, &contents)?;
+    Ok(())
+}
+
+/// Copy the config file at one path to another path, in another format.
+///
+/// Only the one file is converted, and its ```include``` setting is copied as it is. The copy is
+/// read back before it is written, to make sure that it holds exactly the same settings as the
+/// original.
+///
+/// # Arguments
+///
+/// * `source` - A filesystem path to the config file that should be converted.
+/// * `source_format` - The format the config file is written in.
+/// * `destination` - A filesystem path that the copy should be written to. It must not exist yet.
+/// * `destination_format` - The format the copy should be written in.
+///
+/// # Returns
+///
+/// Returns [`ConfigError::DestinationExists`] if there is already a file at the destination, or
+/// [`ConfigError::LossyConversion`] if the destination format can't hold the config's settings.
+pub fn convert_config(
+    source: &str,
+    source_format: Format,
+    destination: &str,
+    destination_format: Format,
+) -> Result<(), Box<dyn error::Error>> {
+    let read = || source_format.parse_document(&fs::read_to_string(source)?);
+    let document = read().map_err(|error| ConfigError::Read {
+        path: source.to_string(),
+        source: error,
+    })?;
+
+    let contents = destination_format
+        .serialize_document(&document)
+        .map_err(|error| {
+            format!(
+                "Unable to convert the config to {}: {}",
+                destination_format, error
+            )
+        })?;
+    if destination_format.parse_document(&contents).ok().as_ref() != Some(&document) {
+        return Err(ConfigError::LossyConversion(destination_format).into());
+    }
+
+    let mut f = match OpenOptions::new()
         .write(true)
-        .create(true)
-        .truncate(true)
-        .open(&config_file_path)?;
-    let yaml = serde_yaml::to_string(&config).unwrap();
+        .create_new(true)
+        .open(destination)
+    {
+        Ok(f) => f,
+        Err(error) if error.kind() == ErrorKind::AlreadyExists => {
+            return Err(ConfigError::DestinationExists(destination.to_string()).into())
+        }
+        Err(error) => return Err(error.into()),
+    };
+    f.write_all(contents.as_bytes())?;
+    Ok(())
+}
 
-    f.write_all(&yaml.into_bytes())?;
+/// The file formats that the config can be written in.
+///
+/// Every format holds the same [`ConfigCategory`]'s, so a config can be converted from one format
+/// to another without losing any settings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    /// Return the format that a config file at the given path is expected to use.
+    ///
+    /// Files ending in ```.json``` are JSON and files ending in ```.toml``` are TOML. Anything else
+    /// is YAML, which is the format of the default ```rpick.yml```.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A filesystem path to a config file.
+    pub fn from_path(path: &str) -> Format {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("json") => Format::Json,
+            Some("toml") => Format::Toml,
+            _ => Format::Yaml,
+        }
+    }
+
+    /// Return the file extension for this format, without a leading dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Toml => "toml",
+            Format::Yaml => "yml",
+        }
+    }
+
+    /// Parse a config written in this format. Any ```include``` setting is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - The text of the config.
+    ///
+    /// # Returns
+    ///
+    /// Returns a mapping of category names to [`ConfigCategory`]'s, or an Error.
+    pub fn parse(
+        self,
+        contents: &str,
+    ) -> Result<BTreeMap<String, ConfigCategory>, Box<dyn error::Error>> {
+        Ok(self.parse_document(contents)?.categories)
+    }
+
+    /// Serialize the given config in this format.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The config that should be serialized.
+    pub fn serialize(
+        self,
+        config: &BTreeMap<String, ConfigCategory>,
+    ) -> Result<String, Box<dyn error::Error>> {
+        self.serialize_document(&ConfigDocument {
+            categories: config.clone(),
+            ..ConfigDocument::default()
+        })
+    }
+
+    /// Parse a whole config file written in this format, including its ```include``` setting.
+    fn parse_document(self, contents: &str) -> Result<ConfigDocument, Box<dyn error::Error>> {
+        Ok(match self {
+            Format::Json => serde_json::from_str(contents)?,
+            Format::Toml => toml::from_str(contents)?,
+            Format::Yaml => serde_yaml::from_str(contents)?,
+        })
+    }
+
+    /// Serialize a whole config file in this format, including its ```include``` setting.
+    fn serialize_document(
+        self,
+        document: &ConfigDocument,
+    ) -> Result<String, Box<dyn error::Error>> {
+        Ok(match self {
+            Format::Json => format!("{}\n", serde_json::to_string_pretty(document)?),
+            Format::Toml => toml::to_string(document)?,
+            Format::Yaml => serde_yaml::to_string(document)?,
+        })
+    }
+}
+
+/// Everything that a single config file holds.
+///
+/// # Attributes
+///
+/// * `include` - Paths to other config files whose categories should be read along with this
+///   file's. Relative paths are relative to the directory of this file.
+/// * `separate_state` - If `true`, the changes that picking makes to this file's categories are
+///   kept in the state file rather than in this file. See [`crate::state`].
+/// * `categories` - A mapping of category names to [`ConfigCategory`]'s.
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct ConfigDocument {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    include: Vec<String>,
+    #[serde(skip_serializing_if = "is_false")]
+    separate_state: bool,
+    #[serde(flatten)]
+    categories: BTreeMap<String, ConfigCategory>,
+}
+
+/// This is written by hand rather than derived with ```#[serde(flatten)]```, since flattening
+/// loses the location of errors in the categories.
+impl<'de> Deserialize<'de> for ConfigDocument {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DocumentVisitor;
+
+        impl<'de> Visitor<'de> for DocumentVisitor {
+            type Value = ConfigDocument;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a map of category names to categories")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ConfigDocument, A::Error> {
+                let mut document = ConfigDocument::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == "include" {
+                        document.include = map.next_value()?;
+                    } else if key == "separate_state" {
+                        document.separate_state = map.next_value()?;
+                    } else {
+                        let category = map.next_value()?;
+                        document.categories.insert(key, category);
+                    }
+                }
+                Ok(document)
+            }
+        }
+
+        deserializer.deserialize_map(DocumentVisitor)
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Format::Json => write!(f, "json"),
+            Format::Toml => write!(f, "toml"),
+            Format::Yaml => write!(f, "yaml"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "toml" => Ok(Format::Toml),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            _ => Err(ConfigError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+/// Replace the contents of the given file by writing them to a temporary file in the same directory
+/// and renaming it over the original. If the process dies partway through, the original file is
+/// left as it was.
+///
+/// If the path is a symlink, the file it points to is replaced rather than the link itself, and an
+/// existing file's permissions are kept.
+///
+/// # Arguments
+///
+/// * `path` - A filesystem path to the file that should be written.
+/// * `contents` - The new contents of the file.
+pub(crate) fn write_atomically(path: &str, contents: &str) -> io::Result<()> {
+    let path = match fs::canonicalize(path) {
+        Ok(path) => path,
+        Err(error) if error.kind() == ErrorKind::NotFound => PathBuf::from(path),
+        Err(error) => return Err(error),
+    };
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut f = tempfile::NamedTempFile::new_in(dir)?;
+    f.write_all(contents.as_bytes())?;
+    if let Ok(metadata) = fs::metadata(&path) {
+        f.as_file().set_permissions(metadata.permissions())?;
+    }
+    f.as_file().sync_all()?;
+    f.persist(&path).map_err(|error| error.error)?;
     Ok(())
 }
 
+/// A config file that is locked for the exclusive use of this process while it reads, changes, and
+/// saves the config.
+///
+/// The lock is advisory: it keeps other rpick processes that use a ConfigFile from changing the
+/// config at the same time, and it is released when the ConfigFile is dropped. The lock is held on
+/// a separate file next to the config (```rpick.yml.lock``` for ```rpick.yml```), since the config
+/// file itself is replaced whenever it is saved. The lock file is removed when the lock is
+/// released.
+///
+/// A ConfigFile that is only [opened](ConfigFile::open) takes the lock when it is first written,
+/// so config files that are only read never need a lock file.
+///
+/// Other programs, or rpick on another machine sharing a synced config, do not know about the
+/// lock. To keep from overwriting their changes, the ConfigFile remembers what the config file
+/// contained when it was read, and refuses to save if the contents have changed since.
+pub struct ConfigFile {
+    /// The path to the config file.
+    path: String,
+    /// The format the config file is written in.
+    format: Format,
+    /// The lock on the config file, or `None` if it has not been locked yet.
+    lock: Option<LockFile>,
+    /// The contents of the config file when it was last read or written, or `None` if it did not
+    /// exist.
+    contents: Option<String>,
+    /// The config file's ```include``` setting, which is kept when the config is saved.
+    include: Vec<String>,
+    /// The config file's ```separate_state``` setting, which is kept when the config is saved.
+    separate_state: bool,
+}
+
+impl ConfigFile {
+    /// Lock the config file at the given path.
+    ///
+    /// The config file itself does not have to exist yet. Its format is chosen by its extension, as
+    /// [`Format::from_path`] does, and can be changed with [`ConfigFile::set_format`].
+    ///
+    /// # Arguments
+    ///
+    /// * `config_file_path` - A filesystem path to the config file.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`ConfigError::Locked`] if another process holds the lock.
+    pub fn lock(config_file_path: &str) -> Result<ConfigFile, Box<dyn error::Error>> {
+        let mut config_file = ConfigFile::open(config_file_path);
+        config_file.lock = Some(LockFile::acquire(config_file_path)?);
+        Ok(config_file)
+    }
+
+    /// Open the config file at the given path without locking it. It is locked when it is first
+    /// written.
+    ///
+    /// The config file itself does not have to exist yet. Its format is chosen by its extension, as
+    /// [`Format::from_path`] does, and can be changed with [`ConfigFile::set_format`].
+    ///
+    /// # Arguments
+    ///
+    /// * `config_file_path` - A filesystem path to the config file.
+    pub fn open(config_file_path: &str) -> ConfigFile {
+        ConfigFile {
+            path: config_file_path.to_string(),
+            format: Format::from_path(config_file_path),
+            lock: None,
+            contents: None,
+            include: vec![],
+            separate_state: false,
+        }
+    }
+
+    /// Read and write the config file in the given format, rather than the one its extension
+    /// suggests.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The format the config file is written in.
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+
+    /// Read the config, and remember its contents so that later changes can be detected.
+    ///
+    /// # Returns
+    ///
+    /// Returns a mapping of category names to [`ConfigCategory`]'s, or an Error.
+    pub fn read(&mut self) -> Result<BTreeMap<String, ConfigCategory>, Box<dyn error::Error>> {
+        let contents = fs::read_to_string(&self.path)?;
+        let document = self.format.parse_document(&contents)?;
+        self.contents = Some(contents);
+        self.include = document.include;
+        self.separate_state = document.separate_state;
+        Ok(document.categories)
+    }
+
+    /// Return the paths of the other config files that this one includes, as they were written in
+    /// the config file when it was read.
+    pub fn includes(&self) -> &[String] {
+        &self.include
+    }
+
+    /// Return `true` if the config file asked for the changes that picking makes to its categories
+    /// to be kept in the state file rather than in the config file.
+    pub fn separate_state(&self) -> bool {
+        self.separate_state
+    }
+
+    /// Save the given config.
+    ///
+    /// Unlike [`write_config`], this keeps a YAML config file's comments, formatting, order of
+    /// categories, and settings that were left out in favor of their defaults. Only the parts of
+    /// the categories that changed since the config was read are rewritten. JSON and TOML config
+    /// files are serialized as a whole.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`ConfigError::Modified`] without saving if the config file has changed since it was
+    /// last read or written by this ConfigFile. If it was never read, the config file must still
+    /// not exist. Returns [`ConfigError::Locked`] if the config file was not locked yet and
+    /// another process holds the lock.
+    pub fn write(
+        &mut self,
+        config: BTreeMap<String, ConfigCategory>,
+    ) -> Result<(), Box<dyn error::Error>> {
+        if self.lock.is_none() {
+            self.lock = Some(LockFile::acquire(&self.path)?);
+        }
+        let current = match fs::read_to_string(&self.path) {
+            Ok(contents) => Some(contents),
+            Err(error) if error.kind() == ErrorKind::NotFound => None,
+            Err(error) => return Err(error.into()),
+        };
+        if current != self.contents {
+            return Err(ConfigError::Modified(self.path.clone()).into());
+        }
+
+        let document = ConfigDocument {
+            include: self.include.clone(),
+            separate_state: self.separate_state,
+            categories: config,
+        };
+        let contents = match (&self.contents, self.format) {
+            (Some(contents), Format::Yaml) => edit::update_yaml(contents, &document)?,
+            _ => self.format.serialize_document(&document)?,
+        };
+        write_atomically(&self.path, &contents)?;
+        self.contents = Some(contents);
+        Ok(())
+    }
+}
+
+/// An advisory lock on a config file, which is held on a separate file next to it.
+struct LockFile {
+    /// The path to the lock file.
+    path: PathBuf,
+    /// The open lock file, which holds the lock for as long as it is open.
+    _file: File,
+}
+
+impl LockFile {
+    /// Take the lock for the given config file, on a file named after it with ```.lock``` added.
+    ///
+    /// # Arguments
+    ///
+    /// * `config_file_path` - A filesystem path to the config file.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`ConfigError::Locked`] if another process holds the lock.
+    fn acquire(config_file_path: &str) -> Result<LockFile, Box<dyn error::Error>> {
+        let mut path = Path::new(config_file_path).as_os_str().to_owned();
+        path.push(".lock");
+        let path = PathBuf::from(path);
+        loop {
+            let file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)?;
+            if let Err(error) = file.try_lock_exclusive() {
+                if error.kind() == fs2::lock_contended_error().kind() {
+                    return Err(ConfigError::Locked.into());
+                }
+                return Err(error.into());
+            }
+            // The process that held the lock may have removed the lock file after it was opened
+            // here, and then nobody else would see this lock. Try again with a new lock file.
+            if is_same_file(&file, &path)? {
+                return Ok(LockFile { path, _file: file });
+            }
+        }
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        // The lock file is removed while it is still locked, so that no other process can take
+        // the lock on it in between. If it can't be removed, it is only left behind, empty.
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Return `true` if the given open file is still the one found at the given path.
+#[cfg(unix)]
+fn is_same_file(file: &File, path: &Path) -> io::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let open = file.metadata()?;
+    match fs::metadata(path) {
+        Ok(found) => Ok(open.dev() == found.dev() && open.ino() == found.ino()),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Return `true` if the given open file is still the one found at the given path. Files can't be
+/// removed while they are open here, so it is enough for the path to exist.
+#[cfg(not(unix))]
+fn is_same_file(_file: &File, path: &Path) -> io::Result<bool> {
+    Ok(path.exists())
+}
+
+/// Check the given config for problems that would keep rpick from picking sensibly.
+///
+/// This goes beyond the checks made while the config is read, which only ensure that the config
+/// has the right shape. See [`ConfigCategory::validate`] for the problems that are looked for.
+///
+/// # Arguments
+///
+/// * `config` - A mapping of category names to [`ConfigCategory`]'s.
+///
+/// # Returns
+///
+/// Returns a [`Diagnostic`] for each problem found, ordered by category. The list is empty if no
+/// problems were found.
+pub fn validate(config: &BTreeMap<String, ConfigCategory>) -> Vec<Diagnostic> {
+    config
+        .iter()
+        .flat_map(|(name, category)| category.validate(name))
+        .collect()
+}
+
 /// A category of items that can be chosen from.
 ///
 /// Each variant of this Enum maps to one of the supported algorithms.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "model")]
@@ -75,7 +625,14 @@ pub enum ConfigCategory {
     /// # Attributes
     ///
     /// * `choices` - The list of choices to pick from.
-    Even { choices: Vec<String> },
+    /// * `group` - The people who decide together whether to accept a choice, if any. See
+    ///   [`crate::group`].
+    Even {
+        #[serde(serialize_with = "serialize_choices")]
+        choices: Vec<Choice>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<Group>,
+    },
     /// The Gaussian variant uses a
     /// [Gaussian distribution](https://en.wikipedia.org/wiki/Normal_distribution) to prefer choices
     /// near the beginning of the list of choices over those at the end. Once a choice has been
@@ -86,10 +643,15 @@ pub enum ConfigCategory {
     /// * `stddev_scaling_factor` - This is used to derive the standard deviation; the standard
     ///   deviation is the length of the list of choices, divided by this scaling factor.
     /// * `choices` - The list of choices to pick from.
+    /// * `group` - The people who decide together whether to accept a choice, if any. See
+    ///   [`crate::group`].
     Gaussian {
         #[serde(default = "default_stddev_scaling_factor")]
         stddev_scaling_factor: f64,
-        choices: Vec<String>,
+        #[serde(serialize_with = "serialize_choices")]
+        choices: Vec<Choice>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<Group>,
     },
     /// The Inventory variant uses a weighted distribution to pick items, with each items chances
     /// being tied to how many tickets it has. When a choice is accepted, that choice's ticket
@@ -98,7 +660,17 @@ pub enum ConfigCategory {
     /// # Attributes
     ///
     /// * `choices` - The list of choices to pick from.
-    Inventory { choices: Vec<InventoryChoice> },
+    /// * `group` - The people who decide together whether to accept a choice, if any. See
+    ///   [`crate::group`].
+    /// * `restock` - How the choices get tickets back, for the choices that do not have a restock
+    ///   policy of their own. If this is `None`, tickets are never given back.
+    Inventory {
+        choices: Vec<InventoryChoice>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<Group>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        restock: Option<Restock>,
+    },
     /// The Lru variant picks the Least Recently Used item from the list of choices. The least
     /// recently used choice is found at the beginning of the list. Once a choice has been
     /// accepted, it is moved to the end of the list.
@@ -106,8 +678,46 @@ pub enum ConfigCategory {
     /// # Attributes
     ///
     /// * `choices` - The list of choices to pick from.
+    /// * `group` - The people who decide together whether to accept a choice, if any. See
+    ///   [`crate::group`].
     #[serde(rename = "lru")]
-    Lru { choices: Vec<String> },
+    Lru {
+        #[serde(serialize_with = "serialize_choices")]
+        choices: Vec<Choice>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<Group>,
+    },
+    /// The Recency variant uses a weighted distribution to pick items, with each item's weight
+    /// growing with the time since it was last picked. A choice's weight is the number of whole
+    /// hours since it was last picked, plus 1. Choices that have never been picked weigh as much as
+    /// the one that has waited the longest. Choices that are cooling down cannot be picked at all.
+    /// When a choice is accepted, its last picked time is set to the time of the pick.
+    ///
+    /// # Attributes
+    ///
+    /// * `choices` - The list of choices to pick from.
+    /// * `group` - The people who decide together whether to accept a choice, if any. See
+    ///   [`crate::group`].
+    Recency {
+        choices: Vec<RecencyChoice>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<Group>,
+    },
+    /// The Rotation variant shares picks out fairly, such as for assigning duties. It counts how
+    /// many times each choice has been assigned, and always offers one of the choices with the
+    /// fewest assignments for its capacity first, breaking ties at random. When a choice is
+    /// accepted, its assignments are increased by 1.
+    ///
+    /// # Attributes
+    ///
+    /// * `choices` - The list of choices to pick from.
+    /// * `group` - The people who decide together whether to accept a choice, if any. See
+    ///   [`crate::group`].
+    Rotation {
+        choices: Vec<RotationChoice>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<Group>,
+    },
     /// The Lottery variant uses a weighted distribution to pick items, with each items chances
     /// being tied to how many tickets it has. When a choice is accepted, that choice's ticket
     /// count is set to 0, and every choice not chosen receives its weight in additional tickets.
@@ -115,13 +725,1034 @@ pub enum ConfigCategory {
     /// # Attributes
     ///
     /// * `choices` - The list of choices to pick from.
-    Lottery { choices: Vec<LotteryChoice> },
+    /// * `group` - The people who decide together whether to accept a choice, if any. See
+    ///   [`crate::group`].
+    Lottery {
+        choices: Vec<LotteryChoice>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<Group>,
+    },
     /// The Weighted variant is a simple weighted distribution.
     ///
     /// # Attributes
     ///
     /// * `choices` - The list of choices to pick from.
-    Weighted { choices: Vec<WeightedChoice> },
+    /// * `group` - The people who decide together whether to accept a choice, if any. See
+    ///   [`crate::group`].
+    Weighted {
+        choices: Vec<WeightedChoice>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<Group>,
+    },
+}
+
+impl ConfigCategory {
+    /// Construct a new category with no choices, using the named model.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The name of the model, as it would be spelled in the config.
+    pub fn new(model: &str) -> Result<ConfigCategory, ConfigError> {
+        match model {
+            "even" => Ok(ConfigCategory::Even {
+                choices: vec![],
+                group: None,
+            }),
+            "gaussian" => Ok(ConfigCategory::Gaussian {
+                stddev_scaling_factor: default_stddev_scaling_factor(),
+                choices: vec![],
+                group: None,
+            }),
+            "inventory" => Ok(ConfigCategory::Inventory {
+                choices: vec![],
+                group: None,
+                restock: None,
+            }),
+            "lottery" => Ok(ConfigCategory::Lottery {
+                choices: vec![],
+                group: None,
+            }),
+            "lru" => Ok(ConfigCategory::Lru {
+                choices: vec![],
+                group: None,
+            }),
+            "recency" => Ok(ConfigCategory::Recency {
+                choices: vec![],
+                group: None,
+            }),
+            "rotation" => Ok(ConfigCategory::Rotation {
+                choices: vec![],
+                group: None,
+            }),
+            "weighted" => Ok(ConfigCategory::Weighted {
+                choices: vec![],
+                group: None,
+            }),
+            _ => Err(ConfigError::UnknownModel(model.to_string())),
+        }
+    }
+
+    /// Add a choice to this category.
+    ///
+    /// The Gaussian and Lru models add the new choice to the beginning of the list, since a choice
+    /// that has never been picked is the least recently used. The other models add it to the end.
+    /// The Rotation model starts the new choice with as many assignments as the choice with the
+    /// smallest share of the picks so far, so that it joins the rotation rather than being picked
+    /// every time until it catches up.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the new choice.
+    /// * `weight` - The choice's weight, for models that support one. Defaults to 1.
+    /// * `tickets` - The choice's starting number of tickets, for models that support them.
+    ///   Defaults to 1.
+    pub fn add_choice(
+        &mut self,
+        name: &str,
+        weight: Option<u64>,
+        tickets: Option<u64>,
+    ) -> Result<(), ConfigError> {
+        if self.choice_names().contains(&name) {
+            return Err(ConfigError::DuplicateChoice(name.to_string()));
+        }
+        let model = self.model();
+        let unsupported = |field| ConfigError::UnsupportedField { model, field };
+        let name = name.to_string();
+
+        match self {
+            ConfigCategory::Even { choices, .. } => {
+                if weight.is_some() {
+                    return Err(unsupported("weight"));
+                }
+                if tickets.is_some() {
+                    return Err(unsupported("tickets"));
+                }
+                choices.push(Choice::from(name));
+            }
+            ConfigCategory::Gaussian { choices, .. } | ConfigCategory::Lru { choices, .. } => {
+                if weight.is_some() {
+                    return Err(unsupported("weight"));
+                }
+                if tickets.is_some() {
+                    return Err(unsupported("tickets"));
+                }
+                choices.insert(0, Choice::from(name));
+            }
+            ConfigCategory::Inventory { choices, .. } => {
+                if weight.is_some() {
+                    return Err(unsupported("weight"));
+                }
+                choices.push(InventoryChoice {
+                    name,
+                    tickets: tickets.unwrap_or_else(default_weight),
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
+                });
+            }
+            ConfigCategory::Lottery { choices, .. } => {
+                choices.push(LotteryChoice {
+                    name,
+                    tickets: tickets.unwrap_or_else(default_weight),
+                    weight: weight.unwrap_or_else(default_weight),
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
+                });
+            }
+            ConfigCategory::Recency { choices, .. } => {
+                if weight.is_some() {
+                    return Err(unsupported("weight"));
+                }
+                if tickets.is_some() {
+                    return Err(unsupported("tickets"));
+                }
+                choices.push(RecencyChoice {
+                    name,
+                    last_picked: None,
+                    cooldown: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    availability: Default::default(),
+                });
+            }
+            ConfigCategory::Rotation { choices, .. } => {
+                if weight.is_some() {
+                    return Err(unsupported("weight"));
+                }
+                if tickets.is_some() {
+                    return Err(unsupported("tickets"));
+                }
+                let assignments = choices
+                    .iter()
+                    .filter(|c| c.capacity > 0)
+                    .map(|c| c.assignments / c.capacity)
+                    .min()
+                    .unwrap_or(0);
+                choices.push(RotationChoice {
+                    name,
+                    assignments,
+                    capacity: default_weight(),
+                    tags: vec![],
+                    details: Default::default(),
+                    availability: Default::default(),
+                });
+            }
+            ConfigCategory::Weighted { choices, .. } => {
+                if tickets.is_some() {
+                    return Err(unsupported("tickets"));
+                }
+                choices.push(WeightedChoice {
+                    name,
+                    weight: weight.unwrap_or_else(default_weight),
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the named choice from this category.
+    pub fn remove_choice(&mut self, name: &str) -> Result<(), ConfigError> {
+        let index = self.choice_index(name)?;
+        match self {
+            ConfigCategory::Even { choices, .. }
+            | ConfigCategory::Gaussian { choices, .. }
+            | ConfigCategory::Lru { choices, .. } => {
+                choices.remove(index);
+            }
+            ConfigCategory::Inventory { choices, .. } => {
+                choices.remove(index);
+            }
+            ConfigCategory::Lottery { choices, .. } => {
+                choices.remove(index);
+            }
+            ConfigCategory::Recency { choices, .. } => {
+                choices.remove(index);
+            }
+            ConfigCategory::Rotation { choices, .. } => {
+                choices.remove(index);
+            }
+            ConfigCategory::Weighted { choices, .. } => {
+                choices.remove(index);
+            }
+        }
+        Ok(())
+    }
+
+    /// Rename one of this category's choices, keeping its place and any other settings it has.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The current name of the choice.
+    /// * `new_name` - What the choice should be called from now on.
+    pub fn rename_choice(&mut self, name: &str, new_name: &str) -> Result<(), ConfigError> {
+        let index = self.choice_index(name)?;
+        if self.choice_names().contains(&new_name) {
+            return Err(ConfigError::DuplicateChoice(new_name.to_string()));
+        }
+        let new_name = new_name.to_string();
+        match self {
+            ConfigCategory::Even { choices, .. }
+            | ConfigCategory::Gaussian { choices, .. }
+            | ConfigCategory::Lru { choices, .. } => choices[index].name = new_name,
+            ConfigCategory::Inventory { choices, .. } => choices[index].name = new_name,
+            ConfigCategory::Lottery { choices, .. } => choices[index].name = new_name,
+            ConfigCategory::Recency { choices, .. } => choices[index].name = new_name,
+            ConfigCategory::Rotation { choices, .. } => choices[index].name = new_name,
+            ConfigCategory::Weighted { choices, .. } => choices[index].name = new_name,
+        }
+        Ok(())
+    }
+
+    /// Return the index of the named choice within this category's list of choices.
+    fn choice_index(&self, name: &str) -> Result<usize, ConfigError> {
+        self.choice_names()
+            .iter()
+            .position(|c| *c == name)
+            .ok_or_else(|| ConfigError::ChoiceNotFound(name.to_string()))
+    }
+
+    /// Return the name of this category's model, as it is spelled in the config.
+    pub fn model(&self) -> &'static str {
+        match self {
+            ConfigCategory::Even { .. } => "even",
+            ConfigCategory::Gaussian { .. } => "gaussian",
+            ConfigCategory::Inventory { .. } => "inventory",
+            ConfigCategory::Lottery { .. } => "lottery",
+            ConfigCategory::Lru { .. } => "lru",
+            ConfigCategory::Recency { .. } => "recency",
+            ConfigCategory::Rotation { .. } => "rotation",
+            ConfigCategory::Weighted { .. } => "weighted",
+        }
+    }
+
+    /// Return the group that decides whether to accept this category's choices, if it has one.
+    pub fn group(&self) -> Option<&Group> {
+        match self {
+            ConfigCategory::Even { group, .. }
+            | ConfigCategory::Gaussian { group, .. }
+            | ConfigCategory::Inventory { group, .. }
+            | ConfigCategory::Lottery { group, .. }
+            | ConfigCategory::Lru { group, .. }
+            | ConfigCategory::Recency { group, .. }
+            | ConfigCategory::Rotation { group, .. }
+            | ConfigCategory::Weighted { group, .. } => group.as_ref(),
+        }
+    }
+
+    /// Check this category for problems that would keep rpick from picking from it sensibly.
+    ///
+    /// These problems are errors:
+    ///
+    /// * The category has no choices.
+    /// * A choice name appears more than once.
+    /// * The `stddev_scaling_factor` is not a number greater than 0.
+    /// * Every choice has a weight of 0.
+    /// * Every choice has a capacity of 0.
+    /// * A restock policy has a period of 0.
+    /// * The category's group has no participants.
+    ///
+    /// These problems are warnings, since the category is usable but may not behave as expected:
+    ///
+    /// * Every choice is out of tickets, and no restock policy will give any back. Picks will fail
+    ///   until tickets are added.
+    /// * A choice has a weight of 0, so it can never be picked.
+    /// * A choice has a capacity of 0, so it can never be picked.
+    /// * A choice has an availability window that ends before it starts, which contains no time at
+    ///   all.
+    /// * A participant is listed more than once in the category's group, so they are asked twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the category, which is recorded in the diagnostics.
+    pub fn validate(&self, name: &str) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        let mut diagnose = |choice: Option<&str>, problem: Problem| {
+            diagnostics.push(Diagnostic {
+                category: name.to_string(),
+                choice: choice.map(String::from),
+                severity: problem.severity(),
+                problem,
+            })
+        };
+
+        let names = self.choice_names();
+        if names.is_empty() {
+            diagnose(None, Problem::NoChoices);
+        }
+        let mut seen = BTreeSet::new();
+        let mut reported = BTreeSet::new();
+        for choice in names.iter() {
+            if !seen.insert(choice) && reported.insert(choice) {
+                diagnose(Some(choice), Problem::DuplicateChoice);
+            }
+        }
+        if let Some(group) = self.group() {
+            if group.participants.is_empty() {
+                diagnose(None, Problem::NoParticipants);
+            }
+            let mut seen = BTreeSet::new();
+            let mut reported = BTreeSet::new();
+            for participant in group.participants.iter() {
+                if !seen.insert(participant) && reported.insert(participant) {
+                    diagnose(None, Problem::DuplicateParticipant(participant.clone()));
+                }
+            }
+        }
+
+        match self {
+            ConfigCategory::Gaussian {
+                stddev_scaling_factor,
+                ..
+            } => {
+                if !(stddev_scaling_factor.is_finite() && *stddev_scaling_factor > 0.0) {
+                    diagnose(
+                        None,
+                        Problem::InvalidStddevScalingFactor(*stddev_scaling_factor),
+                    );
+                }
+            }
+            ConfigCategory::Inventory {
+                choices, restock, ..
+            } => {
+                if restock.as_ref().is_some_and(Restock::has_zero_period) {
+                    diagnose(None, Problem::ZeroRestockPeriod);
+                }
+                for choice in choices {
+                    if choice
+                        .restock
+                        .as_ref()
+                        .is_some_and(Restock::has_zero_period)
+                    {
+                        diagnose(Some(&choice.name), Problem::ZeroRestockPeriod);
+                    }
+                }
+                let restocks = restock.is_some() || choices.iter().any(|c| c.restock.is_some());
+                if !restocks && !choices.is_empty() && choices.iter().all(|c| c.tickets == 0) {
+                    diagnose(None, Problem::AllTicketsExhausted);
+                }
+            }
+            ConfigCategory::Lottery { choices, .. } => {
+                if !choices.is_empty() && choices.iter().all(|c| c.tickets == 0) {
+                    if choices.iter().all(|c| c.weight == 0) {
+                        diagnose(None, Problem::AllWeightsZero);
+                    } else {
+                        diagnose(None, Problem::AllTicketsExhausted);
+                    }
+                }
+            }
+            ConfigCategory::Weighted { choices, .. } => {
+                if !choices.is_empty() && choices.iter().all(|c| c.weight == 0) {
+                    diagnose(None, Problem::AllWeightsZero);
+                } else {
+                    for choice in choices.iter().filter(|c| c.weight == 0) {
+                        diagnose(Some(&choice.name), Problem::ZeroWeight);
+                    }
+                }
+            }
+            ConfigCategory::Rotation { choices, .. } => {
+                if !choices.is_empty() && choices.iter().all(|c| c.capacity == 0) {
+                    diagnose(None, Problem::AllCapacitiesZero);
+                } else {
+                    for choice in choices.iter().filter(|c| c.capacity == 0) {
+                        diagnose(Some(&choice.name), Problem::ZeroCapacity);
+                    }
+                }
+            }
+            ConfigCategory::Even { .. }
+            | ConfigCategory::Lru { .. }
+            | ConfigCategory::Recency { .. } => {}
+        }
+        for (choice, details) in self.choice_details() {
+            for field in details.unknown_fields() {
+                let suggestion = CHOICE_FIELDS
+                    .iter()
+                    .filter(|known| **known != field && edit_distance(known, field) <= 2)
+                    .min_by_key(|known| edit_distance(known, field))
+                    .map(|known| known.to_string());
+                diagnose(
+                    Some(choice),
+                    Problem::UnknownField {
+                        field: field.to_string(),
+                        suggestion,
+                    },
+                );
+            }
+            for attribute in details.attributes.keys() {
+                if CHOICE_FIELDS.contains(&attribute.as_str()) {
+                    diagnose(
+                        Some(choice),
+                        Problem::AttributeNamedLikeField(attribute.clone()),
+                    );
+                }
+            }
+        }
+        for (choice, availability) in self.choice_availability() {
+            for window in availability
+                .available
+                .iter()
+                .chain(availability.unavailable.iter())
+                .filter(|w| w.is_backwards())
+            {
+                diagnose(Some(choice), Problem::BackwardsWindow(window.clone()));
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Return the names and tags of this category's choices, in the order they appear in the
+    /// config.
+    pub fn choice_tags(&self) -> Vec<(&str, &[String])> {
+        match self {
+            ConfigCategory::Even { choices, .. }
+            | ConfigCategory::Gaussian { choices, .. }
+            | ConfigCategory::Lru { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.tags[..]))
+                .collect(),
+            ConfigCategory::Inventory { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.tags[..]))
+                .collect(),
+            ConfigCategory::Lottery { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.tags[..]))
+                .collect(),
+            ConfigCategory::Recency { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.tags[..]))
+                .collect(),
+            ConfigCategory::Rotation { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.tags[..]))
+                .collect(),
+            ConfigCategory::Weighted { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.tags[..]))
+                .collect(),
+        }
+    }
+
+    /// Return the name and details of each of this category's choices, in the order they appear in
+    /// the config.
+    pub fn choice_details(&self) -> Vec<(&str, &ChoiceDetails)> {
+        match self {
+            ConfigCategory::Even { choices, .. }
+            | ConfigCategory::Gaussian { choices, .. }
+            | ConfigCategory::Lru { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.details))
+                .collect(),
+            ConfigCategory::Inventory { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.details))
+                .collect(),
+            ConfigCategory::Lottery { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.details))
+                .collect(),
+            ConfigCategory::Recency { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.details))
+                .collect(),
+            ConfigCategory::Rotation { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.details))
+                .collect(),
+            ConfigCategory::Weighted { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.details))
+                .collect(),
+        }
+    }
+
+    /// Return the details of the choice with the given name, or `None` if the category has no such
+    /// choice. This is handy for showing more about a choice once it has been picked.
+    ///
+    /// # Arguments
+    ///
+    /// * `choice` - The name of the choice.
+    pub fn details(&self, choice: &str) -> Option<&ChoiceDetails> {
+        self.choice_details()
+            .into_iter()
+            .find(|(name, _)| *name == choice)
+            .map(|(_, details)| details)
+    }
+
+    /// Return the name and availability of each of this category's choices, in the order they
+    /// appear in the config.
+    pub fn choice_availability(&self) -> Vec<(&str, &Availability)> {
+        match self {
+            ConfigCategory::Even { choices, .. }
+            | ConfigCategory::Gaussian { choices, .. }
+            | ConfigCategory::Lru { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.availability))
+                .collect(),
+            ConfigCategory::Inventory { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.availability))
+                .collect(),
+            ConfigCategory::Lottery { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.availability))
+                .collect(),
+            ConfigCategory::Recency { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.availability))
+                .collect(),
+            ConfigCategory::Rotation { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.availability))
+                .collect(),
+            ConfigCategory::Weighted { choices, .. } => choices
+                .iter()
+                .map(|c| (c.name.as_str(), &c.availability))
+                .collect(),
+        }
+    }
+
+    /// Give tickets back to the choices of an inventory category, according to the restock
+    /// policies of the category and its choices. A choice's own policy takes the place of the
+    /// category's. Categories of other models are left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time of the pick.
+    pub fn restock(&mut self, now: DateTime<Local>) {
+        if let ConfigCategory::Inventory {
+            choices, restock, ..
+        } = self
+        {
+            let exhausted = choices.iter().all(|c| c.tickets == 0);
+            for choice in choices.iter_mut() {
+                if let Some(policy) = choice.restock.clone().or_else(|| restock.clone()) {
+                    choice.restock(&policy, exhausted, now);
+                }
+            }
+        }
+    }
+
+    /// Return the name and preferences of each of this category's choices, in the order they
+    /// appear in the config, or `None` if the category's model doesn't take preferences.
+    pub fn choice_preferences(&self) -> Option<Vec<(&str, &BTreeMap<String, u64>)>> {
+        match self {
+            ConfigCategory::Lottery { choices, .. } => Some(
+                choices
+                    .iter()
+                    .map(|c| (c.name.as_str(), &c.preferences))
+                    .collect(),
+            ),
+            ConfigCategory::Weighted { choices, .. } => Some(
+                choices
+                    .iter()
+                    .map(|c| (c.name.as_str(), &c.preferences))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Return the names of this category's choices, in the order they appear in the config.
+    pub fn choice_names(&self) -> Vec<&str> {
+        match self {
+            ConfigCategory::Even { choices, .. }
+            | ConfigCategory::Gaussian { choices, .. }
+            | ConfigCategory::Lru { choices, .. } => {
+                choices.iter().map(|c| c.name.as_str()).collect()
+            }
+            ConfigCategory::Inventory { choices, .. } => {
+                choices.iter().map(|c| c.name.as_str()).collect()
+            }
+            ConfigCategory::Lottery { choices, .. } => {
+                choices.iter().map(|c| c.name.as_str()).collect()
+            }
+            ConfigCategory::Recency { choices, .. } => {
+                choices.iter().map(|c| c.name.as_str()).collect()
+            }
+            ConfigCategory::Rotation { choices, .. } => {
+                choices.iter().map(|c| c.name.as_str()).collect()
+            }
+            ConfigCategory::Weighted { choices, .. } => {
+                choices.iter().map(|c| c.name.as_str()).collect()
+            }
+        }
+    }
+}
+
+/// Define the errors that can be returned when changing the configuration.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("The category `{0}` already exists.")]
+    CategoryExists(String),
+    #[error("The category `{0}` was not found in the given config.")]
+    CategoryNotFound(String),
+    #[error("The category already has a choice named `{0}`.")]
+    DuplicateChoice(String),
+    #[error("The category has no choice named `{0}`.")]
+    ChoiceNotFound(String),
+    #[error(
+        "Unknown model `{0}`. The available models are even, gaussian, inventory, lottery, lru, \
+         recency, rotation, and weighted."
+    )]
+    UnknownModel(String),
+    #[error("Unknown config format `{0}`. The available formats are json, toml, and yaml.")]
+    UnknownFormat(String),
+    #[error("Will not overwrite the existing file at {0}.")]
+    DestinationExists(String),
+    #[error("Unable to convert the config to {0} without changing some of its settings.")]
+    LossyConversion(Format),
+    #[error("Error reading config file at {path}: {source}")]
+    Read {
+        path: String,
+        source: Box<dyn error::Error>,
+    },
+    #[error(
+        "The config file is in use by another rpick process. Please try again once that process \
+         is done."
+    )]
+    Locked,
+    #[error(
+        "The config file at {0} was changed by another program after rpick read it. rpick did not \
+         save its changes, so that the other program's changes are not lost. Please try again."
+    )]
+    Modified(String),
+    #[error("The {model} model does not support the `{field}` setting.")]
+    UnsupportedField {
+        model: &'static str,
+        field: &'static str,
+    },
+}
+
+/// A problem found by [`validate`].
+///
+/// # Attributes
+///
+/// * `category` - The name of the category with the problem.
+/// * `choice` - The name of the choice with the problem, if the problem is with a particular
+///   choice rather than the category as a whole.
+/// * `problem` - What is wrong.
+/// * `severity` - How serious the problem is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub category: String,
+    pub choice: Option<String>,
+    pub problem: Problem,
+    pub severity: Severity,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.choice {
+            Some(choice) => write!(
+                f,
+                "Problem with the choice `{}` in the category `{}`: {}",
+                choice, self.category, self.problem
+            ),
+            None => write!(
+                f,
+                "Problem with the category `{}`: {}",
+                self.category, self.problem
+            ),
+        }
+    }
+}
+
+/// The problems that [`validate`] can find.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Problem {
+    /// The category has no choices.
+    NoChoices,
+    /// The choice's name is used by more than one of the category's choices.
+    DuplicateChoice,
+    /// The category's `stddev_scaling_factor` is not a number greater than 0.
+    InvalidStddevScalingFactor(f64),
+    /// Every choice in the category has a weight of 0.
+    AllWeightsZero,
+    /// Every choice in the category is out of tickets.
+    AllTicketsExhausted,
+    /// The choice has a weight of 0.
+    ZeroWeight,
+    /// Every choice in the category has a capacity of 0.
+    AllCapacitiesZero,
+    /// The choice has a capacity of 0.
+    ZeroCapacity,
+    /// The choice has an availability window that ends before it starts.
+    BackwardsWindow(Window),
+    /// The restock policy's period is 0.
+    ZeroRestockPeriod,
+    /// The category's group has no participants.
+    NoParticipants,
+    /// The participant is listed more than once in the category's group.
+    DuplicateParticipant(String),
+    /// The choice has a field that its category's model doesn't use, such as a misspelled one.
+    /// `suggestion` is a known field with a similar name, if there is one.
+    UnknownField {
+        field: String,
+        suggestion: Option<String>,
+    },
+    /// The choice has an attribute with the name of a choice field, which is only shown and not
+    /// used to pick.
+    AttributeNamedLikeField(String),
+}
+
+impl Problem {
+    /// Return how serious this problem is.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Problem::AllTicketsExhausted
+            | Problem::ZeroWeight
+            | Problem::ZeroCapacity
+            | Problem::BackwardsWindow(_)
+            | Problem::DuplicateParticipant(_)
+            | Problem::UnknownField { .. }
+            | Problem::AttributeNamedLikeField(_) => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::NoChoices => write!(f, "There are no choices to pick from."),
+            Problem::DuplicateChoice => write!(f, "The choice appears more than once."),
+            Problem::InvalidStddevScalingFactor(factor) => write!(
+                f,
+                "The stddev_scaling_factor is {}, but it must be a number greater than 0.",
+                factor
+            ),
+            Problem::AllWeightsZero => write!(
+                f,
+                "Every choice has a weight of 0, so nothing can be picked."
+            ),
+            Problem::AllTicketsExhausted => write!(f, "Every choice is out of tickets."),
+            Problem::ZeroWeight => write!(
+                f,
+                "The choice has a weight of 0, so it can never be picked."
+            ),
+            Problem::AllCapacitiesZero => write!(
+                f,
+                "Every choice has a capacity of 0, so nothing can be picked."
+            ),
+            Problem::ZeroCapacity => write!(
+                f,
+                "The choice has a capacity of 0, so it can never be picked."
+            ),
+            Problem::BackwardsWindow(window) => write!(
+                f,
+                "The choice has an availability window {}, which ends before it starts.",
+                window
+            ),
+            Problem::ZeroRestockPeriod => write!(
+                f,
+                "The restock policy's period is 0, so it never gives any tickets."
+            ),
+            Problem::NoParticipants => write!(f, "The group has no participants to decide."),
+            Problem::DuplicateParticipant(participant) => write!(
+                f,
+                "The participant `{}` appears more than once in the group.",
+                participant
+            ),
+            Problem::UnknownField {
+                field,
+                suggestion: Some(suggestion),
+            } => write!(
+                f,
+                "The choice has a field `{}` that is not used, so it is ignored. Did you mean `{}`?",
+                field, suggestion
+            ),
+            Problem::UnknownField {
+                field,
+                suggestion: None,
+            } => write!(
+                f,
+                "The choice has a field `{}` that is not used, so it is ignored.",
+                field
+            ),
+            Problem::AttributeNamedLikeField(attribute) => write!(
+                f,
+                "The attribute `{}` has the name of a choice field, so it is only shown and not \
+                 used to pick.",
+                attribute
+            ),
+        }
+    }
+}
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The category is usable, but it may not behave as expected.
+    Warning,
+    /// rpick cannot pick from the category.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Represents an individual choice for the even, gaussian, and lru models.
+///
+/// In the config, a choice without tags, details, or availability can be written as just its name,
+/// and it is saved that way too, unless another choice in the same category has any of them.
+///
+/// # Attributes
+///
+/// * `name` - The name of the choice.
+/// * `tags` - Labels that the choice can be filtered by when picking. See [`crate::tags`].
+/// * `details` - A description, a link, and other notes about the choice, which are shown when it
+///   is offered.
+/// * `availability` - When the choice can be picked. See [`crate::availability`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Choice {
+    pub name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(flatten)]
+    pub details: ChoiceDetails,
+    #[serde(flatten)]
+    pub availability: Availability,
+}
+
+impl From<&str> for Choice {
+    fn from(name: &str) -> Choice {
+        Choice::from(name.to_string())
+    }
+}
+
+impl From<String> for Choice {
+    fn from(name: String) -> Choice {
+        Choice {
+            name,
+            tags: vec![],
+            details: ChoiceDetails::default(),
+            availability: Availability::default(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Choice {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// The fields of a choice that is written out in full.
+        #[derive(Deserialize)]
+        struct Fields {
+            name: String,
+            #[serde(default)]
+            tags: Vec<String>,
+            #[serde(flatten)]
+            details: ChoiceDetails,
+            #[serde(flatten)]
+            availability: Availability,
+        }
+
+        struct ChoiceVisitor;
+
+        impl<'de> Visitor<'de> for ChoiceVisitor {
+            type Value = Choice;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a choice name, or a map with a name, tags, details, and availability"
+                )
+            }
+
+            fn visit_str<E: serde::de::Error>(self, name: &str) -> Result<Choice, E> {
+                Ok(Choice::from(name))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Choice, A::Error> {
+                let fields = Fields::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(Choice {
+                    name: fields.name,
+                    tags: fields.tags,
+                    details: fields.details,
+                    availability: fields.availability,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(ChoiceVisitor)
+    }
+}
+
+/// Save a list of [`Choice`]'s as plain names if none of them have tags, details, or availability,
+/// and as maps otherwise, since TOML does not allow a list to mix strings with tables.
+fn serialize_choices<S: Serializer>(choices: &[Choice], serializer: S) -> Result<S::Ok, S::Error> {
+    if choices.iter().all(|choice| {
+        choice.tags.is_empty() && choice.details.is_empty() && choice.availability.is_empty()
+    }) {
+        serializer.collect_seq(choices.iter().map(|choice| &choice.name))
+    } else {
+        serializer.collect_seq(choices)
+    }
+}
+
+/// Optional details about a choice, for the people deciding whether to accept it. They are shown in
+/// the chance tables and when the choice is offered, and they are not used to pick.
+///
+/// In the config, these are written alongside the choice's name.
+///
+/// # Attributes
+///
+/// * `description` - A few words about the choice.
+/// * `url` - A link to more information, such as a menu or a ticket.
+/// * `attributes` - Any other notes, such as an address or a phone number, keyed by what they are.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChoiceDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+    /// The other fields that were written alongside the choice's name, so that
+    /// [`ConfigCategory::validate`] can point out misspelled ones. They are not saved.
+    #[serde(flatten, skip_serializing)]
+    unknown: UnknownFields,
+}
+
+impl ChoiceDetails {
+    /// Return `true` if there are no details.
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none() && self.url.is_none() && self.attributes.is_empty()
+    }
+
+    /// Return the names of the fields that were written alongside the choice's name, but that
+    /// its category's model doesn't use. They are ignored.
+    pub fn unknown_fields(&self) -> impl Iterator<Item = &str> {
+        self.unknown.0.iter().map(String::as_str)
+    }
+}
+
+/// The names of the fields of a choice that are not used by its model. Their values are ignored.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct UnknownFields(BTreeSet<String>);
+
+impl<'de> Deserialize<'de> for UnknownFields {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = BTreeMap::<String, IgnoredAny>::deserialize(deserializer)?;
+        // The availability is read after the details, so its fields are seen here as well.
+        Ok(UnknownFields(
+            fields
+                .into_keys()
+                .filter(|field| !["available", "unavailable"].contains(&field.as_str()))
+                .collect(),
+        ))
+    }
+}
+
+/// The names of the fields that choices can have in any of the models, which misspelled fields are
+/// compared to.
+const CHOICE_FIELDS: [&str; 16] = [
+    "assignments",
+    "attributes",
+    "available",
+    "capacity",
+    "cooldown",
+    "description",
+    "last_picked",
+    "name",
+    "preferences",
+    "restock",
+    "restocked",
+    "tags",
+    "tickets",
+    "unavailable",
+    "url",
+    "weight",
+];
+
+/// Return the number of single character insertions, deletions, and substitutions it takes to
+/// turn one string into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
 }
 
 /// Represents an individual choice for the inventory model.
@@ -130,11 +1761,136 @@ pub enum ConfigCategory {
 ///
 /// * `name` - The name of the choice.
 /// * `tickets` - The current number of tickets the choice has.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// * `restocked` - When the choice was last given tickets by its restock policy, or `None` if it
+///   never has been.
+/// * `tags` - Labels that the choice can be filtered by when picking. See [`crate::tags`].
+/// * `details` - A description, a link, and other notes about the choice, which are shown when it
+///   is offered.
+/// * `restock` - How the choice gets tickets back. If this is `None`, the category's policy is
+///   used.
+/// * `availability` - When the choice can be picked. See [`crate::availability`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct InventoryChoice {
     pub name: String,
     #[serde(default = "default_weight")]
     pub tickets: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restocked: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(flatten)]
+    pub details: ChoiceDetails,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restock: Option<Restock>,
+    #[serde(flatten)]
+    pub availability: Availability,
+}
+
+impl InventoryChoice {
+    /// Give the choice tickets according to the given restock policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The restock policy that applies to the choice.
+    /// * `exhausted` - Whether every choice in the category is out of tickets.
+    /// * `now` - The time of the pick.
+    fn restock(&mut self, policy: &Restock, exhausted: bool, now: DateTime<Local>) {
+        match policy {
+            Restock::Reset { tickets } => {
+                if exhausted {
+                    self.tickets = *tickets;
+                    // A pick made at an earlier time with --at doesn't move the clock back.
+                    self.restocked = Some(self.restocked.map_or(now, |r| r.max(now)));
+                }
+            }
+            Restock::Refill {
+                tickets,
+                every,
+                max,
+            } => {
+                let periods = self.periods_since_restocked(*every, now);
+                let refilled = self.tickets.saturating_add(tickets.saturating_mul(periods));
+                self.tickets = match max {
+                    Some(max) => refilled.min(*max).max(self.tickets),
+                    None => refilled,
+                };
+            }
+            Restock::TopUp { max, every } => {
+                if self.periods_since_restocked(*every, now) > 0 {
+                    self.tickets = self.tickets.max(*max);
+                }
+            }
+        }
+    }
+
+    /// Return how many whole periods of the given length have passed since the choice was last
+    /// restocked, and move its restocked time forward by that many periods. For a choice that has
+    /// never been restocked, this starts the clock without counting any periods.
+    ///
+    /// # Arguments
+    ///
+    /// * `every` - The length of a period. A period of 0 never passes.
+    /// * `now` - The time of the pick.
+    fn periods_since_restocked(&mut self, every: Duration, now: DateTime<Local>) -> u64 {
+        if every.is_zero() {
+            return 0;
+        }
+        let restocked = match self.restocked {
+            Some(restocked) => restocked,
+            None => {
+                self.restocked = Some(now);
+                return 0;
+            }
+        };
+        let elapsed = match (now - restocked).to_std() {
+            Ok(elapsed) => elapsed,
+            Err(_) => return 0,
+        };
+        let periods = elapsed.as_nanos() / every.as_nanos();
+        if periods > 0 {
+            let remainder = Duration::from_nanos((elapsed.as_nanos() % every.as_nanos()) as u64);
+            if let Ok(remainder) = chrono::Duration::from_std(remainder) {
+                self.restocked = Some(now - remainder);
+            }
+        }
+        u64::try_from(periods).unwrap_or(u64::MAX)
+    }
+}
+
+/// A policy for giving tickets back to choices in the inventory model, so that a category can
+/// cycle through its choices rather than running out of them. Periods are written like
+/// ```1 week``` or ```3days 12h```.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Restock {
+    /// Once every choice in the category is out of tickets, give the choice `tickets` tickets
+    /// again.
+    Reset { tickets: u64 },
+    /// Give the choice `tickets` more tickets every period, without going over `max` if it is set.
+    Refill {
+        tickets: u64,
+        #[serde(with = "humantime_serde")]
+        every: Duration,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<u64>,
+    },
+    /// Every period, raise the choice's tickets to `max` if it has fewer.
+    TopUp {
+        max: u64,
+        #[serde(with = "humantime_serde")]
+        every: Duration,
+    },
+}
+
+impl Restock {
+    /// Return `true` if the policy restocks every period, and its period is 0, so that it never
+    /// gives any tickets.
+    pub fn has_zero_period(&self) -> bool {
+        match self {
+            Restock::Reset { .. } => false,
+            Restock::Refill { every, .. } | Restock::TopUp { every, .. } => every.is_zero(),
+        }
+    }
 }
 
 /// Represents an individual choice for the lottery model.
@@ -145,13 +1901,139 @@ pub struct InventoryChoice {
 /// * `tickets` - The current number of tickets the choice has.
 /// * `weight` - The number of tickets that will be added to `tickets` each time this choice is not
 ///   picked.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// * `tags` - Labels that the choice can be filtered by when picking. See [`crate::tags`].
+/// * `details` - A description, a link, and other notes about the choice, which are shown when it
+///   is offered.
+/// * `preferences` - How much each participant likes the choice, keyed by participant. When
+///   participants are present for a pick, their preferences scale the choice's tickets.
+/// * `availability` - When the choice can be picked. See [`crate::availability`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LotteryChoice {
     pub name: String,
     #[serde(default = "default_weight")]
     pub tickets: u64,
     #[serde(default = "default_weight")]
     pub weight: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(flatten)]
+    pub details: ChoiceDetails,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub preferences: BTreeMap<String, u64>,
+    #[serde(flatten)]
+    pub availability: Availability,
+}
+
+/// Represents an individual choice for the recency model.
+///
+/// # Attributes
+///
+/// * `name` - The name of the choice.
+/// * `last_picked` - When the choice was last picked, or `None` if it has never been picked.
+/// * `cooldown` - How long after being picked the choice must wait before it can be picked again,
+///   written like ```3 days``` or ```1week 2d```. If this is `None`, the choice can be picked
+///   again right away.
+/// * `tags` - Labels that the choice can be filtered by when picking. See [`crate::tags`].
+/// * `details` - A description, a link, and other notes about the choice, which are shown when it
+///   is offered.
+/// * `availability` - When the choice can be picked. See [`crate::availability`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecencyChoice {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_picked: Option<DateTime<Local>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "humantime_serde"
+    )]
+    pub cooldown: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(flatten)]
+    pub details: ChoiceDetails,
+    #[serde(flatten)]
+    pub availability: Availability,
+}
+
+impl RecencyChoice {
+    /// Return `true` if the choice's cooldown has not yet passed at the given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time of the pick.
+    pub fn is_cooling_down(&self, now: DateTime<Local>) -> bool {
+        match (self.last_picked, self.cooldown) {
+            (Some(last_picked), Some(cooldown)) => match chrono::Duration::from_std(cooldown) {
+                Ok(cooldown) => last_picked
+                    .checked_add_signed(cooldown)
+                    .is_none_or(|ready| now < ready),
+                Err(_) => true,
+            },
+            _ => false,
+        }
+    }
+
+    /// Return the number of whole hours since the choice was last picked, or `None` if it has never
+    /// been picked. A last picked time after the given time counts as 0 hours.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time of the pick.
+    pub fn hours_since_picked(&self, now: DateTime<Local>) -> Option<u64> {
+        self.last_picked
+            .map(|last_picked| (now - last_picked).num_hours().max(0) as u64)
+    }
+}
+
+/// Represents an individual choice for the rotation model.
+///
+/// # Attributes
+///
+/// * `name` - The name of the choice.
+/// * `assignments` - How many times the choice has been picked.
+/// * `capacity` - How large a share of the picks the choice should get, relative to the other
+///   choices. A choice with a capacity of 2 is picked twice as often as a choice with a capacity
+///   of 1, and a choice with a capacity of 0 is never picked.
+/// * `tags` - Labels that the choice can be filtered by when picking. See [`crate::tags`].
+/// * `details` - A description, a link, and other notes about the choice, which are shown when it
+///   is offered.
+/// * `availability` - When the choice can be picked. See [`crate::availability`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RotationChoice {
+    pub name: String,
+    #[serde(default)]
+    pub assignments: u64,
+    #[serde(default = "default_weight")]
+    pub capacity: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(flatten)]
+    pub details: ChoiceDetails,
+    #[serde(flatten)]
+    pub availability: Availability,
+}
+
+impl RotationChoice {
+    /// Compare the shares of the picks that this choice and another have had, for their
+    /// capacities. A choice with a smaller share orders first. Choices with a capacity of 0 order
+    /// last.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The choice to compare with.
+    pub fn cmp_share(&self, other: &RotationChoice) -> Ordering {
+        match (self.capacity, other.capacity) {
+            (0, 0) => Ordering::Equal,
+            (0, _) => Ordering::Greater,
+            (_, 0) => Ordering::Less,
+            (capacity, other_capacity) => {
+                // Compare assignments / capacity without dividing, so that there's no rounding.
+                (self.assignments as u128 * other_capacity as u128)
+                    .cmp(&(other.assignments as u128 * capacity as u128))
+            }
+        }
+    }
 }
 
 /// Represents an individual choice for the weighted model.
@@ -160,11 +2042,25 @@ pub struct LotteryChoice {
 ///
 /// * `name` - The name of the choice
 /// * `weight` - How much chance this choice has of being chosen, relative to the other choices.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// * `tags` - Labels that the choice can be filtered by when picking. See [`crate::tags`].
+/// * `details` - A description, a link, and other notes about the choice, which are shown when it
+///   is offered.
+/// * `preferences` - How much each participant likes the choice, keyed by participant. When
+///   participants are present for a pick, their preferences scale the choice's weight.
+/// * `availability` - When the choice can be picked. See [`crate::availability`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct WeightedChoice {
     pub name: String,
     #[serde(default = "default_weight")]
     pub weight: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(flatten)]
+    pub details: ChoiceDetails,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub preferences: BTreeMap<String, u64>,
+    #[serde(flatten)]
+    pub availability: Availability,
 }
 
 /// Define the default for the stddev_scaling_factor setting as 3.0.
@@ -177,10 +2073,687 @@ fn default_weight() -> u64 {
     1
 }
 
+/// Leave boolean settings out of saved configs when they are `false`, their default.
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use chrono::{NaiveDate, TimeZone};
+
+    #[test]
+    fn test_new() {
+        assert_eq!(
+            ConfigCategory::new("gaussian").unwrap(),
+            ConfigCategory::Gaussian {
+                stddev_scaling_factor: 3.0,
+                choices: vec![],
+                group: None,
+            }
+        );
+        assert_eq!(
+            format!("{}", ConfigCategory::new("bogus").unwrap_err()),
+            "Unknown model `bogus`. The available models are even, gaussian, inventory, lottery, \
+             lru, recency, rotation, and weighted."
+        );
+    }
+
+    #[test]
+    fn test_add_choice() {
+        let mut lottery = ConfigCategory::new("lottery").unwrap();
+        lottery.add_choice("this", None, None).unwrap();
+        lottery.add_choice("that", Some(3), Some(0)).unwrap();
+        let mut lru = ConfigCategory::Lru {
+            choices: vec!["this".into()],
+            group: None,
+        };
+        lru.add_choice("that", None, None).unwrap();
+
+        assert_eq!(
+            lottery,
+            ConfigCategory::Lottery {
+                choices: vec![
+                    LotteryChoice {
+                        name: "this".to_string(),
+                        tickets: 1,
+                        weight: 1,
+                        tags: vec![],
+                        details: Default::default(),
+                        preferences: Default::default(),
+                        availability: Default::default(),
+                    },
+                    LotteryChoice {
+                        name: "that".to_string(),
+                        tickets: 0,
+                        weight: 3,
+                        tags: vec![],
+                        details: Default::default(),
+                        preferences: Default::default(),
+                        availability: Default::default(),
+                    }
+                ],
+                group: None,
+            }
+        );
+        // A new choice has never been used, so it goes to the front of the Lru list.
+        assert_eq!(lru.choice_names(), vec!["that", "this"]);
+        assert_eq!(
+            format!("{}", lru.add_choice("this", None, None).unwrap_err()),
+            "The category already has a choice named `this`."
+        );
+        assert_eq!(
+            format!("{}", lru.add_choice("other", Some(2), None).unwrap_err()),
+            "The lru model does not support the `weight` setting."
+        );
+    }
+
+    #[test]
+    fn test_remove_and_rename_choice() {
+        let mut weighted = ConfigCategory::new("weighted").unwrap();
+        weighted.add_choice("this", Some(2), None).unwrap();
+        weighted.add_choice("that", None, None).unwrap();
+
+        weighted.rename_choice("this", "the other").unwrap();
+        weighted.remove_choice("that").unwrap();
+
+        assert_eq!(
+            weighted,
+            ConfigCategory::Weighted {
+                choices: vec![WeightedChoice {
+                    name: "the other".to_string(),
+                    weight: 2,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
+                }],
+                group: None,
+            }
+        );
+        assert_eq!(
+            format!("{}", weighted.remove_choice("that").unwrap_err()),
+            "The category has no choice named `that`."
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = BTreeMap::new();
+        config.insert(
+            "empty".to_string(),
+            ConfigCategory::Even {
+                choices: vec![],
+                group: None,
+            },
+        );
+        config.insert(
+            "flat".to_string(),
+            ConfigCategory::Gaussian {
+                stddev_scaling_factor: -1.0,
+                choices: vec!["a".into(), "b".into(), "a".into()],
+                group: None,
+            },
+        );
+        config.insert(
+            "tea".to_string(),
+            ConfigCategory::Inventory {
+                choices: vec![InventoryChoice {
+                    name: "Black".to_string(),
+                    tickets: 0,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
+                }],
+                group: None,
+                restock: None,
+            },
+        );
+        config.insert(
+            "lunch".to_string(),
+            ConfigCategory::Weighted {
+                choices: vec![
+                    WeightedChoice {
+                        name: "Centro".to_string(),
+                        weight: 1,
+                        tags: vec![],
+                        details: Default::default(),
+                        preferences: Default::default(),
+                        availability: Default::default(),
+                    },
+                    WeightedChoice {
+                        name: "Sitti".to_string(),
+                        weight: 0,
+                        tags: vec![],
+                        details: Default::default(),
+                        preferences: Default::default(),
+                        availability: Default::default(),
+                    },
+                ],
+                group: None,
+            },
+        );
+        config.insert(
+            "fine".to_string(),
+            ConfigCategory::Lru {
+                choices: vec!["a".into()],
+                group: None,
+            },
+        );
+        config.insert(
+            "nobody".to_string(),
+            ConfigCategory::Even {
+                choices: vec!["a".into()],
+                group: Some(Group {
+                    participants: vec![],
+                    rule: Default::default(),
+                    combine: Default::default(),
+                }),
+            },
+        );
+        config.insert(
+            "snacks".to_string(),
+            ConfigCategory::Inventory {
+                choices: vec![InventoryChoice {
+                    name: "Chips".to_string(),
+                    tickets: 0,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: Some(Restock::TopUp {
+                        max: 3,
+                        every: Duration::from_secs(0),
+                    }),
+                    availability: Default::default(),
+                }],
+                group: None,
+                restock: None,
+            },
+        );
+        config.insert(
+            "team".to_string(),
+            ConfigCategory::Even {
+                choices: vec!["a".into()],
+                group: Some(Group {
+                    participants: vec!["alice".into(), "bob".into(), "alice".into()],
+                    rule: Default::default(),
+                    combine: Default::default(),
+                }),
+            },
+        );
+
+        let diagnostics = validate(&config);
+
+        let diagnostic = |category: &str, choice: Option<&str>, problem| Diagnostic {
+            category: category.to_string(),
+            choice: choice.map(String::from),
+            severity: Problem::severity(&problem),
+            problem,
+        };
+        assert_eq!(
+            diagnostics,
+            vec![
+                diagnostic("empty", None, Problem::NoChoices),
+                diagnostic("flat", Some("a"), Problem::DuplicateChoice),
+                diagnostic("flat", None, Problem::InvalidStddevScalingFactor(-1.0)),
+                diagnostic("lunch", Some("Sitti"), Problem::ZeroWeight),
+                diagnostic("nobody", None, Problem::NoParticipants),
+                diagnostic("snacks", Some("Chips"), Problem::ZeroRestockPeriod),
+                diagnostic("tea", None, Problem::AllTicketsExhausted),
+                diagnostic(
+                    "team",
+                    None,
+                    Problem::DuplicateParticipant("alice".to_string())
+                ),
+            ]
+        );
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+        assert_eq!(diagnostics[3].severity, Severity::Warning);
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "Problem with the choice `a` in the category `flat`: The choice appears more than once."
+        );
+    }
+
+    #[test]
+    fn test_validate_unknown_fields() {
+        let config: BTreeMap<String, ConfigCategory> = serde_yaml::from_str(
+            "lunch:\n  model: weighted\n  choices:\n    - name: Centro\n      wieght: 3\n      \
+             available: [{days: [mon]}]\n      attributes: {weight: '2', address: Main St}\n\
+             tea:\n  model: even\n  choices:\n    - name: Black\n      weight: 2\n",
+        )
+        .unwrap();
+
+        let diagnostics = validate(&config);
+
+        let messages = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    Severity::Warning,
+                    "Problem with the choice `Centro` in the category `lunch`: The choice has a \
+                     field `wieght` that is not used, so it is ignored. Did you mean `weight`?"
+                        .to_string()
+                ),
+                (
+                    Severity::Warning,
+                    "Problem with the choice `Centro` in the category `lunch`: The attribute \
+                     `weight` has the name of a choice field, so it is only shown and not used to \
+                     pick."
+                        .to_string()
+                ),
+                (
+                    Severity::Warning,
+                    "Problem with the choice `Black` in the category `tea`: The choice has a field \
+                     `weight` that is not used, so it is ignored."
+                        .to_string()
+                ),
+            ]
+        );
+        // The unknown fields are not saved.
+        assert!(!serde_yaml::to_string(&config).unwrap().contains("wieght"));
+    }
+
+    #[test]
+    fn test_config_file_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rpick.yml");
+        let path = path.to_str().unwrap();
+
+        let lock_path = dir.path().join("rpick.yml.lock");
+
+        let config_file = ConfigFile::lock(path).unwrap();
+        assert!(lock_path.exists());
+        match ConfigFile::lock(path) {
+            Err(error) => assert!(matches!(
+                error.downcast_ref::<ConfigError>(),
+                Some(ConfigError::Locked)
+            )),
+            Ok(_) => panic!("The config file should have been locked."),
+        }
+        // Config files that only differ by their extensions have separate locks.
+        ConfigFile::lock(dir.path().join("rpick.toml").to_str().unwrap()).unwrap();
+        // An opened config file is only locked when it is written.
+        let mut opened = ConfigFile::open(path);
+        match opened.write(BTreeMap::new()) {
+            Err(error) => assert!(matches!(
+                error.downcast_ref::<ConfigError>(),
+                Some(ConfigError::Locked)
+            )),
+            Ok(_) => panic!("The config file should have been locked."),
+        }
+
+        drop(config_file);
+        assert!(!lock_path.exists());
+        opened.write(BTreeMap::new()).unwrap();
+        assert!(lock_path.exists());
+        drop(opened);
+        ConfigFile::lock(path).unwrap();
+    }
+
+    #[test]
+    fn test_config_file_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rpick.yml");
+        let path = path.to_str().unwrap();
+        fs::write(path, "things:\n  model: even\n  choices: []\n").unwrap();
+        let mut config_file = ConfigFile::lock(path).unwrap();
+        let mut config = config_file.read().unwrap();
+
+        config.insert("more".to_string(), ConfigCategory::new("lru").unwrap());
+        config_file.write(config.clone()).unwrap();
+        // Writing again is fine, since the ConfigFile knows what it wrote.
+        config_file.write(config.clone()).unwrap();
+        assert_eq!(read_config(path).unwrap(), config);
+
+        // Changes made behind the ConfigFile's back must not be overwritten.
+        fs::write(path, "{}").unwrap();
+        match config_file.write(config) {
+            Err(error) => assert!(matches!(
+                error.downcast_ref::<ConfigError>(),
+                Some(ConfigError::Modified(_))
+            )),
+            Ok(_) => panic!("The config file should not have been written."),
+        }
+        assert_eq!(fs::read_to_string(path).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_config_file_write_new() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rpick.yml");
+        let path = path.to_str().unwrap();
+        let mut config_file = ConfigFile::lock(path).unwrap();
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), ConfigCategory::new("even").unwrap());
+
+        config_file.write(config.clone()).unwrap();
+
+        assert_eq!(read_config(path).unwrap(), config);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_config_keeps_permissions_and_symlinks() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target.yml");
+        let link = dir.path().join("rpick.yml");
+        fs::write(&target, "{}").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        symlink(&target, &link).unwrap();
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), ConfigCategory::new("even").unwrap());
+
+        write_config(link.to_str().unwrap(), config.clone()).unwrap();
+
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(read_config(target.to_str().unwrap()).unwrap(), config);
+        assert_eq!(
+            fs::metadata(&target).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path("rpick.json"), Format::Json);
+        assert_eq!(Format::from_path("/etc/rpick.TOML"), Format::Toml);
+        assert_eq!(Format::from_path("rpick.yml"), Format::Yaml);
+        assert_eq!(Format::from_path("rpick.yaml"), Format::Yaml);
+        assert_eq!(Format::from_path("rpick"), Format::Yaml);
+        assert_eq!("toml".parse::<Format>().unwrap(), Format::Toml);
+        assert_eq!(
+            "ini".parse::<Format>().unwrap_err().to_string(),
+            "Unknown config format `ini`. The available formats are json, toml, and yaml."
+        );
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        let mut config = BTreeMap::new();
+        config.insert(
+            "lunch".to_string(),
+            ConfigCategory::Gaussian {
+                stddev_scaling_factor: 2.5,
+                choices: vec!["Centro".into(), "Sitti".into()],
+                group: None,
+            },
+        );
+        config.insert(
+            "tea".to_string(),
+            ConfigCategory::Lottery {
+                choices: vec![LotteryChoice {
+                    name: "Black".to_string(),
+                    tickets: 4,
+                    weight: 2,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
+                }],
+                group: None,
+            },
+        );
+        config.insert("code".to_string(), ConfigCategory::new("lru").unwrap());
+
+        for format in [Format::Json, Format::Toml, Format::Yaml] {
+            let contents = format.serialize(&config).unwrap();
+
+            assert_eq!(format.parse(&contents).unwrap(), config, "{}", format);
+        }
+    }
+
+    #[test]
+    fn test_config_file_write_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rpick.conf");
+        let path = path.to_str().unwrap();
+        fs::write(
+            path,
+            "# Comment\n[tea]\nmodel = 'even'\nchoices = ['Black']\n",
+        )
+        .unwrap();
+        let mut config_file = ConfigFile::lock(path).unwrap();
+        config_file.set_format(Format::Toml);
+        let mut config = config_file.read().unwrap();
+        config.insert("code".to_string(), ConfigCategory::new("lru").unwrap());
+
+        config_file.write(config.clone()).unwrap();
+
+        assert_eq!(read_config_as(path, Format::Toml).unwrap(), config);
+    }
+
+    #[test]
+    fn test_rotation_choice() {
+        let config: BTreeMap<String, ConfigCategory> = serde_yaml::from_str(
+            "duty:\n  model: rotation\n  choices:\n    - name: alice\n      assignments: 4\n      \
+             capacity: 2\n      unavailable:\n        - 2021-06-01\n        - from: 2021-06-10\n          \
+             until: 2021-06-12\n    - name: bob\n      assignments: 1\n    - name: carol\n      \
+             capacity: 0\n",
+        )
+        .unwrap();
+
+        let choices = match &config["duty"] {
+            ConfigCategory::Rotation { choices, .. } => choices,
+            category => panic!("Unexpected category: {:?}", category),
+        };
+        let at = |day| Local.with_ymd_and_hms(2021, 6, day, 12, 0, 0).unwrap();
+        let reason = |day| choices[0].availability.unavailable_reason(at(day));
+        assert_eq!(reason(1), Some("unavailable on 2021-06-01".to_string()));
+        assert_eq!(reason(2), None);
+        assert_eq!(
+            reason(10),
+            Some("unavailable from 2021-06-10 until 2021-06-12".to_string())
+        );
+        assert!(reason(12).is_some());
+        assert_eq!(reason(13), None);
+        assert_eq!(choices[2].assignments, 0);
+        assert_eq!(choices[1].capacity, 1);
+        // alice has had 4 picks for a capacity of 2, which is a larger share than bob's 1.
+        assert_eq!(choices[0].cmp_share(&choices[1]), Ordering::Greater);
+        assert_eq!(choices[2].cmp_share(&choices[1]), Ordering::Greater);
+        assert_eq!(choices[1].cmp_share(&choices[2]), Ordering::Less);
+        // A list that mixes single days and ranges can still be saved as TOML.
+        assert_eq!(
+            Format::Toml
+                .parse(&Format::Toml.serialize(&config).unwrap())
+                .unwrap(),
+            config
+        );
+
+        let mut duty = config["duty"].clone();
+        duty.add_choice("dave", None, None).unwrap();
+        match &duty {
+            ConfigCategory::Rotation { choices, .. } => assert_eq!(choices[3].assignments, 1),
+            category => panic!("Unexpected category: {:?}", category),
+        }
+
+        let mut backwards = config["duty"].clone();
+        if let ConfigCategory::Rotation { choices, .. } = &mut backwards {
+            choices[1].availability.unavailable.push(Window {
+                from: NaiveDate::from_ymd_opt(2021, 6, 5),
+                until: NaiveDate::from_ymd_opt(2021, 6, 4),
+                ..Window::default()
+            });
+        }
+        let problems = backwards
+            .validate("duty")
+            .into_iter()
+            .map(|d| (d.choice, d.problem.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    Some("carol".to_string()),
+                    "The choice has a capacity of 0, so it can never be picked.".to_string()
+                ),
+                (
+                    Some("bob".to_string()),
+                    "The choice has an availability window from 2021-06-05 until 2021-06-04, \
+                     which ends before it starts."
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recency_choice() {
+        let config: BTreeMap<String, ConfigCategory> = serde_yaml::from_str(
+            "food:\n  model: recency\n  choices:\n    - name: tacos\n      \
+             last_picked: 2021-06-01T12:00:00Z\n      cooldown: 2days 12h\n    - name: pizza\n",
+        )
+        .unwrap();
+
+        let choices = match &config["food"] {
+            ConfigCategory::Recency { choices, .. } => choices,
+            category => panic!("Unexpected category: {:?}", category),
+        };
+        let at = |day, hour| {
+            DateTime::parse_from_rfc3339(&format!("2021-06-{:02}T{:02}:00:00Z", day, hour))
+                .unwrap()
+                .with_timezone(&Local)
+        };
+        assert_eq!(choices[0].cooldown, Some(Duration::from_secs(60 * 60 * 60)));
+        assert!(choices[0].is_cooling_down(at(3, 23)));
+        assert!(!choices[0].is_cooling_down(at(4, 0)));
+        assert_eq!(choices[0].hours_since_picked(at(4, 0)), Some(60));
+        assert_eq!(choices[0].hours_since_picked(at(1, 0)), Some(0));
+        assert!(!choices[1].is_cooling_down(at(1, 0)));
+        assert_eq!(choices[1].hours_since_picked(at(1, 0)), None);
+        assert_eq!(
+            Format::Toml
+                .parse(&Format::Toml.serialize(&config).unwrap())
+                .unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn test_restock() {
+        let mut config: BTreeMap<String, ConfigCategory> = serde_yaml::from_str(
+            "books:\n  model: inventory\n  restock:\n    policy: reset\n    tickets: 2\n  \
+             choices:\n    - name: Dune\n      tickets: 0\n    - name: Emma\n      \
+             tickets: 0\n      \
+             restock:\n        policy: refill\n        tickets: 2\n        every: 1day\n        \
+             max: 5\n    - name: Ulysses\n      tickets: 0\n      restock:\n        \
+             policy: top_up\n        max: 3\n        every: 1week\n",
+        )
+        .unwrap();
+        let at = |day, hour| {
+            DateTime::parse_from_rfc3339(&format!("2021-06-{:02}T{:02}:00:00Z", day, hour))
+                .unwrap()
+                .with_timezone(&Local)
+        };
+        let tickets = |config: &BTreeMap<String, ConfigCategory>| match &config["books"] {
+            ConfigCategory::Inventory { choices, .. } => choices
+                .iter()
+                .map(|c| (c.tickets, c.restocked))
+                .collect::<Vec<_>>(),
+            category => panic!("Unexpected category: {:?}", category),
+        };
+
+        // Every choice is out of tickets, so Dune is reset, and the others start their clocks.
+        config.get_mut("books").unwrap().restock(at(1, 12));
+        assert_eq!(
+            tickets(&config),
+            vec![
+                (2, Some(at(1, 12))),
+                (0, Some(at(1, 12))),
+                (0, Some(at(1, 12)))
+            ]
+        );
+
+        // Two and a half days later, Emma has been refilled twice. Dune is not reset since there
+        // are tickets left, and Ulysses is not due yet.
+        config.get_mut("books").unwrap().restock(at(4, 0));
+        assert_eq!(
+            tickets(&config),
+            vec![
+                (2, Some(at(1, 12))),
+                (4, Some(at(3, 12))),
+                (0, Some(at(1, 12)))
+            ]
+        );
+
+        // A week in, Emma is refilled up to its max, and Ulysses is topped up.
+        config.get_mut("books").unwrap().restock(at(8, 12));
+        assert_eq!(
+            tickets(&config),
+            vec![
+                (2, Some(at(1, 12))),
+                (5, Some(at(8, 12))),
+                (3, Some(at(8, 12)))
+            ]
+        );
+
+        // Picking the clock back gives no tickets, and no restocked time moves back, even when
+        // Dune is reset.
+        if let Some(ConfigCategory::Inventory { choices, .. }) = config.get_mut("books") {
+            choices.iter_mut().for_each(|c| c.tickets = 0);
+        }
+        config.get_mut("books").unwrap().restock(at(1, 0));
+        assert_eq!(
+            tickets(&config),
+            vec![
+                (2, Some(at(1, 12))),
+                (0, Some(at(8, 12))),
+                (0, Some(at(8, 12)))
+            ]
+        );
+
+        for format in [Format::Json, Format::Toml, Format::Yaml] {
+            let contents = format.serialize(&config).unwrap();
+
+            assert_eq!(format.parse(&contents).unwrap(), config, "{}", format);
+        }
+        assert!(
+            serde_yaml::from_str::<Restock>("policy: reset\ntickets: 1\nevery: 1day\n").is_err()
+        );
+    }
+
+    #[test]
+    fn test_choice_details() {
+        let config: BTreeMap<String, ConfigCategory> = serde_yaml::from_str(
+            "lunch:\n  model: lru\n  choices:\n    - Leftovers\n    - name: Centro\n      \
+             description: Italian\n      url: https://centro.example\n      attributes:\n        \
+             phone: 555-0100\n        address: 1 Main St\ntea:\n  model: weighted\n  choices:\n    \
+             - name: Black\n      weight: 2\n      attributes:\n        caffeine: high\n",
+        )
+        .unwrap();
+
+        let centro = config["lunch"].details("Centro").unwrap();
+        assert_eq!(centro.description.as_deref(), Some("Italian"));
+        assert_eq!(centro.url.as_deref(), Some("https://centro.example"));
+        assert_eq!(centro.attributes["phone"], "555-0100");
+        assert_eq!(centro.attributes.len(), 2);
+        assert!(config["lunch"].details("Leftovers").unwrap().is_empty());
+        assert_eq!(config["lunch"].details("Sitti"), None);
+        assert_eq!(
+            config["tea"].choice_details()[0].1.attributes["caffeine"],
+            "high"
+        );
+        for format in [Format::Json, Format::Toml, Format::Yaml] {
+            let contents = format.serialize(&config).unwrap();
+
+            assert_eq!(format.parse(&contents).unwrap(), config, "{}", format);
+        }
+    }
+
     #[test]
     fn test_defaults() {
         assert!((default_stddev_scaling_factor() - 3.0).abs() < 0.000_001);
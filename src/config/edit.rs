@@ -0,0 +1,889 @@
+/* Copyright © 2021 Randy Barlow
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, version 3 of the License.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
+//! # Config editing
+//!
+//! Serializing the whole config would throw away its comments, sort its categories, and write out
+//! every setting that was left at its default. This module instead finds the parts of the config
+//! text that changed and rewrites only those, leaving everything else as the user wrote it.
+use std::iter::{self, Peekable};
+use std::ops::Range;
+use std::vec;
+
+use serde_yaml::Value;
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::{Marker, TScalarStyle};
+
+use super::ConfigDocument;
+
+/// A change to a text: the range of the text is replaced with the string.
+type Edit = (Range<usize>, String);
+
+/// Return the given YAML text, changed so that it holds the given config.
+///
+/// Within each category that differs from the one in `text`, only the settings and choices that
+/// changed are rewritten. A category that cannot be changed that way is rewritten as a whole, and
+/// new categories are added to the end. If `text` cannot be edited at all, for example because a
+/// category was removed, the whole config is serialized from scratch.
+///
+/// # Arguments
+///
+/// * `text` - The YAML text the config was read from.
+/// * `config` - The config that should be saved.
+pub(super) fn update_yaml(
+    text: &str,
+    config: &ConfigDocument,
+) -> Result<String, serde_yaml::Error> {
+    if let Some(updated) = try_update(text, config) {
+        // Make sure the edits did what they were meant to before trusting them.
+        let reread = serde_yaml::from_str::<ConfigDocument>(&updated);
+        if reread.ok().as_ref() == Some(config) {
+            return Ok(updated);
+        }
+    }
+    serde_yaml::to_string(config)
+}
+
+/// Edit the given YAML text to hold the given config, or return `None` if that can't be done.
+fn try_update(text: &str, config: &ConfigDocument) -> Option<String> {
+    let old: ConfigDocument = serde_yaml::from_str(text).ok()?;
+    if old.include != config.include || old.separate_state != config.separate_state {
+        return None;
+    }
+    let (old, config) = (&old.categories, &config.categories);
+    if old.keys().any(|name| !config.contains_key(name)) {
+        return None;
+    }
+    let document = Document::parse(text)?;
+    let entries = match &document.root {
+        Node::Mapping {
+            flow: false,
+            entries,
+            ..
+        } => entries,
+        _ => return None,
+    };
+
+    let mut edits = vec![];
+    let mut added = String::new();
+    for (name, category) in config {
+        let new = serde_yaml::to_value(category).ok()?;
+        match old.get(name) {
+            Some(old_category) if old_category == category => {}
+            Some(old_category) => {
+                let old = serde_yaml::to_value(old_category).ok()?;
+                let (key, value) = entries.iter().find(|(key, _)| key.scalar() == Some(name))?;
+                let mut category_edits = vec![];
+                if document
+                    .update(value, &old, &new, &mut category_edits)
+                    .is_none()
+                {
+                    category_edits = vec![document.replace_value(key, value, &new)?];
+                }
+                edits.append(&mut category_edits);
+            }
+            None => {
+                added.push_str(&entry(0, &Value::String(name.clone()), &new)?);
+                added.push('\n');
+            }
+        }
+    }
+
+    let mut updated = apply(text, 0..text.len(), &edits)?;
+    if !added.is_empty() {
+        if !updated.is_empty() && !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push_str(&added);
+    }
+    Some(updated)
+}
+
+/// A node of a YAML document, along with the range of the text it was parsed from.
+///
+/// The ranges are byte offsets. The nodes inside of flow collections (like ```[a, b]```) do not
+/// have meaningful ranges, since flow collections are only ever replaced as a whole.
+enum Node {
+    Scalar {
+        value: String,
+        span: Range<usize>,
+    },
+    Sequence {
+        flow: bool,
+        span: Range<usize>,
+        items: Vec<Node>,
+    },
+    Mapping {
+        flow: bool,
+        span: Range<usize>,
+        entries: Vec<(Node, Node)>,
+    },
+}
+
+impl Node {
+    /// Return the range of the text this node was parsed from.
+    ///
+    /// For block collections, this runs from the start of the first child to the end of the last.
+    fn span(&self) -> Range<usize> {
+        match self {
+            Node::Scalar { span, .. }
+            | Node::Sequence { span, .. }
+            | Node::Mapping { span, .. } => span.clone(),
+        }
+    }
+
+    /// Return this node's value if it is a scalar.
+    fn scalar(&self) -> Option<&str> {
+        match self {
+            Node::Scalar { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return `true` if this node is a flow collection, such as ```[a, b]``` or ```{a: b}```.
+    fn is_flow(&self) -> bool {
+        matches!(
+            self,
+            Node::Sequence { flow: true, .. } | Node::Mapping { flow: true, .. }
+        )
+    }
+}
+
+/// A parsed YAML document, along with the text it was parsed from.
+struct Document<'a> {
+    text: &'a str,
+    root: Node,
+}
+
+impl<'a> Document<'a> {
+    /// Parse the given text, or return `None` if it isn't a single YAML document that can be
+    /// edited. Documents that use anchors, aliases, or block scalars (```|``` and ```>```) can't be.
+    fn parse(text: &'a str) -> Option<Document<'a>> {
+        let mut receiver = EventReceiver::default();
+        Parser::new(text.chars()).load(&mut receiver, true).ok()?;
+        // The parser counts characters, but the text must be sliced by bytes.
+        let offsets = text
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain(iter::once(text.len()))
+            .collect::<Vec<_>>();
+        let events = receiver
+            .events
+            .into_iter()
+            .map(|(event, index)| Some((event, *offsets.get(index)?)))
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut builder = Builder {
+            text,
+            events: events.into_iter().peekable(),
+            flow_depth: 0,
+        };
+        if !matches!(builder.events.next()?.0, Event::StreamStart)
+            || !matches!(builder.events.next()?.0, Event::DocumentStart)
+        {
+            return None;
+        }
+        let root = builder.node()?;
+        if !matches!(builder.events.next()?.0, Event::DocumentEnd)
+            || !matches!(builder.events.next()?.0, Event::StreamEnd)
+        {
+            return None;
+        }
+
+        Some(Document { text, root })
+    }
+
+    /// Add the edits needed to change the given node's value from `old` to `new`.
+    ///
+    /// Return `None` if the node can't be changed in place, in which case some of the edits may
+    /// have been added already.
+    fn update(&self, node: &Node, old: &Value, new: &Value, edits: &mut Vec<Edit>) -> Option<()> {
+        if old == new {
+            return Some(());
+        }
+
+        match (node, new) {
+            (Node::Scalar { span, .. }, _) if !is_collection(new) => {
+                edits.push((span.clone(), scalar(new)?));
+                Some(())
+            }
+            (
+                Node::Mapping {
+                    flow: false,
+                    entries,
+                    ..
+                },
+                Value::Mapping(new),
+            ) => {
+                let old = old.as_mapping()?;
+                if old.iter().any(|(key, _)| !new.contains_key(key)) {
+                    return None;
+                }
+                for (key, new_value) in new {
+                    let old_value = old.get(key);
+                    if old_value == Some(new_value) {
+                        continue;
+                    }
+                    let name = key.as_str()?;
+                    match entries.iter().find(|(key, _)| key.scalar() == Some(name)) {
+                        Some((key, value)) if value.is_flow() => {
+                            edits.push(self.replace_value(key, value, new_value)?);
+                        }
+                        Some((_, value)) => self.update(value, old_value?, new_value, edits)?,
+                        // The setting was left out of the config, and is no longer the default.
+                        None => edits.push(self.insert_entry(entries, key, new_value)?),
+                    }
+                }
+                Some(())
+            }
+            (
+                Node::Sequence {
+                    flow: false, items, ..
+                },
+                Value::Sequence(new),
+            ) => self.update_sequence(items, old.as_sequence()?, new, edits),
+            _ => None,
+        }
+    }
+
+    /// Add the edits needed to change a block sequence's items from `old` to `new`.
+    ///
+    /// Items are matched up by their names, so that items which were moved, added, or removed
+    /// take their comments and formatting with them.
+    fn update_sequence(
+        &self,
+        items: &[Node],
+        old: &[Value],
+        new: &[Value],
+        edits: &mut Vec<Edit>,
+    ) -> Option<()> {
+        // Block sequences can't be empty, so let the caller rewrite the whole category instead.
+        if items.len() != old.len() || new.is_empty() {
+            return None;
+        }
+        let names = old.iter().map(item_name).collect::<Option<Vec<_>>>()?;
+        let mut used = vec![false; old.len()];
+        let mut matches = vec![];
+        for item in new {
+            let name = item_name(item)?;
+            let index = (0..old.len()).find(|&i| !used[i] && names[i] == name);
+            if let Some(index) = index {
+                used[index] = true;
+            }
+            matches.push(index);
+        }
+
+        if new.len() == old.len() && matches.iter().enumerate().all(|(i, m)| *m == Some(i)) {
+            for (i, item) in new.iter().enumerate() {
+                self.update(&items[i], &old[i], item, edits)?;
+            }
+            return Some(());
+        }
+
+        // Rebuild the sequence from its items' lines, in the new order.
+        let chunks = self.item_chunks(items)?;
+        let region = chunks.first()?.start..chunks.last()?.end;
+        let indent = self.column(self.dash(&items[0])?);
+        let mut text = String::new();
+        for (item, index) in new.iter().zip(matches) {
+            match index {
+                Some(index) => {
+                    let mut item_edits = vec![];
+                    self.update(&items[index], &old[index], item, &mut item_edits)?;
+                    text.push_str(&apply(self.text, chunks[index].clone(), &item_edits)?);
+                    if !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+                }
+                None => {
+                    text.push_str(&sequence_item(indent, item)?);
+                    text.push('\n');
+                }
+            }
+        }
+        if !self.text[region.clone()].ends_with('\n') {
+            text.pop();
+        }
+        edits.push((region, text));
+        Some(())
+    }
+
+    /// Return an edit that replaces the value of the mapping entry with the given key and value
+    /// nodes. Any comments inside the old value are lost.
+    fn replace_value(&self, key: &Node, value: &Node, new: &Value) -> Option<Edit> {
+        let indent = self.column(key.span().start);
+        Some((key.span().end..value.span().end, entry_value(indent, new)?))
+    }
+
+    /// Return an edit that adds an entry to the end of the given block mapping.
+    fn insert_entry(&self, entries: &[(Node, Node)], key: &Value, value: &Value) -> Option<Edit> {
+        let indent = self.column(entries.first()?.0.span().start);
+        let at = self.line_end(entries.last()?.1.span().end);
+        Some((at..at, format!("\n{}", entry(indent, key, value)?)))
+    }
+
+    /// Return the range of whole lines that belongs to each of the given block sequence items.
+    ///
+    /// An item's lines run from the line with its dash through the line it ends on, and also
+    /// include any comment or blank lines between it and the item before it. The first item also
+    /// gets the comment lines just above it.
+    fn item_chunks(&self, items: &[Node]) -> Option<Vec<Range<usize>>> {
+        let mut chunks = vec![];
+        let mut start = self.line_start(self.dash(items.first()?)?);
+        while start > 0 {
+            let previous = self.line_start(start - 1);
+            if !self.text[previous..start].trim_start().starts_with('#') {
+                break;
+            }
+            start = previous;
+        }
+        for item in items {
+            let dash = self.dash(item)?;
+            // Each item must start its own line.
+            if dash < start || !self.text[self.line_start(dash)..dash].trim().is_empty() {
+                return None;
+            }
+            let end = self.next_line(item.span().end);
+            chunks.push(start..end);
+            start = end;
+        }
+        Some(chunks)
+    }
+
+    /// Return the offset of the ```-``` that introduces the given block sequence item.
+    fn dash(&self, item: &Node) -> Option<usize> {
+        let before = self.text[..item.span().start].trim_end_matches(' ');
+        if before.ends_with('-') {
+            Some(before.len() - 1)
+        } else {
+            None
+        }
+    }
+
+    /// Return how far the given offset is from the start of its line.
+    fn column(&self, offset: usize) -> usize {
+        offset - self.line_start(offset)
+    }
+
+    /// Return the offset of the start of the line the given offset is on.
+    fn line_start(&self, offset: usize) -> usize {
+        self.text[..offset].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// Return the offset of the end of the line the given offset is on, before its newline.
+    fn line_end(&self, offset: usize) -> usize {
+        self.text[offset..]
+            .find('\n')
+            .map_or(self.text.len(), |i| offset + i)
+    }
+
+    /// Return the offset of the start of the line after the one the given offset is on.
+    fn next_line(&self, offset: usize) -> usize {
+        (self.line_end(offset) + 1).min(self.text.len())
+    }
+}
+
+/// Collects the parser's events, along with the character index each one starts at.
+#[derive(Default)]
+struct EventReceiver {
+    events: Vec<(Event, usize)>,
+}
+
+impl MarkedEventReceiver for EventReceiver {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        self.events.push((event, mark.index()));
+    }
+}
+
+/// Builds [`Node`]'s from the parser's events, which have been given byte offsets.
+struct Builder<'a> {
+    text: &'a str,
+    events: Peekable<vec::IntoIter<(Event, usize)>>,
+    /// How many flow collections the next node is inside of.
+    flow_depth: usize,
+}
+
+impl Builder<'_> {
+    /// Build the next node from the events, or return `None` if it can't be edited.
+    fn node(&mut self) -> Option<Node> {
+        let (event, start) = self.events.next()?;
+        match event {
+            Event::Scalar(value, style, 0, _) => {
+                let end = if self.flow_depth > 0 {
+                    start
+                } else {
+                    scalar_end(self.text, start, style, &value)?
+                };
+                Some(Node::Scalar {
+                    value,
+                    span: start..end,
+                })
+            }
+            Event::SequenceStart(0) => {
+                let flow = self.text[start..].starts_with('[');
+                let (items, end) =
+                    self.children(flow, |event| matches!(event, Event::SequenceEnd))?;
+                let span = self.span(flow, start, end, &items)?;
+                Some(Node::Sequence { flow, span, items })
+            }
+            Event::MappingStart(0) => {
+                let flow = self.text[start..].starts_with('{');
+                let (nodes, end) =
+                    self.children(flow, |event| matches!(event, Event::MappingEnd))?;
+                let span = self.span(flow, start, end, &nodes)?;
+                let mut nodes = nodes.into_iter();
+                let mut entries = vec![];
+                while let Some(key) = nodes.next() {
+                    entries.push((key, nodes.next()?));
+                }
+                Some(Node::Mapping {
+                    flow,
+                    span,
+                    entries,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Build the nodes in a collection, up to the event that ends it. Return them along with the
+    /// offset of that event.
+    fn children(&mut self, flow: bool, is_end: fn(&Event) -> bool) -> Option<(Vec<Node>, usize)> {
+        if flow {
+            self.flow_depth += 1;
+        }
+        let mut children = vec![];
+        while !is_end(&self.events.peek()?.0) {
+            children.push(self.node()?);
+        }
+        if flow {
+            self.flow_depth -= 1;
+        }
+        let (_, end) = self.events.next()?;
+        Some((children, end))
+    }
+
+    /// Return the span of a collection.
+    fn span(
+        &self,
+        flow: bool,
+        start: usize,
+        end: usize,
+        children: &[Node],
+    ) -> Option<Range<usize>> {
+        if flow {
+            // The end event is at the closing bracket.
+            Some(start..end + 1)
+        } else {
+            Some(children.first()?.span().start..children.last()?.span().end)
+        }
+    }
+}
+
+/// Return the offset just past the end of the scalar that starts at the given offset, or `None` if
+/// it can't be found. Plain scalars must fit on one line.
+fn scalar_end(text: &str, start: usize, style: TScalarStyle, value: &str) -> Option<usize> {
+    let rest = &text[start..];
+    match style {
+        TScalarStyle::Plain => {
+            let mut end = rest.len();
+            let mut chars = rest.char_indices().peekable();
+            let mut previous = ' ';
+            while let Some((i, c)) = chars.next() {
+                let next = chars.peek().map_or(' ', |(_, next)| *next);
+                if c == '\n' || (c == ':' && next.is_whitespace()) || (c == '#' && previous == ' ')
+                {
+                    end = i;
+                    break;
+                }
+                previous = c;
+            }
+            let raw = rest[..end].trim_end();
+            if raw != value {
+                return None;
+            }
+            Some(start + raw.len())
+        }
+        TScalarStyle::SingleQuoted => {
+            let mut chars = rest.char_indices().skip(1).peekable();
+            while let Some((i, c)) = chars.next() {
+                if c == '\'' {
+                    if matches!(chars.peek(), Some((_, '\''))) {
+                        chars.next();
+                    } else {
+                        return Some(start + i + 1);
+                    }
+                }
+            }
+            None
+        }
+        TScalarStyle::DoubleQuoted => {
+            let mut chars = rest.char_indices().skip(1);
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    '"' => return Some(start + i + 1),
+                    _ => {}
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Return the given range of the text with the given edits made to it, or `None` if the edits
+/// overlap. Edits outside of the range are ignored.
+fn apply(text: &str, range: Range<usize>, edits: &[Edit]) -> Option<String> {
+    let mut edits = edits
+        .iter()
+        .filter(|(span, _)| span.start >= range.start && span.end <= range.end)
+        .collect::<Vec<_>>();
+    edits.sort_by_key(|(span, _)| (span.start, span.end));
+
+    let mut result = String::new();
+    let mut position = range.start;
+    for (span, replacement) in edits {
+        if span.start < position {
+            return None;
+        }
+        result.push_str(&text[position..span.start]);
+        result.push_str(replacement);
+        position = span.end;
+    }
+    result.push_str(&text[position..range.end]);
+    Some(result)
+}
+
+/// Return the name that identifies a choice: the choice itself for models whose choices are
+/// strings, or its `name` setting for the others.
+fn item_name(item: &Value) -> Option<&str> {
+    match item {
+        Value::String(name) => Some(name),
+        Value::Mapping(mapping) => mapping.get(&Value::String("name".to_string()))?.as_str(),
+        _ => None,
+    }
+}
+
+/// Return `true` if the given value is a sequence or a mapping.
+fn is_collection(value: &Value) -> bool {
+    matches!(value, Value::Sequence(_) | Value::Mapping(_))
+}
+
+/// Serialize the given value in block style, with every line indented by `indent` spaces.
+fn block(value: &Value, indent: usize) -> Option<String> {
+    let yaml = serde_yaml::to_string(value).ok()?;
+    let yaml = yaml.strip_prefix("---\n").unwrap_or(&yaml);
+    Some(
+        yaml.lines()
+            .map(|line| format!("{}{}\n", " ".repeat(indent), line))
+            .collect(),
+    )
+}
+
+/// Serialize the given scalar value, which must fit on one line.
+fn scalar(value: &Value) -> Option<String> {
+    let yaml = block(value, 0)?;
+    let yaml = yaml.strip_suffix('\n')?;
+    if yaml.contains('\n') {
+        return None;
+    }
+    Some(yaml.to_string())
+}
+
+/// Serialize a mapping entry, with its key indented by `indent` spaces and no trailing newline.
+fn entry(indent: usize, key: &Value, value: &Value) -> Option<String> {
+    Some(format!(
+        "{}{}{}",
+        " ".repeat(indent),
+        scalar(key)?,
+        entry_value(indent, value)?
+    ))
+}
+
+/// Serialize the part of a mapping entry that follows its key, starting with the colon. `indent`
+/// is how far the key is indented.
+fn entry_value(indent: usize, value: &Value) -> Option<String> {
+    match value {
+        Value::Sequence(sequence) if sequence.is_empty() => Some(": []".to_string()),
+        Value::Mapping(mapping) if mapping.is_empty() => Some(": {}".to_string()),
+        Value::Sequence(_) | Value::Mapping(_) => Some(format!(
+            ":\n{}",
+            block(value, indent + 2)?.trim_end_matches('\n')
+        )),
+        _ => Some(format!(": {}", scalar(value)?)),
+    }
+}
+
+/// Serialize a block sequence item, with its dash indented by `indent` spaces and no trailing
+/// newline.
+fn sequence_item(indent: usize, item: &Value) -> Option<String> {
+    let body = match item {
+        Value::Mapping(mapping) if !mapping.is_empty() => block(item, indent + 2)?
+            .trim_start()
+            .trim_end_matches('\n')
+            .to_string(),
+        Value::Sequence(_) | Value::Mapping(_) => return None,
+        _ => scalar(item)?,
+    };
+    Some(format!("{}- {}", " ".repeat(indent), body))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::config::{Choice, ConfigCategory};
+
+    /// Read the given config, let the given function change it, and return the updated text.
+    fn update(text: &str, change: impl FnOnce(&mut BTreeMap<String, ConfigCategory>)) -> String {
+        let mut config: ConfigDocument = serde_yaml::from_str(text).unwrap();
+        change(&mut config.categories);
+
+        let updated = update_yaml(text, &config).unwrap();
+
+        let reread: ConfigDocument = serde_yaml::from_str(&updated).unwrap();
+        assert_eq!(reread, config);
+        updated
+    }
+
+    /// Return the choices of the given lru category.
+    fn lru_choices<'a>(
+        config: &'a mut BTreeMap<String, ConfigCategory>,
+        category: &str,
+    ) -> &'a mut Vec<Choice> {
+        match config.get_mut(category) {
+            Some(ConfigCategory::Lru { choices, .. }) => choices,
+            _ => panic!("Expected an lru category"),
+        }
+    }
+
+    #[test]
+    fn test_update_yaml_defaults_comments_and_order() {
+        let text = "# Things to eat.
+restaurant:
+  model: gaussian  # Prefer the places we haven't been to.
+  choices:
+    - Spirits
+    - Centro
+    - Sitti
+code:
+  model: lru
+  choices: [Add a feature, Fix a bug]
+";
+
+        let updated = update(text, |config| {
+            if let Some(ConfigCategory::Gaussian { choices, .. }) = config.get_mut("restaurant") {
+                let choice = choices.remove(0);
+                choices.push(choice);
+            }
+        });
+
+        assert_eq!(
+            updated,
+            "# Things to eat.
+restaurant:
+  model: gaussian  # Prefer the places we haven't been to.
+  choices:
+    - Centro
+    - Sitti
+    - Spirits
+code:
+  model: lru
+  choices: [Add a feature, Fix a bug]
+"
+        );
+    }
+
+    #[test]
+    fn test_update_yaml_moves_comments_with_choices() {
+        let text = "code:
+  model: lru
+  choices:
+  # The fun one.
+  - Add a feature
+  - Fix a bug # The important one.
+  - Write some docs
+";
+
+        let updated = update(text, |config| {
+            let choices = lru_choices(config, "code");
+            let choice = choices.remove(0);
+            choices.push(choice);
+        });
+
+        assert_eq!(
+            updated,
+            "code:
+  model: lru
+  choices:
+  - Fix a bug # The important one.
+  - Write some docs
+  # The fun one.
+  - Add a feature
+"
+        );
+    }
+
+    #[test]
+    fn test_update_yaml_adds_omitted_settings() {
+        let text = "tea:
+  model: inventory
+  choices:
+    - name: Earl Grey # Hot.
+      tickets: 3
+    - name: Black
+";
+
+        let updated = update(text, |config| {
+            if let Some(ConfigCategory::Inventory { choices, .. }) = config.get_mut("tea") {
+                choices[0].tickets = 2;
+                choices[1].tickets = 0;
+            }
+        });
+
+        assert_eq!(
+            updated,
+            "tea:
+  model: inventory
+  choices:
+    - name: Earl Grey # Hot.
+      tickets: 2
+    - name: Black
+      tickets: 0
+"
+        );
+    }
+
+    #[test]
+    fn test_update_yaml_adds_and_removes_choices() {
+        let text = "code:
+  model: lru
+  choices:
+    - Add a feature # Fun.
+    - Fix a bug
+dinner:
+  model: even
+  choices: []
+";
+
+        let updated = update(text, |config| {
+            let choices = lru_choices(config, "code");
+            choices.remove(1);
+            choices.insert(0, "Write some docs".into());
+            if let Some(ConfigCategory::Even { choices, .. }) = config.get_mut("dinner") {
+                choices.push("Tacos".into());
+            }
+        });
+
+        assert_eq!(
+            updated,
+            "code:
+  model: lru
+  choices:
+    - Write some docs
+    - Add a feature # Fun.
+dinner:
+  model: even
+  choices:
+    - Tacos
+"
+        );
+    }
+
+    #[test]
+    fn test_update_yaml_new_category() {
+        let text = "# Things to do.\ncode:\n  model: lru\n  choices:\n    - Fix a bug";
+
+        let updated = update(text, |config| {
+            config.insert(
+                "activity".to_string(),
+                ConfigCategory::Even {
+                    choices: vec!["read".into()],
+                    group: None,
+                },
+            );
+        });
+
+        assert_eq!(
+            updated,
+            "# Things to do.\ncode:\n  model: lru\n  choices:\n    - Fix a bug\nactivity:\n  \
+             model: even\n  choices:\n    - read\n"
+        );
+    }
+
+    #[test]
+    fn test_update_yaml_quoted_scalars() {
+        let text =
+            "code:\n  model: lru\n  choices:\n    - 'It''s a feature'\n    - \"Fix \\\"it\\\"\"\n";
+
+        let updated = update(text, |config| {
+            let choices = lru_choices(config, "code");
+            choices[1] = "Fix it".into();
+        });
+
+        assert_eq!(
+            updated,
+            "code:\n  model: lru\n  choices:\n    - 'It''s a feature'\n    - Fix it\n"
+        );
+    }
+
+    #[test]
+    fn test_update_yaml_rewrites_category_it_cannot_edit() {
+        let text = "# Things to do.
+code: {model: lru, choices: [Add a feature, Fix a bug]}
+# Things to drink.
+tea:
+  model: even
+  choices:
+    - Black
+";
+
+        let updated = update(text, |config| {
+            lru_choices(config, "code").reverse();
+        });
+
+        assert_eq!(
+            updated,
+            "# Things to do.
+code:
+  model: lru
+  choices:
+    - Fix a bug
+    - Add a feature
+# Things to drink.
+tea:
+  model: even
+  choices:
+    - Black
+"
+        );
+    }
+
+    #[test]
+    fn test_update_yaml_rewrites_everything_it_cannot_edit() {
+        let text = "# Things to do.\ncode:\n  model: lru\n  choices:\n    - Fix a bug\ntea:\n  \
+                    model: even\n  choices:\n    - Black\n";
+
+        let updated = update(text, |config| {
+            config.remove("tea");
+        });
+
+        assert_eq!(
+            updated,
+            "---\ncode:\n  model: lru\n  choices:\n    - Fix a bug\n"
+        );
+    }
+}
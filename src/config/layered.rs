@@ -0,0 +1,668 @@
+/* Copyright © 2021 Randy Barlow
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, version 3 of the License.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
+//! # Layered configs
+//!
+//! rpick can gather its categories from several config files: the main config, the config files in
+//! a config directory, a project config found by walking up from the current directory, and any
+//! files that those include. This module reads those files in order, and remembers which file each
+//! category came from so that changes to it can be saved back to the same file. Files with the
+//! ```separate_state``` setting have the changes that picking makes saved to the state file
+//! instead.
+use std::collections::{BTreeMap, BTreeSet};
+use std::error;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use super::{ConfigCategory, ConfigDocument, ConfigError, ConfigFile, Format};
+use crate::state::{self, CategoryState};
+
+/// The names of the files that [`find_project_config`] looks for, in order of preference.
+const PROJECT_FILES: [&str; 3] = ["rpick.yml", "rpick.toml", "rpick.json"];
+
+/// The extensions of the files that are read from a config directory.
+const CONFIG_EXTENSIONS: [&str; 4] = ["json", "toml", "yaml", "yml"];
+
+/// Describes where to find the config files that make up the user's config.
+///
+/// The files are read in the order below. When more than one file has a category with the same
+/// name, the one read last is used. A file's includes are read just before the file itself, so a
+/// file's own categories take precedence over the ones it includes.
+///
+/// # Attributes
+///
+/// * `primary` - The path to the main config file. New categories are saved to it.
+/// * `format` - The format of the main config file, if it should not be chosen by its extension.
+///   Other files' formats are always chosen by their extensions.
+/// * `require_primary` - If `true`, it is an error for the main config file not to exist.
+///   Otherwise, it is treated as if it were empty.
+/// * `directory` - A directory whose ```.yml```, ```.yaml```, ```.toml```, and ```.json``` files
+///   are read after the main config, in order by name. It does not have to exist.
+/// * `project` - A project config file, which is read last.
+/// * `state` - The path to the state file, which holds the pick state of the categories in files
+///   with the ```separate_state``` setting. It does not have to exist, and it is covered by the
+///   main config file's lock.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigSources {
+    pub primary: String,
+    pub format: Option<Format>,
+    pub require_primary: bool,
+    pub directory: Option<PathBuf>,
+    pub project: Option<PathBuf>,
+    pub state: String,
+}
+
+impl ConfigSources {
+    /// Construct a new ConfigSources with just the given main config file, which must exist.
+    ///
+    /// The state file is kept next to the main config file, with the extension ```.state.yml```.
+    ///
+    /// # Arguments
+    ///
+    /// * `primary` - The path to the main config file.
+    pub fn new(primary: &str) -> ConfigSources {
+        ConfigSources {
+            primary: primary.to_string(),
+            format: None,
+            require_primary: true,
+            directory: None,
+            project: None,
+            state: Path::new(primary)
+                .with_extension("state.yml")
+                .to_string_lossy()
+                .into_owned(),
+        }
+    }
+}
+
+/// Find the project config for the given directory.
+///
+/// # Arguments
+///
+/// * `start` - The directory to start looking in, usually the current directory.
+///
+/// # Returns
+///
+/// Returns the first ```rpick.yml```, ```rpick.toml```, or ```rpick.json``` found in `start` or
+/// the closest of its parent directories, or `None` if there isn't one.
+pub fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .flat_map(|dir| PROJECT_FILES.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+/// Read the config from all of the given sources, without locking them.
+///
+/// This is meant for reading the config without changing it. Use [`LayeredConfig::lock`] to make
+/// changes.
+///
+/// # Arguments
+///
+/// * `sources` - Where to find the config files.
+///
+/// # Returns
+///
+/// Returns a mapping of category names to [`ConfigCategory`]'s, or an Error.
+pub fn read_layered(
+    sources: &ConfigSources,
+) -> Result<BTreeMap<String, ConfigCategory>, Box<dyn error::Error>> {
+    let (_, config) = Loader::load(sources, false)?;
+    Ok(config)
+}
+
+/// A config gathered from several files, which are locked for the exclusive use of this process.
+///
+/// Each file is a [`ConfigFile`], so the same locking and change detection apply to each of them.
+/// The main config file is locked right away, which also covers the state file. The other files
+/// are only locked once they are written, so files that are only read, such as read-only includes,
+/// never need a lock file.
+pub struct LayeredConfig {
+    /// The files the config was read from, in the order they were read.
+    layers: Vec<Layer>,
+    /// The index in `layers` of the main config file.
+    primary: usize,
+    /// The index in `layers` of the file that each category was read from.
+    sources: BTreeMap<String, usize>,
+    /// The path to the state file.
+    state_path: String,
+    /// The state file's contents when it was last read or written.
+    state: BTreeMap<String, CategoryState>,
+}
+
+impl LayeredConfig {
+    /// Lock and read all of the config files from the given sources.
+    ///
+    /// # Arguments
+    ///
+    /// * `sources` - Where to find the config files.
+    ///
+    /// # Returns
+    ///
+    /// Returns the LayeredConfig, which holds its locks until it is dropped, along with a mapping of
+    /// category names to [`ConfigCategory`]'s.
+    pub fn lock(
+        sources: &ConfigSources,
+    ) -> Result<(LayeredConfig, BTreeMap<String, ConfigCategory>), Box<dyn error::Error>> {
+        Loader::load(sources, true)
+    }
+
+    /// Return the path to the file that the named category was read from, or `None` if it was not
+    /// read from any of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `category` - The name of the category.
+    pub fn source(&self, category: &str) -> Option<&str> {
+        self.sources
+            .get(category)
+            .map(|index| self.layers[*index].path.as_str())
+    }
+
+    /// Save the given config, writing each category back to the file it was read from.
+    ///
+    /// New categories are saved to the main config file. Only the files whose categories changed
+    /// are written. A category that was also found in an earlier file is only saved to the file it
+    /// was read from, and the earlier file is left alone.
+    ///
+    /// The pick state of categories in files with the ```separate_state``` setting is saved to the
+    /// state file, and their files are only written if their choices were added, removed, or
+    /// otherwise changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The config that should be saved.
+    pub fn write(
+        &mut self,
+        mut config: BTreeMap<String, ConfigCategory>,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let mut updated = self
+            .layers
+            .iter()
+            .map(|layer| layer.categories.clone())
+            .collect::<Vec<_>>();
+        let mut state = self.state.clone();
+        for (name, index) in &self.sources {
+            match config.remove(name) {
+                Some(category) => {
+                    let category = self.split_state(*index, name, category, &mut state);
+                    updated[*index].insert(name.clone(), category)
+                }
+                None => {
+                    if self.layers[*index].separate_state {
+                        state.remove(name);
+                    }
+                    updated[*index].remove(name)
+                }
+            };
+        }
+        for (name, category) in config {
+            let category = self.split_state(self.primary, &name, category, &mut state);
+            updated[self.primary].insert(name, category);
+        }
+
+        for (layer, categories) in self.layers.iter_mut().zip(updated) {
+            if layer.categories != categories {
+                let file = layer
+                    .file
+                    .as_mut()
+                    .expect("A LayeredConfig always has its files open.");
+                file.write(categories.clone())?;
+                layer.categories = categories;
+            }
+        }
+        self.sources = category_sources(&self.layers);
+        if state != self.state {
+            state::write_state(&self.state_path, &state)?;
+            self.state = state;
+        }
+        Ok(())
+    }
+
+    /// Return the category that should be saved to the given layer. If the layer keeps its state
+    /// separately, the category keeps the order and tickets it has in the layer's file, and its
+    /// state is recorded in `state` unless it matches the file.
+    fn split_state(
+        &self,
+        index: usize,
+        name: &str,
+        category: ConfigCategory,
+        state: &mut BTreeMap<String, CategoryState>,
+    ) -> ConfigCategory {
+        let layer = &self.layers[index];
+        if !layer.separate_state {
+            return category;
+        }
+        let current = CategoryState::of(&category);
+        let definition = match layer.categories.get(name) {
+            Some(definition) => {
+                let mut category = category;
+                CategoryState::of(definition).apply(&mut category);
+                if &category == definition {
+                    definition.clone()
+                } else {
+                    category
+                }
+            }
+            None => category,
+        };
+        if CategoryState::of(&definition) == current {
+            state.remove(name);
+        } else {
+            state.insert(name.to_string(), current);
+        }
+        definition
+    }
+}
+
+/// One of the files that a [`LayeredConfig`] was read from.
+struct Layer {
+    /// The path to the file.
+    path: String,
+    /// The open file, or `None` if the file was read without opening it for writing.
+    file: Option<ConfigFile>,
+    /// The categories that the file had when it was last read or written.
+    categories: BTreeMap<String, ConfigCategory>,
+    /// Whether the file has the ```separate_state``` setting.
+    separate_state: bool,
+}
+
+/// Reads the files that make up a layered config.
+struct Loader {
+    /// Whether to open the files so that they can be written, and lock the main config file.
+    writable: bool,
+    /// The files that have been read so far.
+    layers: Vec<Layer>,
+    /// The paths of the files that have been read or are being read, so that no file is read twice
+    /// and include cycles are broken.
+    seen: BTreeSet<PathBuf>,
+}
+
+impl Loader {
+    /// Read the config files from the given sources.
+    fn load(
+        sources: &ConfigSources,
+        writable: bool,
+    ) -> Result<(LayeredConfig, BTreeMap<String, ConfigCategory>), Box<dyn error::Error>> {
+        let mut loader = Loader {
+            writable,
+            layers: vec![],
+            seen: BTreeSet::new(),
+        };
+
+        let primary = loader
+            .load_file(
+                Path::new(&sources.primary),
+                sources.format,
+                sources.require_primary,
+                writable,
+            )?
+            .expect("The main config file is always read first.");
+        if let Some(directory) = &sources.directory {
+            for path in config_files_in(directory)? {
+                loader.load_file(&path, None, true, false)?;
+            }
+        }
+        if let Some(project) = &sources.project {
+            loader.load_file(project, None, true, false)?;
+        }
+
+        let state = if loader.layers.iter().any(|layer| layer.separate_state) {
+            state::read_state(&sources.state).map_err(|source| ConfigError::Read {
+                path: sources.state.clone(),
+                source,
+            })?
+        } else {
+            BTreeMap::new()
+        };
+
+        let category_sources = category_sources(&loader.layers);
+        let mut config = BTreeMap::new();
+        for layer in &loader.layers {
+            config.extend(layer.categories.clone());
+        }
+        for (name, category) in config.iter_mut() {
+            if let Some(category_state) = state.get(name) {
+                if loader.layers[category_sources[name]].separate_state {
+                    category_state.apply(category);
+                }
+            }
+        }
+        let layered = LayeredConfig {
+            sources: category_sources,
+            layers: loader.layers,
+            primary,
+            state_path: sources.state.clone(),
+            state,
+        };
+        Ok((layered, config))
+    }
+
+    /// Read the given file and the files it includes, and return the index of its layer. Return
+    /// `None` if the file has already been read.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file.
+    /// * `format` - The format of the file, if it should not be chosen by its extension.
+    /// * `required` - If `false`, a missing file is treated as if it were empty.
+    /// * `lock` - Whether to lock the file right away, if this Loader opens its files for writing.
+    fn load_file(
+        &mut self,
+        path: &Path,
+        format: Option<Format>,
+        required: bool,
+        lock: bool,
+    ) -> Result<Option<usize>, Box<dyn error::Error>> {
+        if !self
+            .seen
+            .insert(fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()))
+        {
+            return Ok(None);
+        }
+        let path_string = path.to_string_lossy().into_owned();
+        let (file, document) = self
+            .read_file(&path_string, format, required, lock)
+            .map_err(|source| ConfigError::Read {
+                path: path_string.clone(),
+                source,
+            })?;
+
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        for include in &document.include {
+            self.load_file(&include_path(directory, include), None, true, false)?;
+        }
+
+        self.layers.push(Layer {
+            path: path_string,
+            file,
+            categories: document.categories,
+            separate_state: document.separate_state,
+        });
+        Ok(Some(self.layers.len() - 1))
+    }
+
+    /// Read the given file, opening it for writing if this Loader does so, and locking it if asked
+    /// to.
+    fn read_file(
+        &self,
+        path: &str,
+        format: Option<Format>,
+        required: bool,
+        lock: bool,
+    ) -> Result<(Option<ConfigFile>, ConfigDocument), Box<dyn error::Error>> {
+        let format = format.unwrap_or_else(|| Format::from_path(path));
+        let exists = required || Path::new(path).exists();
+        if self.writable {
+            let mut file = if lock {
+                ConfigFile::lock(path)?
+            } else {
+                ConfigFile::open(path)
+            };
+            file.set_format(format);
+            let document = if exists {
+                let categories = file.read()?;
+                ConfigDocument {
+                    include: file.includes().to_vec(),
+                    separate_state: file.separate_state(),
+                    categories,
+                }
+            } else {
+                ConfigDocument::default()
+            };
+            Ok((Some(file), document))
+        } else if exists {
+            Ok((None, format.parse_document(&fs::read_to_string(path)?)?))
+        } else {
+            Ok((None, ConfigDocument::default()))
+        }
+    }
+}
+
+/// Return the index of the layer that each category was read from. When more than one layer has a
+/// category, the last one wins.
+fn category_sources(layers: &[Layer]) -> BTreeMap<String, usize> {
+    let mut sources = BTreeMap::new();
+    for (index, layer) in layers.iter().enumerate() {
+        for name in layer.categories.keys() {
+            sources.insert(name.clone(), index);
+        }
+    }
+    sources
+}
+
+/// Return the config files in the given directory, in order by name. A directory that does not
+/// exist has no config files.
+fn config_files_in(directory: &Path) -> Result<Vec<PathBuf>, Box<dyn error::Error>> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(error) => return Err(error.into()),
+    };
+    let mut paths = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        if path.is_file()
+            && extension.is_some_and(|extension| CONFIG_EXTENSIONS.contains(&extension.as_str()))
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Return the path of an included file. Relative paths are relative to the directory of the file
+/// that includes them, and a leading ```~/``` stands for the user's home directory.
+fn include_path(directory: &Path, include: &str) -> PathBuf {
+    if let Some(rest) = include.strip_prefix("~/") {
+        if let Some(home) = dirs_next::home_dir() {
+            return home.join(rest);
+        }
+    }
+    directory.join(include)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write the given files into the given directory.
+    fn write_files(directory: &Path, files: &[(&str, &str)]) {
+        for (name, contents) in files {
+            let path = directory.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+    }
+
+    /// Return an lru category with the given choices.
+    fn lru(choices: &[&str]) -> ConfigCategory {
+        ConfigCategory::Lru {
+            choices: choices.iter().map(|choice| (*choice).into()).collect(),
+            group: None,
+        }
+    }
+
+    #[test]
+    fn test_find_project_config() {
+        let dir = tempfile::tempdir().unwrap();
+        write_files(
+            dir.path(),
+            &[("rpick.toml", ""), ("a/rpick.json", ""), ("a/b/c/file", "")],
+        );
+
+        assert_eq!(
+            find_project_config(&dir.path().join("a/b/c")),
+            Some(dir.path().join("a/rpick.json"))
+        );
+        assert_eq!(
+            find_project_config(dir.path()),
+            Some(dir.path().join("rpick.toml"))
+        );
+    }
+
+    #[test]
+    fn test_read_layered() {
+        let dir = tempfile::tempdir().unwrap();
+        write_files(
+            dir.path(),
+            &[
+                (
+                    "rpick.yml",
+                    "include: [team/team.toml]\nlunch:\n  model: lru\n  choices: [main]\n",
+                ),
+                (
+                    "team/team.toml",
+                    "include = ['../rpick.yml']\n[lunch]\nmodel = 'lru'\nchoices = ['team']\n\
+                     [tea]\nmodel = 'lru'\nchoices = ['team']\n",
+                ),
+                (
+                    "rpick/b.json",
+                    r#"{"code": {"model": "lru", "choices": ["b"]}}"#,
+                ),
+                ("rpick/a.yml", "code: {model: lru, choices: [a]}\n"),
+                ("rpick/notes.txt", "Not a config."),
+                (
+                    "project/rpick.yml",
+                    "tea: {model: lru, choices: [project]}\n",
+                ),
+            ],
+        );
+        let sources = ConfigSources {
+            directory: Some(dir.path().join("rpick")),
+            project: Some(dir.path().join("project/rpick.yml")),
+            ..ConfigSources::new(dir.path().join("rpick.yml").to_str().unwrap())
+        };
+
+        let config = read_layered(&sources).unwrap();
+
+        let mut expected = BTreeMap::new();
+        expected.insert("code".to_string(), lru(&["b"]));
+        expected.insert("lunch".to_string(), lru(&["main"]));
+        expected.insert("tea".to_string(), lru(&["project"]));
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn test_read_layered_missing_primary() {
+        let dir = tempfile::tempdir().unwrap();
+        let primary = dir.path().join("rpick.yml");
+        let mut sources = ConfigSources::new(primary.to_str().unwrap());
+
+        let error = read_layered(&sources).unwrap_err();
+        sources.require_primary = false;
+        let config = read_layered(&sources).unwrap();
+
+        assert!(error.to_string().starts_with(&format!(
+            "Error reading config file at {}: ",
+            primary.display()
+        )));
+        assert!(config.is_empty());
+    }
+
+    #[test]
+    fn test_layered_config_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let team = "# The team's categories.\ncode:\n  model: lru\n  choices:\n    - a\n    - b\n";
+        let project = "tea: {model: lru, choices: [project]}\n";
+        write_files(
+            dir.path(),
+            &[
+                ("rpick.yml", "include:\n  - team.yml\n"),
+                ("team.yml", team),
+                ("project.yml", project),
+            ],
+        );
+        let primary = dir.path().join("rpick.yml");
+        let sources = ConfigSources {
+            project: Some(dir.path().join("project.yml")),
+            ..ConfigSources::new(primary.to_str().unwrap())
+        };
+        let lock_path = |name: &str| dir.path().join(format!("{}.lock", name));
+        let (mut layered, mut config) = LayeredConfig::lock(&sources).unwrap();
+        // Only the main config file is locked until the others are written.
+        assert!(lock_path("rpick.yml").exists());
+        assert!(!lock_path("team.yml").exists());
+        assert_eq!(
+            layered.source("code"),
+            Some(dir.path().join("team.yml").to_str().unwrap())
+        );
+        assert_eq!(layered.source("lunch"), None);
+
+        config.insert("code".to_string(), lru(&["b", "a"]));
+        config.insert("lunch".to_string(), lru(&["new"]));
+        layered.write(config.clone()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("team.yml")).unwrap(),
+            "# The team's categories.\ncode:\n  model: lru\n  choices:\n    - b\n    - a\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("project.yml")).unwrap(),
+            project
+        );
+        assert_eq!(
+            fs::read_to_string(&primary).unwrap(),
+            "include:\n  - team.yml\nlunch:\n  model: lru\n  choices:\n    - new\n"
+        );
+        assert_eq!(layered.source("lunch"), Some(primary.to_str().unwrap()));
+        assert!(lock_path("team.yml").exists());
+        assert!(!lock_path("project.yml").exists());
+        drop(layered);
+        assert!(!lock_path("rpick.yml").exists());
+        assert!(!lock_path("team.yml").exists());
+        assert_eq!(read_layered(&sources).unwrap(), config);
+    }
+
+    #[test]
+    fn test_layered_config_separate_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let team = "separate_state: true\ncode:\n  model: lru\n  choices: [a, b, c]\n";
+        write_files(dir.path(), &[("rpick.yml", team)]);
+        let primary = dir.path().join("rpick.yml");
+        let sources = ConfigSources::new(primary.to_str().unwrap());
+        let state_path = dir.path().join("rpick.state.yml");
+        assert_eq!(sources.state, state_path.to_str().unwrap());
+        let (mut layered, mut config) = LayeredConfig::lock(&sources).unwrap();
+
+        config.insert("code".to_string(), lru(&["b", "c", "a"]));
+        layered.write(config).unwrap();
+        drop(layered);
+
+        assert_eq!(fs::read_to_string(&primary).unwrap(), team);
+        assert_eq!(
+            fs::read_to_string(&state_path).unwrap(),
+            "---\ncode:\n  order:\n    - b\n    - c\n    - a\n"
+        );
+        assert_eq!(
+            read_layered(&sources).unwrap()["code"],
+            lru(&["b", "c", "a"])
+        );
+
+        // Choices added to or removed from the config file are reconciled with the state.
+        fs::write(
+            &primary,
+            "separate_state: true\ncode:\n  model: lru\n  choices: [a, c, d]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_layered(&sources).unwrap()["code"],
+            lru(&["d", "c", "a"])
+        );
+    }
+}
@@ -13,15 +13,17 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
 //! # The Engine
 //!
 //! This module defines the Engine, the core of the rpick crate.
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
+use chrono::{DateTime, Local, SubsecRound};
 use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use rand_distr::{Distribution, Normal};
 use statrs::distribution::ContinuousCDF;
 use thiserror::Error;
 
-use crate::{config, ui};
+use crate::{config, group, history, tags, ui};
 
 /// The rpick Engine object allows you to write your own rpick interface.
 ///
@@ -29,28 +31,70 @@ use crate::{config, ui};
 ///
 /// * `ui` - This is a struct that implements the [`ui::Ui`] trait.
 /// * `rng` - This must be a random number generator that implements the [`rand::RngCore`]
-///           trait.
-pub struct Engine<'ui, U> {
+///   trait.
+/// * `display_tables` - If false, chance tables are not displayed before each prompt. This is
+///   used to display a single combined table when picking several items at once.
+/// * `rejected` - The choices the user has rejected since the last accepted choice.
+/// * `vetoes` - The participants who vetoed each choice since the last accepted choice, for
+///   categories picked by a group.
+/// * `history_entries` - A [`history::HistoryEntry`] for each choice accepted during the most
+///   recent pick.
+/// * `now` - The time that picks are made at, or `None` to use the current time.
+/// * `tag_filter` - Only choices whose tags match this filter are picked.
+/// * `details` - The details of the choices in the category being picked from, keyed by name, for
+///   the choices that have any.
+/// * `group` - The group that decides whether to accept choices from the category being picked
+///   from, if it has one.
+/// * `participants` - The participants who are present, whose preferences weigh the choices.
+/// * `preferences` - The preferences for each choice in the category being picked from, keyed by
+///   choice and then by participant, or `None` if its model doesn't take preferences.
+/// * `unavailable` - Why each choice in the category being picked from can't be picked at the
+///   time of the pick, keyed by name, for the choices that the tag filter lets through.
+pub struct Engine<'ui, U: ?Sized> {
     ui: &'ui U,
     rng: Box<dyn rand::RngCore>,
+    display_tables: bool,
+    rejected: Vec<String>,
+    vetoes: BTreeMap<String, Vec<String>>,
+    history_entries: Vec<history::HistoryEntry>,
+    now: Option<DateTime<Local>>,
+    tag_filter: tags::TagFilter,
+    details: BTreeMap<String, config::ChoiceDetails>,
+    group: Option<group::Group>,
+    participants: Vec<String>,
+    preferences: Option<BTreeMap<String, BTreeMap<String, u64>>>,
+    unavailable: BTreeMap<String, String>,
+    category: String,
 }
 
 impl<'a, 'ui, U> Engine<'ui, U>
 where
-    U: ui::Ui,
+    U: ui::Ui + ?Sized,
 {
     /// Instantiate an Engine.
     ///
     /// # Arguments
     ///
     /// * `ui` - This is a struct that implements the [`ui::Ui`] trait. It is how rpick will
-    ///     interact with the caller.
-    pub fn new(ui: &'ui U) -> Engine<U> {
+    ///   interact with the caller.
+    pub fn new(ui: &'ui U) -> Engine<'ui, U> {
         let rng = rand::thread_rng();
 
         Engine {
             ui,
             rng: Box::new(rng),
+            display_tables: true,
+            rejected: vec![],
+            vetoes: BTreeMap::new(),
+            history_entries: vec![],
+            now: None,
+            tag_filter: tags::TagFilter::default(),
+            details: BTreeMap::new(),
+            group: None,
+            participants: vec![],
+            preferences: None,
+            unavailable: BTreeMap::new(),
+            category: String::new(),
         }
     }
 
@@ -59,7 +103,7 @@ where
     /// # Arguments
     ///
     /// * `config` - A mapping of category names to [`config::ConfigCategory`] objects, which
-    ///     contain the parameters which should be used for the pick.
+    ///   contain the parameters which should be used for the pick.
     /// * `category` - The category you wish to choose from.
     ///
     /// # Returns
@@ -70,157 +114,707 @@ where
         config: &mut BTreeMap<String, config::ConfigCategory>,
         category: String,
     ) -> Result<String, PickError> {
-        let config_category = config.get_mut(&category[..]);
-        match config_category {
-            Some(category) => match category {
-                config::ConfigCategory::Even { choices } => Ok(self.pick_even(choices)),
-                config::ConfigCategory::Gaussian {
-                    choices,
-                    stddev_scaling_factor,
-                } => Ok(self.pick_gaussian(choices, *stddev_scaling_factor)),
-                config::ConfigCategory::Inventory { choices } => Ok(self.pick_inventory(choices)),
-                config::ConfigCategory::Lottery { choices } => Ok(self.pick_lottery(choices)),
-                config::ConfigCategory::Lru { choices } => Ok(self.pick_lru(choices)),
-                config::ConfigCategory::Weighted { choices } => Ok(self.pick_weighted(choices)),
-            },
+        let mut choices = self.pick_many(config, category, 1, &[])?;
+        Ok(choices.remove(0))
+    }
+
+    /// Pick `count` distinct items from the [`config::ConfigCategory`] referenced by the given
+    /// `category`.
+    ///
+    /// Items are drawn without replacement: once a choice has been accepted, it is not offered
+    /// again during this call. The category's model updates its state once for each accepted
+    /// item, in the order they were accepted. Choices that are excluded, that the tag filter
+    /// leaves out, or that are unavailable at the time of the pick are never offered, but the
+    /// model's state still covers them, so that an lru category, for example, keeps its order
+    /// across picks with different exclusions. If more than one item is requested, a single chance
+    /// table showing all of the accepted items is displayed once they have all been picked.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - A mapping of category names to [`config::ConfigCategory`] objects, which
+    ///   contain the parameters which should be used for the pick.
+    /// * `category` - The category you wish to choose from.
+    /// * `count` - How many distinct items to pick. It must be at least 1.
+    /// * `exclude` - The names of choices that must not be picked this time, such as a restaurant
+    ///   that is closed today. Each must be the name of a choice in the category.
+    ///
+    /// # Returns
+    ///
+    /// This will return the chosen items, in the order they were accepted.
+    pub fn pick_many(
+        &mut self,
+        config: &mut BTreeMap<String, config::ConfigCategory>,
+        category: String,
+        count: usize,
+        exclude: &[String],
+    ) -> Result<Vec<String>, PickError> {
+        self.history_entries.clear();
+        if count == 0 {
+            return Err(PickError::NothingRequested(category));
+        }
+        let config_category = match config.get_mut(&category[..]) {
+            Some(config_category) => config_category,
+            None => return Err(PickError::CategoryNotFound(category)),
+        };
+
+        self.check_participants(&category, config_category)?;
+        config_category.restock(self.now());
+        let names = config_category
+            .choice_names()
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        if let Some(choice) = exclude.iter().find(|c| !names.contains(c.as_str())) {
+            return Err(PickError::ChoiceNotFound {
+                category,
+                choice: choice.clone(),
+            });
+        }
+        if names.is_empty() {
+            return Err(PickError::NoChoices(category));
+        }
+        let mut excluded = self.filtered_out(config_category);
+        if names.iter().all(|name| excluded.iter().any(|e| e == name)) {
+            return Err(PickError::NoMatchingChoices(category));
+        }
+        self.unavailable = unavailable_choices(config_category, self.now());
+        self.unavailable.retain(|name, _| !excluded.contains(name));
+        excluded.extend(self.unavailable.keys().cloned());
+        if names.iter().all(|name| excluded.iter().any(|e| e == name)) {
+            return Err(PickError::AllUnavailable(category));
+        }
+        excluded.extend(exclude.iter().cloned());
+        let available = names
+            .iter()
+            .filter(|name| !excluded.iter().any(|e| e == *name))
+            .count();
+        if available == 0 {
+            return Err(PickError::AllExcluded(category));
+        }
+        check_candidates(
+            &category,
+            config_category,
+            &excluded,
+            &self.participants,
+            self.now(),
+        )?;
+        // Choices without tickets or weight can't be picked, so they don't count.
+        let available =
+            candidate_weights(config_category, &excluded, &self.participants, self.now())
+                .into_iter()
+                .filter(|(_, weight)| *weight > 0)
+                .map(|(name, _)| name)
+                .collect::<BTreeSet<_>>()
+                .len();
+        if count > available {
+            return Err(PickError::TooFewChoices {
+                category,
+                available,
+                requested: count,
+            });
+        }
+
+        self.details = choice_details(config_category);
+        // Only the participants who are present vote, if it is known who they are.
+        self.group = config_category.group().cloned().and_then(|mut group| {
+            if !self.participants.is_empty() {
+                group.participants.retain(|p| self.participants.contains(p));
+            }
+            (!group.participants.is_empty()).then_some(group)
+        });
+        self.preferences = choice_preferences(config_category);
+        let snapshot = if count > 1 {
+            Some((config_category.clone(), excluded.clone()))
+        } else {
+            None
+        };
+
+        self.display_tables = snapshot.is_none();
+        self.rejected.clear();
+        self.vetoes.clear();
+        let mut picks = Vec::with_capacity(count);
+        while picks.len() < count {
+            let choice = self.pick_category(&category, config_category, &excluded)?;
+            self.history_entries.push(history::HistoryEntry {
+                timestamp: self.now(),
+                category: category.clone(),
+                model: config_category.model().to_string(),
+                choice: choice.clone(),
+                rejected: std::mem::take(&mut self.rejected),
+                vetoes: std::mem::take(&mut self.vetoes),
+            });
+            excluded.push(choice.clone());
+            picks.push(choice);
+        }
+        self.display_tables = true;
+        // The lottery model gives every choice more tickets with each pick, including the choices
+        // accepted earlier in this one, so they are emptied once all of the picks are made.
+        if let config::ConfigCategory::Lottery { choices, .. } = config_category {
+            for choice in choices.iter_mut().filter(|c| picks.contains(&c.name)) {
+                choice.tickets = 0;
+            }
+        }
+
+        if let Some((snapshot, excluded)) = snapshot {
+            if self.ui.call_display_table() {
+                self.display_category_table(&snapshot, &picks, &excluded, &self.unavailable);
+            }
+        }
+
+        Ok(picks)
+    }
+
+    /// Display the chance table for the [`config::ConfigCategory`] referenced by the given
+    /// `category`, without prompting the user or changing the category. Choices that are
+    /// unavailable at the time are listed at the bottom of the table, with the reason, and
+    /// inventory choices are shown with the tickets their restock policies would give them.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - A mapping of category names to [`config::ConfigCategory`] objects.
+    /// * `category` - The category you wish to display.
+    pub fn display_category(
+        &self,
+        config: &BTreeMap<String, config::ConfigCategory>,
+        category: String,
+    ) -> Result<(), PickError> {
+        match config.get(&category[..]) {
+            Some(config_category) => {
+                check_parameters(&category, config_category)?;
+                self.check_participants(&category, config_category)?;
+                let mut config_category = config_category.clone();
+                config_category.restock(self.now());
+                let unavailable = unavailable_choices(&config_category, self.now());
+                let excluded = unavailable.keys().cloned().collect::<Vec<_>>();
+                self.display_category_table(&config_category, &[], &excluded, &unavailable);
+                Ok(())
+            }
             None => Err(PickError::CategoryNotFound(category)),
         }
     }
 
+    /// Simulate many picks from the [`config::ConfigCategory`] referenced by the given `category`,
+    /// to preview how often each choice would be picked in the long run.
+    ///
+    /// The simulation runs against a copy of the category, accepting the first choice offered in
+    /// each round and applying the model's state changes between rounds. A day passes between
+    /// rounds, and rounds in which every choice is unavailable, or cooling down for the recency
+    /// model, pass without a pick. Only the choices that match the tag filter are simulated. The
+    /// given `config` is not changed, and the Ui is not used. Inventory choices are restocked as
+    /// the days pass. If every choice runs out of tickets partway through, the simulation stops
+    /// early and reports on the rounds that were completed.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - A mapping of category names to [`config::ConfigCategory`] objects.
+    /// * `category` - The category you wish to simulate.
+    /// * `rounds` - How many picks to simulate.
+    pub fn simulate(
+        &mut self,
+        config: &BTreeMap<String, config::ConfigCategory>,
+        category: String,
+        rounds: usize,
+    ) -> Result<Simulation, PickError> {
+        let mut config_category = match config.get(&category[..]) {
+            Some(config_category) => config_category.clone(),
+            None => return Err(PickError::CategoryNotFound(category)),
+        };
+        self.check_participants(&category, &config_category)?;
+
+        let excluded = self.filtered_out(&config_category);
+        let mut names = config_category
+            .choice_names()
+            .into_iter()
+            .map(String::from)
+            .filter(|name| !excluded.contains(name))
+            .collect::<Vec<_>>();
+        let mut seen = BTreeSet::new();
+        names.retain(|name| seen.insert(name.clone()));
+        if names.is_empty() && !excluded.is_empty() {
+            return Err(PickError::NoMatchingChoices(category));
+        }
+        let mut picked_rounds: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+        let ui = ui::AutoAcceptUi::new();
+        let mut engine = Engine::new(&ui);
+        engine.participants = self.participants.clone();
+        // Lend our rng to the simulation, so that seeding this Engine seeds the simulation.
+        std::mem::swap(&mut self.rng, &mut engine.rng);
+        let start = self.now();
+        let mut result = Ok(rounds);
+        for round in 0..rounds {
+            let now = start + chrono::Duration::days(round as i64);
+            engine.now = Some(now);
+            let mut excluded = excluded.clone();
+            excluded.extend(unavailable_choices(&config_category, now).into_keys());
+            if !names.is_empty() && names.iter().all(|name| excluded.contains(name)) {
+                continue;
+            }
+            match engine.pick_category(&category, &mut config_category, &excluded) {
+                Ok(choice) => picked_rounds.entry(choice).or_default().push(round),
+                Err(PickError::AllCoolingDown(_)) => {}
+                Err(PickError::AllTicketsExhausted(_)) if round > 0 => {
+                    result = Ok(round);
+                    break;
+                }
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            }
+        }
+        std::mem::swap(&mut self.rng, &mut engine.rng);
+        let rounds = result?;
+
+        let choices = names
+            .into_iter()
+            .map(|name| {
+                let picked = picked_rounds.remove(&name).unwrap_or_default();
+                SimulatedChoice::new(name, &picked, rounds)
+            })
+            .collect();
+        Ok(Simulation { rounds, choices })
+    }
+
+    /// Return a [`history::HistoryEntry`] for each choice that was accepted during the most recent
+    /// call to [`Engine::pick`] or [`Engine::pick_many`], in the order they were accepted.
+    pub fn history_entries(&self) -> &[history::HistoryEntry] {
+        &self.history_entries
+    }
+
     /// Use the given random number generator rather than the default.
     pub fn set_rng<R: 'static + Rng>(&mut self, rng: R) {
         self.rng = Box::new(rng);
     }
 
-    /// Express disapproval to the user.
-    fn express_disapproval(&mut self) {
-        self.ui.info("🤨");
+    /// Use a ChaCha8 random number generator seeded with the given value rather than the default,
+    /// so that picks can be reproduced.
+    ///
+    /// ChaCha8 produces the same stream of numbers on every platform, so the same seed, config,
+    /// and answers to the Ui's prompts lead to the same picks on any machine or architecture
+    /// running the same version of rpick.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The value to seed the random number generator with.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.set_rng(ChaCha8Rng::seed_from_u64(seed));
+    }
+
+    /// Pick as if it were the given time, rather than the current time.
+    ///
+    /// The time decides how much each choice in the recency model weighs and whether it is
+    /// cooling down, and it is recorded as the time that choices were picked.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time that picks should be made at.
+    pub fn set_now(&mut self, now: DateTime<Local>) {
+        self.now = Some(now);
+    }
+
+    /// Return the time that picks are made at, which is the current time unless
+    /// [`Engine::set_now`] was called.
+    pub fn now(&self) -> DateTime<Local> {
+        self.now.unwrap_or_else(Local::now)
+    }
+
+    /// Only pick choices whose tags match the given filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_filter` - The filter that choices' tags must match.
+    pub fn set_tag_filter(&mut self, tag_filter: tags::TagFilter) {
+        self.tag_filter = tag_filter;
+    }
+
+    /// Weigh choices by the combined preferences of the given participants, who are present for
+    /// the pick. Only the weighted and lottery models take preferences. Participants who haven't
+    /// given a choice a preference count as giving it 1, and the category's group decides how the
+    /// preferences are combined. See [`group::Combine`].
+    ///
+    /// # Arguments
+    ///
+    /// * `participants` - The names of the participants who are present.
+    pub fn set_participants(&mut self, participants: Vec<String>) {
+        self.participants = participants;
+    }
+
+    /// Return an error if a participant who is present is unknown to the given category, because
+    /// they are not in its group and have not given any of its choices a preference.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the category, which is used in errors.
+    /// * `category` - The category to check.
+    fn check_participants(
+        &self,
+        name: &str,
+        category: &config::ConfigCategory,
+    ) -> Result<(), PickError> {
+        let preferences = category.choice_preferences().unwrap_or_default();
+        let unknown = self.participants.iter().find(|participant| {
+            !category
+                .group()
+                .is_some_and(|group| group.participants.contains(participant))
+                && !preferences
+                    .iter()
+                    .any(|(_, preferences)| preferences.contains_key(*participant))
+        });
+        match unknown {
+            Some(participant) => Err(PickError::UnknownParticipant {
+                category: name.to_string(),
+                participant: participant.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Return the names of the choices in the given category that the tag filter leaves out.
+    ///
+    /// A name is left out only if none of the choices with that name match the filter.
+    fn filtered_out(&self, category: &config::ConfigCategory) -> Vec<String> {
+        if self.tag_filter.is_empty() {
+            return vec![];
+        }
+        let choice_tags = category.choice_tags();
+        let matching = choice_tags
+            .iter()
+            .filter(|(_, tags)| self.tag_filter.matches(tags))
+            .map(|(name, _)| *name)
+            .collect::<BTreeSet<_>>();
+        choice_tags
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| !matching.contains(name))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    /// Return the error for a pick in which every choice was offered and rejected, which ends the
+    /// pick rather than offering the same choices again.
+    fn all_rejected(&self) -> PickError {
+        PickError::AllRejected(self.category.clone())
     }
 
     /// Prompt the user for consent for the given choice, returning a bool true if they accept the
-    /// choice, or false if they do not.
-    fn get_consent(&mut self, choice: &str) -> bool {
-        self.ui.prompt_choice(choice)
+    /// choice, or false if they do not. If the Ui can no longer answer, [`PickError::Aborted`] is
+    /// returned instead.
+    ///
+    /// If the category is picked by a group, each participant votes on the choice instead, and the
+    /// group's rule decides whether it is accepted. Any vetoes are recorded.
+    fn get_consent(&mut self, choice: &str) -> Result<bool, PickError> {
+        let accepted = match (&self.group, self.details.get(choice)) {
+            (Some(group), _) => {
+                let mut votes = Vec::with_capacity(group.participants.len());
+                for participant in &group.participants {
+                    let vote = self.ui.prompt_vote(participant, choice);
+                    if vote == group::Vote::Veto {
+                        let vetoed_by = self.vetoes.entry(choice.to_string()).or_default();
+                        if !vetoed_by.contains(participant) {
+                            vetoed_by.push(participant.clone());
+                        }
+                    }
+                    votes.push(vote);
+                }
+                group.rule.accepts(&votes)
+            }
+            (None, Some(details)) => self.ui.prompt_choice_with_details(choice, details),
+            (None, None) => self.ui.prompt_choice(choice),
+        };
+        if !accepted {
+            if self.ui.is_aborted() {
+                return Err(PickError::Aborted);
+            }
+            self.rejected.push(choice.to_string());
+        }
+        Ok(accepted)
+    }
+
+    /// Return true if a chance table should be built and handed to the Ui before prompting.
+    fn should_display_table(&self) -> bool {
+        self.display_tables && self.ui.call_display_table()
+    }
+
+    /// Pick an item from the given category, ignoring any choices named in `excluded`. Inventory
+    /// choices are restocked first.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the category, which is used in errors.
+    /// * `category` - The category to pick from.
+    /// * `excluded` - The names of choices that must not be picked.
+    fn pick_category(
+        &mut self,
+        name: &str,
+        category: &mut config::ConfigCategory,
+        excluded: &[String],
+    ) -> Result<String, PickError> {
+        category.restock(self.now());
+        check_candidates(name, category, excluded, &self.participants, self.now())?;
+        self.category = name.to_string();
+        let combine = combine(category);
+        match category {
+            config::ConfigCategory::Even { choices, .. } => self.pick_even(choices, excluded),
+            config::ConfigCategory::Gaussian {
+                choices,
+                stddev_scaling_factor,
+                ..
+            } => self.pick_gaussian(choices, *stddev_scaling_factor, excluded),
+            config::ConfigCategory::Inventory { choices, .. } => {
+                self.pick_inventory(choices, excluded)
+            }
+            config::ConfigCategory::Lottery { choices, .. } => {
+                self.pick_lottery(choices, combine, excluded)
+            }
+            config::ConfigCategory::Lru { choices, .. } => self.pick_lru(choices, excluded),
+            config::ConfigCategory::Recency { choices, .. } => self.pick_recency(choices, excluded),
+            config::ConfigCategory::Rotation { choices, .. } => {
+                self.pick_rotation(choices, excluded)
+            }
+            config::ConfigCategory::Weighted { choices, .. } => {
+                self.pick_weighted(choices, combine, excluded)
+            }
+        }
     }
 
     /// Use an even distribution random model to pick from the given choices.
-    fn pick_even(&mut self, choices: &[String]) -> String {
+    fn pick_even(
+        &mut self,
+        choices: &[config::Choice],
+        excluded: &[String],
+    ) -> Result<String, PickError> {
         let initialize_candidates = || {
             choices
                 .iter()
                 .enumerate()
-                .map(|x| ((x.0, x.1), 1))
+                .filter(|x| !excluded.contains(&x.1.name))
+                .map(|x| ((x.0, &x.1.name), 1))
                 .collect::<Vec<_>>()
         };
 
-        let index = self.pick_weighted_common(&initialize_candidates);
+        let index = self.pick_weighted_common(&initialize_candidates)?;
 
-        choices[index].clone()
+        Ok(choices[index].name.clone())
     }
 
     /// Run the gaussian model for the given choices and standard deviation scaling factor. When the
     /// user accepts a choice, move that choice to end of the choices Vector and return.
-    fn pick_gaussian(&mut self, choices: &mut Vec<String>, stddev_scaling_factor: f64) -> String {
-        let mut candidates = choices.clone();
+    fn pick_gaussian(
+        &mut self,
+        choices: &mut Vec<config::Choice>,
+        stddev_scaling_factor: f64,
+        excluded: &[String],
+    ) -> Result<String, PickError> {
+        let mut candidates = choices
+            .iter()
+            .map(|x| &x.name)
+            .filter(|x| !excluded.contains(x))
+            .cloned()
+            .collect::<Vec<_>>();
         let mut index;
 
         loop {
             let stddev = (candidates.len() as f64) / stddev_scaling_factor;
-            let normal = Normal::new(0.0, stddev).unwrap();
+            let normal = Normal::new(0.0, stddev)
+                .expect("The candidates and stddev_scaling_factor must have been checked.");
             index = normal.sample(&mut self.rng).abs() as usize;
 
             if let Some(value) = candidates.get(index) {
-                if self.ui.call_display_table() {
-                    self.display_gaussian_chance_table(index, &candidates, stddev);
+                if self.should_display_table() {
+                    self.display_gaussian_chance_table(
+                        &[index],
+                        &candidates,
+                        stddev,
+                        &self.details,
+                        &self.unavailable,
+                    );
                 }
 
-                if self.get_consent(&value[..]) {
-                    index = choices.iter().position(|x| x == value).unwrap();
+                if self.get_consent(&value[..])? {
+                    index = choices.iter().position(|x| &x.name == value).unwrap();
                     break;
                 } else if candidates.len() > 1 {
                     index = candidates.iter().position(|x| x == value).unwrap();
                     candidates.remove(index);
                 } else {
-                    self.express_disapproval();
-                    candidates = choices.clone();
+                    return Err(self.all_rejected());
                 }
             }
         }
 
         let value = choices.remove(index);
-        choices.push(value.clone());
-        value
+        let name = value.name.clone();
+        choices.push(value);
+        Ok(name)
     }
 
     /// Run the inventory model for the given choices.
-    fn pick_inventory(&mut self, choices: &mut Vec<config::InventoryChoice>) -> String {
+    fn pick_inventory(
+        &mut self,
+        choices: &mut [config::InventoryChoice],
+        excluded: &[String],
+    ) -> Result<String, PickError> {
         let initialize_candidates = || {
             choices
                 .iter()
                 .enumerate()
-                .filter(|x| x.1.tickets > 0)
+                .filter(|x| x.1.tickets > 0 && !excluded.contains(&x.1.name))
                 .map(|x| ((x.0, &x.1.name), x.1.tickets))
                 .collect::<Vec<_>>()
         };
 
-        let index = self.pick_weighted_common(&initialize_candidates);
+        let index = self.pick_weighted_common(&initialize_candidates)?;
 
         choices[index].tickets -= 1;
-        choices[index].name.clone()
+        Ok(choices[index].name.clone())
     }
 
     /// Run the Lru model for the given choices. When the user accepts a choice, move that choice to
     /// the end of the choices Vector and return.
-    fn pick_lru(&mut self, choices: &mut Vec<String>) -> String {
-        for (index, choice) in choices.iter().enumerate() {
-            if self.ui.call_display_table() {
-                self.display_lru_table(index, choices);
+    fn pick_lru(
+        &mut self,
+        choices: &mut Vec<config::Choice>,
+        excluded: &[String],
+    ) -> Result<String, PickError> {
+        let candidates = choices
+            .iter()
+            .map(|x| &x.name)
+            .filter(|x| !excluded.contains(x))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for (index, choice) in candidates.iter().enumerate() {
+            if self.should_display_table() {
+                self.display_lru_table(
+                    &[0],
+                    &candidates[index..],
+                    &self.details,
+                    &self.unavailable,
+                );
             }
 
-            if self.get_consent(&choice[..]) {
+            if self.get_consent(&choice[..])? {
+                let index = choices.iter().position(|x| &x.name == choice).unwrap();
                 let chosen = choices.remove(index);
-                choices.push(chosen.clone());
-                return chosen;
+                choices.push(chosen);
+                return Ok(choice.clone());
             }
         }
-        self.express_disapproval();
-        // If we've gotten here, the user hasn't made a choice. So… let's do it again!
-        self.pick_lru(choices)
+        Err(self.all_rejected())
     }
 
-    /// Run the lottery model for the given choices.
-    fn pick_lottery(&mut self, choices: &mut Vec<config::LotteryChoice>) -> String {
+    /// Run the lottery model for the given choices. Each choice's tickets are scaled by the
+    /// preferences of the participants who are present, combined as `combine` says.
+    fn pick_lottery(
+        &mut self,
+        choices: &mut [config::LotteryChoice],
+        combine: group::Combine,
+        excluded: &[String],
+    ) -> Result<String, PickError> {
+        let weights = choices
+            .iter()
+            .map(|c| preferred_weight(c.tickets, &c.preferences, &self.participants, combine))
+            .collect::<Vec<_>>();
         let initialize_candidates = || {
             choices
                 .iter()
                 .enumerate()
-                .filter(|x| x.1.tickets > 0)
-                .map(|x| ((x.0, &x.1.name), x.1.tickets))
+                .filter(|x| weights[x.0] > 0 && !excluded.contains(&x.1.name))
+                .map(|x| ((x.0, &x.1.name), weights[x.0]))
                 .collect::<Vec<_>>()
         };
 
-        let index = self.pick_weighted_common(&initialize_candidates);
+        let index = self.pick_weighted_common(&initialize_candidates)?;
 
         for choice in choices.iter_mut() {
             choice.tickets += choice.weight;
         }
         choices[index].tickets = 0;
-        choices[index].name.clone()
+        Ok(choices[index].name.clone())
+    }
+
+    /// Run the recency model for the given choices. When the user accepts a choice, record the
+    /// time of the pick as the time it was last picked.
+    fn pick_recency(
+        &mut self,
+        choices: &mut [config::RecencyChoice],
+        excluded: &[String],
+    ) -> Result<String, PickError> {
+        let now = self.now();
+        let weights = recency_weights(choices, now);
+        let initialize_candidates = || {
+            choices
+                .iter()
+                .enumerate()
+                .filter(|x| weights[x.0] > 0 && !excluded.contains(&x.1.name))
+                .map(|x| ((x.0, &x.1.name), weights[x.0]))
+                .collect::<Vec<_>>()
+        };
+
+        let index = self.pick_weighted_common(&initialize_candidates)?;
+
+        choices[index].last_picked = Some(now.trunc_subsecs(0));
+        Ok(choices[index].name.clone())
+    }
+
+    /// Run the rotation model for the given choices. The available choices with the smallest
+    /// share of the picks for their capacity are offered first, in a random order, followed by the
+    /// others in order of their shares. When the user accepts a choice, its assignments are
+    /// increased by 1.
+    fn pick_rotation(
+        &mut self,
+        choices: &mut [config::RotationChoice],
+        excluded: &[String],
+    ) -> Result<String, PickError> {
+        let mut candidates = rotation_candidates(choices, excluded);
+        // Shuffling before the stable sort breaks ties between equal shares at random.
+        candidates.shuffle(&mut self.rng);
+        candidates.sort_by(|a, b| choices[*a].cmp_share(&choices[*b]));
+
+        for (position, index) in candidates.iter().enumerate() {
+            let choice = choices[*index].name.clone();
+            if self.should_display_table() {
+                let remaining = candidates[position..]
+                    .iter()
+                    .map(|i| &choices[*i])
+                    .collect::<Vec<_>>();
+                self.display_rotation_table(&[0], &remaining, &self.details, &self.unavailable);
+            }
+
+            if self.get_consent(&choice)? {
+                choices[*index].assignments += 1;
+                return Ok(choice);
+            }
+        }
+        Err(self.all_rejected())
     }
 
-    /// Run the weighted model for the given choices.
-    fn pick_weighted(&mut self, choices: &[config::WeightedChoice]) -> String {
+    /// Run the weighted model for the given choices. Each choice's weight is scaled by the
+    /// preferences of the participants who are present, combined as `combine` says.
+    fn pick_weighted(
+        &mut self,
+        choices: &[config::WeightedChoice],
+        combine: group::Combine,
+        excluded: &[String],
+    ) -> Result<String, PickError> {
+        let weights = choices
+            .iter()
+            .map(|c| preferred_weight(c.weight, &c.preferences, &self.participants, combine))
+            .collect::<Vec<_>>();
         let initialize_candidates = || {
             choices
                 .iter()
                 .enumerate()
-                .map(|x| ((x.0, &x.1.name), x.1.weight))
+                .filter(|x| weights[x.0] > 0 && !excluded.contains(&x.1.name))
+                .map(|x| ((x.0, &x.1.name), weights[x.0]))
                 .collect::<Vec<_>>()
         };
 
-        let index = self.pick_weighted_common(&initialize_candidates);
+        let index = self.pick_weighted_common(&initialize_candidates)?;
 
-        choices[index].name.clone()
+        Ok(choices[index].name.clone())
     }
 
     /// A common weighted choice algorithm used as the core of many models.
@@ -230,29 +824,243 @@ where
     /// readable name of the choice. The second element of the outer 2-tuple should express the
     /// weight of that choice. For example, if the first choice is "ice cream" and has a weight of
     /// 5, the data structure would look like this: ((0, "ice cream"), 5)
+    ///
+    /// Every candidate must have a weight greater than 0, and there must be at least one of them.
+    /// [`check_candidates`] makes sure of this before a pick begins.
     fn pick_weighted_common(
         &mut self,
         initialize_candidates: &dyn Fn() -> Vec<((usize, &'a String), u64)>,
-    ) -> usize {
+    ) -> Result<usize, PickError> {
         let mut candidates = initialize_candidates();
 
         loop {
             let (index, choice) = candidates
                 .choose_weighted(&mut self.rng, |item| item.1)
-                .unwrap()
+                .expect("There must be candidates with weights greater than 0.")
                 .0;
 
-            if self.ui.call_display_table() {
-                self.display_weighted_chance_table(index, &candidates);
+            if self.should_display_table() {
+                self.display_weighted_chance_table(
+                    &[index],
+                    &candidates,
+                    &self.details,
+                    self.preferences.as_ref(),
+                    &self.unavailable,
+                );
             }
 
-            if self.get_consent(&choice[..]) {
-                break index;
+            if self.get_consent(&choice[..])? {
+                break Ok(index);
             } else if candidates.len() > 1 {
                 candidates.remove(candidates.iter().position(|x| (x.0).1 == choice).unwrap());
             } else {
-                self.express_disapproval();
-                candidates = initialize_candidates();
+                break Err(self.all_rejected());
+            }
+        }
+    }
+
+    /// Display the given chance table, with a row at the bottom for each of the unavailable choices
+    /// and a column saying why they can't be picked. The column is left out if every choice is
+    /// available.
+    ///
+    /// # Arguments
+    ///
+    /// `table` - The chance table for the choices that can be picked.
+    /// `unavailable` - Why each of the choices that are unavailable can't be picked, keyed by name.
+    fn display_table<'t>(
+        &self,
+        mut table: ui::Table<'t>,
+        unavailable: &'t BTreeMap<String, String>,
+    ) {
+        if !unavailable.is_empty() {
+            let width = table.header.len();
+            table.header.push("Unavailable".into());
+            if !table.footer.is_empty() {
+                table.footer.push("".into());
+            }
+            for row in table.rows.iter_mut() {
+                row.cells.push("".into());
+            }
+            for (name, reason) in unavailable {
+                let mut cells: Vec<ui::Cell> = vec![name.as_str().into()];
+                cells.extend((1..width).map(|_| ui::Cell::from("")));
+                cells.push(reason.as_str().into());
+                table.rows.push(ui::Row {
+                    cells,
+                    chosen: false,
+                });
+            }
+        }
+        self.ui.display_table(&table);
+    }
+
+    /// Display the chance table for the given category as a whole, with the rows for the named
+    /// choices marked as chosen.
+    ///
+    /// # Arguments
+    ///
+    /// `category` - The category to build the table for.
+    /// `chosen` - The names of the choices that should be marked as chosen.
+    /// `excluded` - The names of choices that could not be picked, which are left out of the
+    ///     table.
+    /// `unavailable` - Why each of the choices that are unavailable can't be picked, keyed by name.
+    ///     These are listed at the bottom of the table.
+    fn display_category_table(
+        &self,
+        category: &config::ConfigCategory,
+        chosen: &[String],
+        excluded: &[String],
+        unavailable: &BTreeMap<String, String>,
+    ) {
+        let details = choice_details(category);
+        let preferences = choice_preferences(category);
+        let combine = combine(category);
+        let positions = |names: &mut dyn Iterator<Item = &String>| {
+            names
+                .enumerate()
+                .filter(|(_, name)| chosen.contains(name))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+        };
+
+        match category {
+            config::ConfigCategory::Even { choices, .. } => {
+                let candidates = choices
+                    .iter()
+                    .enumerate()
+                    .filter(|x| !excluded.contains(&x.1.name))
+                    .map(|x| ((x.0, &x.1.name), 1))
+                    .collect::<Vec<_>>();
+                self.display_weighted_chance_table(
+                    &positions(&mut choices.iter().map(|c| &c.name)),
+                    &candidates,
+                    &details,
+                    preferences.as_ref(),
+                    unavailable,
+                );
+            }
+            config::ConfigCategory::Gaussian {
+                choices,
+                stddev_scaling_factor,
+                ..
+            } => {
+                let names = choices
+                    .iter()
+                    .map(|c| c.name.clone())
+                    .filter(|name| !excluded.contains(name))
+                    .collect::<Vec<_>>();
+                let stddev = (names.len() as f64) / stddev_scaling_factor;
+                self.display_gaussian_chance_table(
+                    &positions(&mut names.iter()),
+                    &names,
+                    stddev,
+                    &details,
+                    unavailable,
+                );
+            }
+            config::ConfigCategory::Inventory { choices, .. } => {
+                let candidates = choices
+                    .iter()
+                    .enumerate()
+                    .filter(|x| x.1.tickets > 0 && !excluded.contains(&x.1.name))
+                    .map(|x| ((x.0, &x.1.name), x.1.tickets))
+                    .collect::<Vec<_>>();
+                self.display_weighted_chance_table(
+                    &positions(&mut choices.iter().map(|c| &c.name)),
+                    &candidates,
+                    &details,
+                    preferences.as_ref(),
+                    unavailable,
+                );
+            }
+            config::ConfigCategory::Lottery { choices, .. } => {
+                let candidates = choices
+                    .iter()
+                    .enumerate()
+                    .map(|x| {
+                        let tickets = preferred_weight(
+                            x.1.tickets,
+                            &x.1.preferences,
+                            &self.participants,
+                            combine,
+                        );
+                        ((x.0, &x.1.name), tickets)
+                    })
+                    .filter(|x| x.1 > 0 && !excluded.contains((x.0).1))
+                    .collect::<Vec<_>>();
+                self.display_weighted_chance_table(
+                    &positions(&mut choices.iter().map(|c| &c.name)),
+                    &candidates,
+                    &details,
+                    preferences.as_ref(),
+                    unavailable,
+                );
+            }
+            config::ConfigCategory::Lru { choices, .. } => {
+                let names = choices
+                    .iter()
+                    .map(|c| c.name.clone())
+                    .filter(|name| !excluded.contains(name))
+                    .collect::<Vec<_>>();
+                self.display_lru_table(
+                    &positions(&mut names.iter()),
+                    &names,
+                    &details,
+                    unavailable,
+                );
+            }
+            config::ConfigCategory::Recency { choices, .. } => {
+                let weights = recency_weights(choices, self.now());
+                let candidates = choices
+                    .iter()
+                    .enumerate()
+                    .filter(|x| weights[x.0] > 0 && !excluded.contains(&x.1.name))
+                    .map(|x| ((x.0, &x.1.name), weights[x.0]))
+                    .collect::<Vec<_>>();
+                self.display_weighted_chance_table(
+                    &positions(&mut choices.iter().map(|c| &c.name)),
+                    &candidates,
+                    &details,
+                    preferences.as_ref(),
+                    unavailable,
+                );
+            }
+            config::ConfigCategory::Rotation { choices, .. } => {
+                let mut candidates = rotation_candidates(choices, excluded);
+                candidates.sort_by(|a, b| choices[*a].cmp_share(&choices[*b]));
+                let candidates = candidates
+                    .into_iter()
+                    .map(|i| &choices[i])
+                    .collect::<Vec<_>>();
+                self.display_rotation_table(
+                    &positions(&mut candidates.iter().map(|c| &c.name)),
+                    &candidates,
+                    &details,
+                    unavailable,
+                );
+            }
+            config::ConfigCategory::Weighted { choices, .. } => {
+                let candidates = choices
+                    .iter()
+                    .enumerate()
+                    .filter(|x| !excluded.contains(&x.1.name))
+                    .map(|x| {
+                        let weight = preferred_weight(
+                            x.1.weight,
+                            &x.1.preferences,
+                            &self.participants,
+                            combine,
+                        );
+                        ((x.0, &x.1.name), weight)
+                    })
+                    .collect::<Vec<_>>();
+                self.display_weighted_chance_table(
+                    &positions(&mut choices.iter().map(|c| &c.name)),
+                    &candidates,
+                    &details,
+                    preferences.as_ref(),
+                    unavailable,
+                );
             }
         }
     }
@@ -261,86 +1069,164 @@ where
     ///
     /// # Arguments
     ///
-    /// `index` - The index of the candidate that was chosen. This is used to turn the chosen
-    ///     candidate yellow in the table.
+    /// `chosen` - The indices of the candidates that were chosen. This is used to turn the chosen
+    ///     candidates yellow in the table.
     /// `candidates` - A list of the candidates.
-    fn display_gaussian_chance_table(&mut self, index: usize, candidates: &[String], stddev: f64) {
+    /// `stddev` - The standard deviation of the distribution the candidates are picked with.
+    /// `details` - The details of the candidates, which are shown in extra columns.
+    /// `unavailable` - Why each of the choices that are unavailable can't be picked, keyed by name.
+    fn display_gaussian_chance_table(
+        &self,
+        chosen: &[usize],
+        candidates: &[String],
+        stddev: f64,
+        details: &BTreeMap<String, config::ChoiceDetails>,
+        unavailable: &BTreeMap<String, String>,
+    ) {
         // Let's make a copy of the candidate list so that we can sort it for the table
         // without sorting the real candidate list.
         let candidates = candidates.to_owned();
 
-        let header: Vec<ui::Cell> = vec!["Name".into(), "Chance".into()];
+        let columns = DetailColumns::new(details);
+        let mut header: Vec<ui::Cell> = vec!["Name".into(), "Chance".into()];
+        header.extend(columns.header());
         let mut rows = vec![];
-        let distribution = statrs::distribution::Normal::new(0.0, stddev).unwrap();
         let mut total_chance = 0.0;
-        for (i, candidate) in candidates.iter().enumerate() {
-            // We multiply by 200 here: 100 is for expressing percents to humans, and the factor
-            // of 2 is to account for the abs() we use in pick_gaussian(), which causes us to
-            // reflect the distribution around the x-axis (thus the chance is this slice of the CDF
-            // on both sides of the x-axis, which is the same chance as twice this singular slice).
-            let chance: f64 =
-                (distribution.cdf((i as f64) + 1.0) - distribution.cdf(i as f64)) * 200.;
-            total_chance += chance;
-            let mut cells: Vec<ui::Cell> = vec![];
-            let chosen = i == index;
-            cells.push(ui::Cell::from(candidate.as_ref()));
-            cells.push(chance.into());
-            let row = ui::Row { cells, chosen };
-            rows.push(row);
+        // The distribution is undefined when there are no candidates, but then there are no rows to
+        // build either.
+        if let Ok(distribution) = statrs::distribution::Normal::new(0.0, stddev) {
+            for (i, candidate) in candidates.iter().enumerate() {
+                // We multiply by 200 here: 100 is for expressing percents to humans, and the factor
+                // of 2 is to account for the abs() we use in pick_gaussian(), which causes us to
+                // reflect the distribution around the x-axis (thus the chance is this slice of the
+                // CDF on both sides of the x-axis, which is the same chance as twice this singular
+                // slice).
+                let chance: f64 =
+                    (distribution.cdf((i as f64) + 1.0) - distribution.cdf(i as f64)) * 200.;
+                total_chance += chance;
+                let mut cells: Vec<ui::Cell> = vec![];
+                let chosen = chosen.contains(&i);
+                cells.push(ui::Cell::from(candidate.as_ref()));
+                cells.push(chance.into());
+                cells.extend(columns.cells(candidate));
+                let row = ui::Row { cells, chosen };
+                rows.push(row);
+            }
         }
         let footer: Vec<ui::Cell> = vec!["Total".into(), total_chance.into()];
 
-        self.ui.display_table(&ui::Table {
-            footer,
-            header,
-            rows,
-        });
+        self.display_table(
+            ui::Table {
+                footer,
+                header,
+                rows,
+            },
+            unavailable,
+        );
     }
 
-    /// Print a table to self.output showing the candidates, sorted by chance of being chosen.
+    /// Print a table to self.output showing the candidates in the order they will be offered,
+    /// with the next candidate at the bottom.
     ///
     /// # Arguments
     ///
-    /// `index` - The index of the candidate that was chosen. This is used to turn the chosen
-    ///     candidate yellow in the table.
-    /// `candidates` - A list of the candidates.
-    fn display_lru_table(&mut self, index: usize, candidates: &[String]) {
-        // Filter out candidates that have already been rejected by the user.
-        let candidates = candidates
-            .iter()
-            .enumerate()
-            .filter(|(i, _)| i >= &index)
-            .map(|x| x.1)
-            .collect::<Vec<_>>();
-
-        let header: Vec<ui::Cell> = vec!["Name".into()];
+    /// `chosen` - The indices of the candidates that were chosen. This is used to turn the chosen
+    ///     candidates yellow in the table.
+    /// `candidates` - A list of the candidates that have not been rejected by the user.
+    /// `details` - The details of the candidates, which are shown in extra columns.
+    /// `unavailable` - Why each of the choices that are unavailable can't be picked, keyed by name.
+    fn display_lru_table(
+        &self,
+        chosen: &[usize],
+        candidates: &[String],
+        details: &BTreeMap<String, config::ChoiceDetails>,
+        unavailable: &BTreeMap<String, String>,
+    ) {
+        let columns = DetailColumns::new(details);
+        let mut header: Vec<ui::Cell> = vec!["Name".into()];
+        header.extend(columns.header());
         let mut rows = vec![];
-        for (i, candidate) in candidates.iter().rev().enumerate() {
+        for (i, candidate) in candidates.iter().enumerate().rev() {
             let mut cells: Vec<ui::Cell> = vec![];
-            let chosen = i == candidates.len() - 1;
+            let chosen = chosen.contains(&i);
             cells.push(ui::Cell::from(candidate.as_ref()));
+            cells.extend(columns.cells(candidate));
             rows.push(ui::Row { cells, chosen });
         }
         let footer = vec![];
 
-        self.ui.display_table(&ui::Table {
-            footer,
-            header,
-            rows,
-        });
+        self.display_table(
+            ui::Table {
+                footer,
+                header,
+                rows,
+            },
+            unavailable,
+        );
+    }
+
+    /// Print a table to self.output showing the rotation candidates in the order they will be
+    /// offered, with the next candidate at the bottom.
+    ///
+    /// # Arguments
+    ///
+    /// `chosen` - The indices of the candidates that were chosen. This is used to turn the chosen
+    ///     candidates yellow in the table.
+    /// `candidates` - The candidates that have not been rejected by the user.
+    /// `details` - The details of the candidates, which are shown in extra columns.
+    /// `unavailable` - Why each of the choices that are unavailable can't be picked, keyed by name.
+    fn display_rotation_table(
+        &self,
+        chosen: &[usize],
+        candidates: &[&config::RotationChoice],
+        details: &BTreeMap<String, config::ChoiceDetails>,
+        unavailable: &BTreeMap<String, String>,
+    ) {
+        let columns = DetailColumns::new(details);
+        let mut header: Vec<ui::Cell> =
+            vec!["Name".into(), "Assignments".into(), "Capacity".into()];
+        header.extend(columns.header());
+        let mut rows = vec![];
+        for (i, candidate) in candidates.iter().enumerate().rev() {
+            let mut cells: Vec<ui::Cell> = vec![];
+            let chosen = chosen.contains(&i);
+            cells.push(ui::Cell::from(candidate.name.as_str()));
+            cells.push(candidate.assignments.into());
+            cells.push(candidate.capacity.into());
+            cells.extend(columns.cells(&candidate.name));
+            rows.push(ui::Row { cells, chosen });
+        }
+        let footer = vec![];
+
+        self.display_table(
+            ui::Table {
+                footer,
+                header,
+                rows,
+            },
+            unavailable,
+        );
     }
 
     /// Print a table to self.output showing the candidates, sorted by chance of being chosen.
     ///
     /// # Arguments
     ///
-    /// `index` - The index of the candidate that was chosen. This is used to turn the chosen
-    ///     candidate yellow in the table.
+    /// `chosen` - The original indices of the candidates that were chosen. This is used to turn
+    ///     the chosen candidates yellow in the table.
     /// `candidates` - A list of the candidates.
+    /// `details` - The details of the candidates, which are shown in extra columns.
+    /// `preferences` - The preferences for the candidates, keyed by candidate and then by
+    ///     participant, or `None` if the model doesn't take preferences. The preferences of the
+    ///     participants who are present are shown in a column for each of them.
+    /// `unavailable` - Why each of the choices that are unavailable can't be picked, keyed by name.
     fn display_weighted_chance_table(
-        &mut self,
-        index: usize,
-        candidates: &[((usize, &'a String), u64)],
+        &self,
+        chosen: &[usize],
+        candidates: &[((usize, &String), u64)],
+        details: &BTreeMap<String, config::ChoiceDetails>,
+        preferences: Option<&BTreeMap<String, BTreeMap<String, u64>>>,
+        unavailable: &BTreeMap<String, String>,
     ) {
         // Let's make a copy of the candidate list so that we can sort it for the table
         // without sorting the real candidate list.
@@ -350,40 +1236,526 @@ where
         let total: u64 = candidates.iter().map(|x| x.1).sum();
 
         let mut rows = vec![];
-        let header: Vec<ui::Cell> = vec!["Name".into(), "Weight".into(), "Chance".into()];
+        let columns = DetailColumns::new(details);
+        let participants = match preferences {
+            Some(_) => &self.participants[..],
+            None => &[],
+        };
+        let mut header: Vec<ui::Cell> = vec!["Name".into()];
+        header.extend(participants.iter().map(|p| ui::Cell::from(p.as_str())));
+        header.extend(["Weight".into(), "Chance".into()]);
+        header.extend(columns.header());
         for candidate in candidates.iter() {
             let chance: f64 = (candidate.1 as f64) / (total as f64) * 100.;
             let mut cells: Vec<ui::Cell> = vec![];
-            let chosen = (candidate.0).0 == index;
+            let chosen = chosen.contains(&(candidate.0).0);
             cells.push(ui::Cell::from((candidate.0).1.as_ref()));
+            let choice_preferences = preferences.and_then(|p| p.get((candidate.0).1));
+            cells.extend(participants.iter().map(|participant| {
+                ui::Cell::from(
+                    choice_preferences
+                        .and_then(|p| p.get(participant))
+                        .copied()
+                        .unwrap_or(1),
+                )
+            }));
             cells.push(candidate.1.into());
             cells.push(chance.into());
+            cells.extend(columns.cells((candidate.0).1));
             rows.push(ui::Row { cells, chosen });
         }
-        let footer: Vec<ui::Cell> = vec!["Total".into(), total.into(), 100.00.into()];
+        let mut footer: Vec<ui::Cell> = vec!["Total".into()];
+        footer.extend(participants.iter().map(|_| ui::Cell::from("")));
+        footer.extend([total.into(), 100.00.into()]);
 
-        self.ui.display_table(&ui::Table {
-            footer,
-            header,
-            rows,
-        });
+        self.display_table(
+            ui::Table {
+                footer,
+                header,
+                rows,
+            },
+            unavailable,
+        );
     }
 }
 
-/// Define the errors that can be returned from [`Engine::pick`].
-#[derive(Debug, Error)]
-pub enum PickError {
-    #[error("The category `{0}` was not found in the given config.")]
-    CategoryNotFound(String),
-}
+/// Return the details of the given category's choices, keyed by name, for the choices that have
+/// any. If several choices share a name, the first one's details are used.
+fn choice_details(category: &config::ConfigCategory) -> BTreeMap<String, config::ChoiceDetails> {
+    let mut details = BTreeMap::new();
+    for (name, choice_details) in category.choice_details() {
+        if !choice_details.is_empty() && !details.contains_key(name) {
+            details.insert(name.to_string(), choice_details.clone());
+        }
+    }
+    details
+}
+
+/// Return the preferences for the given category's choices, keyed by choice and then by
+/// participant, or `None` if its model doesn't take preferences. If several choices share a name,
+/// the first one's preferences are used.
+fn choice_preferences(
+    category: &config::ConfigCategory,
+) -> Option<BTreeMap<String, BTreeMap<String, u64>>> {
+    let mut preferences = BTreeMap::new();
+    for (name, choice_preferences) in category.choice_preferences()? {
+        preferences
+            .entry(name.to_string())
+            .or_insert_with(|| choice_preferences.clone());
+    }
+    Some(preferences)
+}
+
+/// Return why each of the given category's choices can't be picked at the given time, keyed by
+/// name, for the choices that can't. A name is only unavailable if every choice with that name is,
+/// and then the first one's reason is given.
+fn unavailable_choices(
+    category: &config::ConfigCategory,
+    now: DateTime<Local>,
+) -> BTreeMap<String, String> {
+    let mut reasons = BTreeMap::new();
+    let mut available = BTreeSet::new();
+    for (name, availability) in category.choice_availability() {
+        match availability.unavailable_reason(now) {
+            Some(reason) => {
+                reasons.entry(name.to_string()).or_insert(reason);
+            }
+            None => {
+                available.insert(name);
+            }
+        }
+    }
+    reasons.retain(|name, _| !available.contains(name.as_str()));
+    reasons
+}
+
+/// Return how the given category combines the preferences of the participants who are present.
+fn combine(category: &config::ConfigCategory) -> group::Combine {
+    category
+        .group()
+        .map(|group| group.combine)
+        .unwrap_or_default()
+}
+
+/// Return the given weight scaled by the combined preferences of the given participants for a
+/// choice. Participants who haven't given the choice a preference count as giving it 1. With no
+/// participants, the weight is returned as it is.
+///
+/// # Arguments
+///
+/// * `weight` - The weight, or number of tickets, the choice has on its own.
+/// * `preferences` - The choice's preferences, keyed by participant.
+/// * `participants` - The names of the participants who are present.
+/// * `combine` - How the participants' preferences are combined.
+fn preferred_weight(
+    weight: u64,
+    preferences: &BTreeMap<String, u64>,
+    participants: &[String],
+    combine: group::Combine,
+) -> u64 {
+    if participants.is_empty() {
+        return weight;
+    }
+    let preferences = participants
+        .iter()
+        .map(|participant| preferences.get(participant).copied().unwrap_or(1))
+        .collect::<Vec<_>>();
+    weight.saturating_mul(combine.apply(&preferences))
+}
+
+/// The columns that chance tables use to show the details of their choices: one for descriptions,
+/// one for URLs, and one for each attribute. Columns that no choice has a value for are left out.
+struct DetailColumns<'a> {
+    details: &'a BTreeMap<String, config::ChoiceDetails>,
+    description: bool,
+    url: bool,
+    attributes: BTreeSet<&'a str>,
+}
+
+impl<'a> DetailColumns<'a> {
+    /// Decide which columns are needed to show the given details.
+    ///
+    /// # Arguments
+    ///
+    /// * `details` - The details of the choices in the table, keyed by name.
+    fn new(details: &'a BTreeMap<String, config::ChoiceDetails>) -> DetailColumns<'a> {
+        DetailColumns {
+            details,
+            description: details.values().any(|d| d.description.is_some()),
+            url: details.values().any(|d| d.url.is_some()),
+            attributes: details
+                .values()
+                .flat_map(|d| d.attributes.keys().map(String::as_str))
+                .collect(),
+        }
+    }
+
+    /// Return the header cells for the columns.
+    fn header(&self) -> Vec<ui::Cell<'a>> {
+        let mut header = vec![];
+        if self.description {
+            header.push("Description".into());
+        }
+        if self.url {
+            header.push("URL".into());
+        }
+        header.extend(
+            self.attributes
+                .iter()
+                .map(|attribute| ui::Cell::from(*attribute)),
+        );
+        header
+    }
+
+    /// Return the cells for the columns in the given choice's row. Values the choice doesn't have
+    /// are left blank.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the choice.
+    fn cells(&self, name: &str) -> Vec<ui::Cell<'a>> {
+        let details = self.details.get(name);
+        let mut cells = vec![];
+        if self.description {
+            cells.push(ui::Cell::from(
+                details.and_then(|d| d.description.as_deref()).unwrap_or(""),
+            ));
+        }
+        if self.url {
+            cells.push(ui::Cell::from(
+                details.and_then(|d| d.url.as_deref()).unwrap_or(""),
+            ));
+        }
+        for attribute in &self.attributes {
+            cells.push(ui::Cell::from(
+                details
+                    .and_then(|d| d.attributes.get(*attribute))
+                    .map_or("", String::as_str),
+            ));
+        }
+        cells
+    }
+}
+
+/// Return an error if the given category's parameters are invalid.
+///
+/// # Arguments
+///
+/// * `name` - The name of the category, which is used in errors.
+/// * `category` - The category to check.
+fn check_parameters(name: &str, category: &config::ConfigCategory) -> Result<(), PickError> {
+    if category
+        .group()
+        .is_some_and(|group| group.participants.is_empty())
+    {
+        return Err(PickError::InvalidParameter {
+            category: name.to_string(),
+            parameter: "group",
+            reason: "it must have at least one participant".to_string(),
+        });
+    }
+    if let config::ConfigCategory::Gaussian {
+        stddev_scaling_factor,
+        ..
+    } = category
+    {
+        if !(stddev_scaling_factor.is_finite() && *stddev_scaling_factor > 0.0) {
+            return Err(PickError::InvalidParameter {
+                category: name.to_string(),
+                parameter: "stddev_scaling_factor",
+                reason: "it must be a number greater than 0".to_string(),
+            });
+        }
+    }
+    if let config::ConfigCategory::Inventory {
+        choices, restock, ..
+    } = category
+    {
+        if restock
+            .iter()
+            .chain(choices.iter().filter_map(|c| c.restock.as_ref()))
+            .any(config::Restock::has_zero_period)
+        {
+            return Err(PickError::InvalidParameter {
+                category: name.to_string(),
+                parameter: "restock",
+                reason: "its period must be longer than 0".to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Return an error if nothing can be picked from the given category, either because its parameters
+/// are invalid or because none of its choices are eligible.
+///
+/// # Arguments
+///
+/// * `name` - The name of the category, which is used in errors.
+/// * `category` - The category to check.
+/// * `excluded` - The names of choices that must not be picked.
+/// * `participants` - The names of the participants who are present.
+/// * `now` - The time of the pick.
+fn check_candidates(
+    name: &str,
+    category: &config::ConfigCategory,
+    excluded: &[String],
+    participants: &[String],
+    now: DateTime<Local>,
+) -> Result<(), PickError> {
+    check_parameters(name, category)?;
+    let weights = candidate_weights(category, excluded, participants, now)
+        .into_iter()
+        .map(|(_, weight)| weight)
+        .collect::<Vec<_>>();
+
+    if weights.is_empty() {
+        return Err(PickError::NoChoices(name.to_string()));
+    }
+    if weights.iter().all(|weight| *weight == 0) {
+        return Err(match category {
+            config::ConfigCategory::Inventory { .. } | config::ConfigCategory::Lottery { .. } => {
+                PickError::AllTicketsExhausted(name.to_string())
+            }
+            config::ConfigCategory::Recency { .. } => PickError::AllCoolingDown(name.to_string()),
+            config::ConfigCategory::Rotation { .. } => PickError::InvalidParameter {
+                category: name.to_string(),
+                parameter: "capacity",
+                reason: "at least one choice must have a capacity greater than 0".to_string(),
+            },
+            _ => PickError::InvalidParameter {
+                category: name.to_string(),
+                parameter: "weight",
+                reason: "at least one choice must have a weight greater than 0".to_string(),
+            },
+        });
+    }
+    Ok(())
+}
+
+/// Return the name and weight of each of the given category's choices that are not excluded. A
+/// choice with a weight of 0 can't be picked, such as one that is out of tickets or cooling down.
+///
+/// # Arguments
+///
+/// * `category` - The category to weigh the choices of.
+/// * `excluded` - The names of choices that must not be picked.
+/// * `participants` - The names of the participants who are present.
+/// * `now` - The time of the pick.
+fn candidate_weights<'c>(
+    category: &'c config::ConfigCategory,
+    excluded: &[String],
+    participants: &[String],
+    now: DateTime<Local>,
+) -> Vec<(&'c String, u64)> {
+    let combine = combine(category);
+
+    let weights = match category {
+        config::ConfigCategory::Even { choices, .. }
+        | config::ConfigCategory::Gaussian { choices, .. }
+        | config::ConfigCategory::Lru { choices, .. } => {
+            choices.iter().map(|c| (&c.name, 1)).collect::<Vec<_>>()
+        }
+        config::ConfigCategory::Inventory { choices, .. } => {
+            choices.iter().map(|c| (&c.name, c.tickets)).collect()
+        }
+        config::ConfigCategory::Lottery { choices, .. } => choices
+            .iter()
+            .map(|c| {
+                let tickets = preferred_weight(c.tickets, &c.preferences, participants, combine);
+                (&c.name, tickets)
+            })
+            .collect(),
+        config::ConfigCategory::Recency { choices, .. } => choices
+            .iter()
+            .map(|c| &c.name)
+            .zip(recency_weights(choices, now))
+            .collect(),
+        config::ConfigCategory::Rotation { choices, .. } => {
+            choices.iter().map(|c| (&c.name, c.capacity)).collect()
+        }
+        config::ConfigCategory::Weighted { choices, .. } => choices
+            .iter()
+            .map(|c| {
+                let weight = preferred_weight(c.weight, &c.preferences, participants, combine);
+                (&c.name, weight)
+            })
+            .collect(),
+    };
+    weights
+        .into_iter()
+        .filter(|(c, _)| !excluded.contains(c))
+        .collect()
+}
+
+/// Return the weight of each of the given recency choices at the given time.
+///
+/// A choice weighs the number of whole hours since it was last picked, plus 1, so that a choice
+/// picked moments ago can still be picked. Choices that have never been picked weigh as much as
+/// the one that has waited the longest, and choices that are cooling down weigh 0.
+fn recency_weights(choices: &[config::RecencyChoice], now: DateTime<Local>) -> Vec<u64> {
+    let waits = choices
+        .iter()
+        .map(|c| {
+            c.hours_since_picked(now)
+                .map(|hours| hours.saturating_add(1))
+        })
+        .collect::<Vec<_>>();
+    let longest = waits.iter().flatten().max().copied().unwrap_or(1);
+    choices
+        .iter()
+        .zip(waits)
+        .map(|(choice, wait)| {
+            if choice.is_cooling_down(now) {
+                0
+            } else {
+                wait.unwrap_or(longest)
+            }
+        })
+        .collect()
+}
+
+/// Return the indices of the given rotation choices that can be picked, in the order they appear
+/// in the config.
+///
+/// # Arguments
+///
+/// * `choices` - The rotation choices.
+/// * `excluded` - The names of choices that must not be picked.
+fn rotation_candidates(choices: &[config::RotationChoice], excluded: &[String]) -> Vec<usize> {
+    choices
+        .iter()
+        .enumerate()
+        .filter(|x| x.1.capacity > 0 && !excluded.contains(&x.1.name))
+        .map(|x| x.0)
+        .collect()
+}
+
+/// The results of [`Engine::simulate`].
+///
+/// # Attributes
+///
+/// * `rounds` - How many picks were simulated.
+/// * `choices` - The results for each choice, in the order they appeared in the category.
+#[derive(Debug, PartialEq)]
+pub struct Simulation {
+    pub rounds: usize,
+    pub choices: Vec<SimulatedChoice>,
+}
+
+/// How a single choice fared during a [`Simulation`].
+///
+/// # Attributes
+///
+/// * `name` - The name of the choice.
+/// * `picks` - How many rounds the choice was picked in.
+/// * `frequency` - The fraction of rounds the choice was picked in, from 0 to 1.
+/// * `mean_gap` - The average number of rounds from one pick of this choice to the next, or `None`
+///   if it was picked fewer than two times.
+/// * `longest_drought` - The longest run of consecutive rounds in which this choice was not
+///   picked, including the rounds before its first pick and after its last.
+#[derive(Debug, PartialEq)]
+pub struct SimulatedChoice {
+    pub name: String,
+    pub picks: usize,
+    pub frequency: f64,
+    pub mean_gap: Option<f64>,
+    pub longest_drought: usize,
+}
+
+impl SimulatedChoice {
+    /// Summarize a choice's results from the rounds it was picked in.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the choice.
+    /// * `picked` - The rounds the choice was picked in, in ascending order.
+    /// * `rounds` - How many rounds were simulated in total.
+    fn new(name: String, picked: &[usize], rounds: usize) -> SimulatedChoice {
+        let picks = picked.len();
+        let frequency = if rounds > 0 {
+            picks as f64 / rounds as f64
+        } else {
+            0.0
+        };
+        let mean_gap = if picks > 1 {
+            Some((picked[picks - 1] - picked[0]) as f64 / (picks - 1) as f64)
+        } else {
+            None
+        };
+
+        // Each drought ends at a pick (or at the end of the simulation) and began right after the
+        // previous pick (or at the start of the simulation).
+        let mut longest_drought = 0;
+        let mut drought_start = 0;
+        for round in picked.iter().copied().chain(std::iter::once(rounds)) {
+            longest_drought = longest_drought.max(round - drought_start);
+            drought_start = round + 1;
+        }
+
+        SimulatedChoice {
+            name,
+            picks,
+            frequency,
+            mean_gap,
+            longest_drought,
+        }
+    }
+}
+
+/// Define the errors that can be returned from the [`Engine`]'s methods.
+#[derive(Debug, Error)]
+pub enum PickError {
+    #[error("The category `{0}` was not found in the given config.")]
+    CategoryNotFound(String),
+    #[error("Cannot pick {requested} items from the category `{category}`, which only has {available} distinct choices.")]
+    TooFewChoices {
+        category: String,
+        available: usize,
+        requested: usize,
+    },
+    #[error("Cannot pick 0 items from the category `{0}`. Ask for at least 1.")]
+    NothingRequested(String),
+    #[error("The category `{0}` has no choices to pick from.")]
+    NoChoices(String),
+    #[error("The category `{category}` has no choice named `{choice}`.")]
+    ChoiceNotFound { category: String, choice: String },
+    #[error("No choices in the category `{0}` match the tag filter.")]
+    NoMatchingChoices(String),
+    #[error("Every choice in the category `{0}` was excluded.")]
+    AllExcluded(String),
+    #[error("Every choice in the category `{0}` is out of tickets.")]
+    AllTicketsExhausted(String),
+    #[error("Every choice in the category `{0}` is cooling down.")]
+    AllCoolingDown(String),
+    #[error("Every choice in the category `{0}` is unavailable.")]
+    AllUnavailable(String),
+    #[error("Every choice in the category `{0}` was rejected.")]
+    AllRejected(String),
+    #[error("The pick was abandoned, since the Ui can no longer answer prompts.")]
+    Aborted,
+    #[error("The category `{category}` doesn't know the participant `{participant}`.")]
+    UnknownParticipant {
+        category: String,
+        participant: String,
+    },
+    #[error("The `{parameter}` setting of the category `{category}` is invalid: {reason}.")]
+    InvalidParameter {
+        category: String,
+        parameter: &'static str,
+        reason: String,
+    },
+}
 
 #[cfg(test)]
 mod tests {
     use approx::abs_diff_eq;
+    use chrono::{Duration, TimeZone};
     use mockall::predicate;
     use rand::SeedableRng;
 
     use super::*;
+    use crate::availability;
 
     struct FakeRng(u32);
 
@@ -416,78 +1788,1280 @@ mod tests {
             }
         }
 
-        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
-            self.fill_bytes(dest);
-            Ok(())
-        }
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_get_consent() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_is_aborted().returning(|| false);
+        ui.expect_prompt_choice()
+            .with(predicate::in_iter(vec![
+                "you want this",
+                "you don't want this",
+            ]))
+            .times(2)
+            .returning(|x| !x.contains("don't"));
+        let mut engine = Engine::new(&ui);
+
+        assert!(engine.get_consent("you want this").unwrap());
+        assert!(!engine.get_consent("you don't want this").unwrap());
+    }
+
+    #[test]
+    fn test_pick() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_is_aborted().returning(|| false);
+        ui.expect_call_display_table().times(2).returning(|| false);
+        ui.expect_prompt_choice()
+            .with(predicate::in_iter(vec!["that", "this"]))
+            .times(2)
+            .returning(|c| c == "that");
+        let mut engine = Engine::new(&ui);
+        engine.set_rng(FakeRng(0));
+        let choices = vec![
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
+        ];
+        let category = config::ConfigCategory::Even {
+            choices,
+            group: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+
+        let choice = engine
+            .pick(&mut config, "things".to_string())
+            .expect("unexpected");
+
+        assert_eq!(choice, "that");
+    }
+
+    #[test]
+    fn test_pick_nonexistant_category() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        let choices = vec![
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
+        ];
+        let category = config::ConfigCategory::Even {
+            choices,
+            group: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+
+        match engine.pick(&mut config, "does not exist".to_string()) {
+            Ok(_) => {
+                panic!("The non-existant category should have returned an error.");
+            }
+            Err(error) => {
+                assert_eq!(
+                    format!("{}", error),
+                    "The category `does not exist` was not found in the given config."
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_pick_many() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_call_display_table().times(1).returning(|| false);
+        ui.expect_prompt_choice()
+            .with(predicate::in_iter(vec!["this", "that"]))
+            .times(2)
+            .returning(|_| true);
+        let mut engine = Engine::new(&ui);
+        let choices = vec![
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
+        ];
+        let category = config::ConfigCategory::Lru {
+            choices,
+            group: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+
+        let picks = engine
+            .pick_many(&mut config, "things".to_string(), 2, &[])
+            .expect("unexpected");
+
+        assert_eq!(picks, vec![String::from("this"), String::from("that")]);
+        // The Lru model should have moved each accepted choice to the end in acceptance order.
+        assert_eq!(
+            config["things"],
+            config::ConfigCategory::Lru {
+                choices: vec![
+                    config::Choice::from("the other"),
+                    config::Choice::from("this"),
+                    config::Choice::from("that")
+                ],
+                group: None,
+            }
+        );
+    }
+
+    /// The engine should record each accepted choice along with the choices rejected before it.
+    #[test]
+    fn test_pick_many_tag_filter() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_call_display_table().times(1).returning(|| false);
+        ui.expect_prompt_choice()
+            .with(predicate::in_iter(vec!["that", "the other"]))
+            .times(2)
+            .returning(|_| true);
+        let mut engine = Engine::new(&ui);
+        engine.set_tag_filter(tags::TagFilter {
+            include: vec!["b".parse().unwrap()],
+            exclude: vec![],
+        });
+        let tagged = |name: &str, tags: &[&str]| config::Choice {
+            name: name.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            details: Default::default(),
+            availability: Default::default(),
+        };
+        let choices = vec![
+            tagged("this", &["a"]),
+            tagged("that", &["a", "b"]),
+            tagged("the other", &["b"]),
+        ];
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Lru {
+                choices,
+                group: None,
+            },
+        );
+
+        assert!(matches!(
+            engine.pick_many(&mut config, "things".to_string(), 3, &[]),
+            Err(PickError::TooFewChoices { available: 2, .. })
+        ));
+        let picks = engine
+            .pick_many(&mut config, "things".to_string(), 2, &[])
+            .expect("unexpected");
+
+        assert_eq!(picks, vec![String::from("that"), String::from("the other")]);
+        // The choice that was filtered out keeps its place at the front of the Lru order.
+        assert_eq!(
+            config["things"],
+            config::ConfigCategory::Lru {
+                choices: vec![
+                    tagged("this", &["a"]),
+                    tagged("that", &["a", "b"]),
+                    tagged("the other", &["b"]),
+                ],
+                group: None,
+            }
+        );
+        engine.set_tag_filter(tags::TagFilter {
+            include: vec!["c".parse().unwrap()],
+            exclude: vec![],
+        });
+        match engine.pick(&mut config, "things".to_string()) {
+            Err(PickError::NoMatchingChoices(category)) => assert_eq!(category, "things"),
+            _ => panic!("The tag filter should not have matched any choices."),
+        }
+    }
+
+    #[test]
+    fn test_pick_many_exclude() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_call_display_table().returning(|| false);
+        ui.expect_prompt_choice()
+            .with(predicate::eq("that"))
+            .times(1)
+            .returning(|_| true);
+        let mut engine = Engine::new(&ui);
+        let choices = vec![
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
+        ];
+        let category = config::ConfigCategory::Lru {
+            choices,
+            group: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+        let exclude = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        let picks = engine
+            .pick_many(&mut config, "things".to_string(), 1, &exclude(&["this"]))
+            .expect("unexpected");
+
+        assert_eq!(picks, vec![String::from("that")]);
+        // The excluded choice keeps its place at the front of the Lru order.
+        assert_eq!(
+            config["things"],
+            config::ConfigCategory::Lru {
+                choices: vec![
+                    config::Choice::from("this"),
+                    config::Choice::from("the other"),
+                    config::Choice::from("that"),
+                ],
+                group: None,
+            }
+        );
+        assert_eq!(
+            engine
+                .pick_many(&mut config, "things".to_string(), 1, &exclude(&["nope"]))
+                .unwrap_err()
+                .to_string(),
+            "The category `things` has no choice named `nope`."
+        );
+        assert_eq!(
+            engine
+                .pick_many(
+                    &mut config,
+                    "things".to_string(),
+                    1,
+                    &exclude(&["this", "that", "the other"])
+                )
+                .unwrap_err()
+                .to_string(),
+            "Every choice in the category `things` was excluded."
+        );
+        assert!(matches!(
+            engine.pick_many(
+                &mut config,
+                "things".to_string(),
+                2,
+                &exclude(&["this", "that"])
+            ),
+            Err(PickError::TooFewChoices { available: 1, .. })
+        ));
+    }
+
+    #[test]
+    /// Only the choices that can be picked should count towards the number of items requested.
+    fn test_pick_many_eligible() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_call_display_table().returning(|| false);
+        ui.expect_prompt_choice().times(2).returning(|_| true);
+        let mut engine = Engine::new(&ui);
+        let choice = |name: &str, tickets| config::InventoryChoice {
+            name: name.to_string(),
+            tickets,
+            restocked: None,
+            tags: vec![],
+            details: Default::default(),
+            restock: None,
+            availability: Default::default(),
+        };
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Inventory {
+                choices: vec![choice("this", 1), choice("that", 0)],
+                group: None,
+                restock: None,
+            },
+        );
+
+        assert!(matches!(
+            engine.pick_many(&mut config, "things".to_string(), 2, &[]),
+            Err(PickError::TooFewChoices { available: 1, .. })
+        ));
+
+        // Once the category is out of tickets and reset, both choices can be picked.
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Inventory {
+                choices: vec![choice("this", 0), choice("that", 0)],
+                group: None,
+                restock: Some(config::Restock::Reset { tickets: 1 }),
+            },
+        );
+        let picks = engine
+            .pick_many(&mut config, "things".to_string(), 2, &[])
+            .expect("unexpected");
+
+        assert_eq!(picks.len(), 2);
+    }
+
+    #[test]
+    /// Every lottery choice accepted in one pick should end up without tickets.
+    fn test_pick_many_lottery() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_call_display_table().returning(|| false);
+        ui.expect_prompt_choice().times(2).returning(|_| true);
+        let mut engine = Engine::new(&ui);
+        let choice = |name: &str| config::LotteryChoice {
+            name: name.to_string(),
+            tickets: 1,
+            weight: 1,
+            tags: vec![],
+            details: Default::default(),
+            preferences: Default::default(),
+            availability: Default::default(),
+        };
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Lottery {
+                choices: vec![choice("this"), choice("that"), choice("the other")],
+                group: None,
+            },
+        );
+
+        let picks = engine
+            .pick_many(&mut config, "things".to_string(), 2, &[])
+            .expect("unexpected");
+
+        match &config["things"] {
+            config::ConfigCategory::Lottery { choices, .. } => {
+                for choice in choices {
+                    let expected = if picks.contains(&choice.name) { 0 } else { 3 };
+                    assert_eq!(choice.tickets, expected, "{:?}", choice);
+                }
+            }
+            category => panic!("Unexpected category: {:?}", category),
+        }
+    }
+
+    #[test]
+    /// The pick should be abandoned if the Ui can no longer answer, leaving the category alone.
+    fn test_pick_aborted() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_call_display_table().returning(|| false);
+        ui.expect_prompt_choice().times(1).returning(|_| false);
+        ui.expect_is_aborted().times(1).returning(|| true);
+        let mut engine = Engine::new(&ui);
+        let category = config::ConfigCategory::Lru {
+            choices: vec![config::Choice::from("this"), config::Choice::from("that")],
+            group: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category.clone());
+
+        assert!(matches!(
+            engine.pick(&mut config, "things".to_string()),
+            Err(PickError::Aborted)
+        ));
+        assert_eq!(config["things"], category);
+    }
+
+    #[test]
+    fn test_history_entries() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_is_aborted().returning(|| false);
+        ui.expect_call_display_table().times(1).returning(|| false);
+        ui.expect_prompt_choice()
+            .times(3)
+            .returning(|choice| choice != "that");
+        let mut engine = Engine::new(&ui);
+        let choices = vec![
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
+        ];
+        let category = config::ConfigCategory::Lru {
+            choices,
+            group: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+
+        engine
+            .pick_many(&mut config, "things".to_string(), 2, &[])
+            .expect("unexpected");
+
+        let entries = engine.history_entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].category, "things");
+        assert_eq!(entries[0].model, "lru");
+        assert_eq!(entries[0].choice, "this");
+        assert!(entries[0].rejected.is_empty());
+        assert_eq!(entries[1].choice, "the other");
+        assert_eq!(entries[1].rejected, vec![String::from("that")]);
+    }
+
+    #[test]
+    fn test_pick_group() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_is_aborted().returning(|| false);
+        ui.expect_call_display_table().returning(|| false);
+        ui.expect_prompt_vote()
+            .times(9)
+            .returning(|participant, choice| match (participant, choice) {
+                ("alice", "this") => group::Vote::Veto,
+                ("bob", "that") => group::Vote::Veto,
+                ("carol", _) => group::Vote::Abstain,
+                _ => group::Vote::Accept,
+            });
+        let mut engine = Engine::new(&ui);
+        let category = config::ConfigCategory::Lru {
+            choices: vec![
+                config::Choice::from("this"),
+                config::Choice::from("that"),
+                config::Choice::from("the other"),
+            ],
+            group: Some(group::Group {
+                participants: vec!["alice".to_string(), "bob".to_string(), "carol".to_string()],
+                rule: group::Rule::NoVetoes,
+                combine: Default::default(),
+            }),
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+
+        let choice = engine
+            .pick(&mut config, "things".to_string())
+            .expect("unexpected");
+
+        assert_eq!(choice, "the other");
+        let entry = &engine.history_entries()[0];
+        assert_eq!(entry.rejected, vec!["this".to_string(), "that".to_string()]);
+        assert_eq!(entry.vetoes["this"], vec!["alice".to_string()]);
+        assert_eq!(entry.vetoes["that"], vec!["bob".to_string()]);
+        assert_eq!(entry.vetoes.len(), 2);
+    }
+
+    #[test]
+    fn test_pick_with_participants() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_call_display_table().returning(|| true);
+        ui.expect_display_table()
+            .withf(|t| {
+                let expected_table = ui::Table {
+                    footer: vec![
+                        ui::Cell::Text("Total"),
+                        ui::Cell::Text(""),
+                        ui::Cell::Text(""),
+                        ui::Cell::Unsigned(6),
+                        ui::Cell::Float(100.0),
+                    ],
+                    header: vec![
+                        ui::Cell::Text("Name"),
+                        ui::Cell::Text("alice"),
+                        ui::Cell::Text("bob"),
+                        ui::Cell::Text("Weight"),
+                        ui::Cell::Text("Chance"),
+                    ],
+                    rows: vec![ui::Row {
+                        cells: vec![
+                            ui::Cell::Text("that"),
+                            ui::Cell::Unsigned(2),
+                            ui::Cell::Unsigned(3),
+                            ui::Cell::Unsigned(6),
+                            ui::Cell::Float(100.0),
+                        ],
+                        chosen: true,
+                    }],
+                };
+                tables_equal(t, &expected_table)
+            })
+            .times(1)
+            .returning(|_| ());
+        // carol isn't present, so only alice and bob vote.
+        ui.expect_prompt_vote()
+            .withf(|participant, choice| participant != "carol" && choice == "that")
+            .times(2)
+            .returning(|_, _| group::Vote::Accept);
+        let mut engine = Engine::new(&ui);
+        engine.set_participants(vec!["alice".to_string(), "bob".to_string()]);
+        let mut this = config::WeightedChoice {
+            name: "this".to_string(),
+            weight: 5,
+            tags: vec![],
+            details: Default::default(),
+            preferences: Default::default(),
+            availability: Default::default(),
+        };
+        this.preferences.insert("alice".to_string(), 0);
+        let mut that = this.clone();
+        that.name = "that".to_string();
+        that.weight = 1;
+        that.preferences.insert("alice".to_string(), 2);
+        that.preferences.insert("bob".to_string(), 3);
+        let category = config::ConfigCategory::Weighted {
+            choices: vec![this, that],
+            group: Some(group::Group {
+                participants: vec!["alice".to_string(), "bob".to_string(), "carol".to_string()],
+                rule: group::Rule::Unanimous,
+                combine: group::Combine::Product,
+            }),
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+
+        let choice = engine
+            .pick(&mut config, "things".to_string())
+            .expect("unexpected");
+
+        assert_eq!(choice, "that");
+
+        engine.set_participants(vec!["alice".to_string(), "dave".to_string()]);
+        let error = engine.pick(&mut config, "things".to_string()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The category `things` doesn't know the participant `dave`."
+        );
+    }
+
+    #[test]
+    fn test_pick_group_without_participants() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        let category = config::ConfigCategory::Even {
+            choices: vec![config::Choice::from("this")],
+            group: Some(group::Group {
+                participants: vec![],
+                rule: group::Rule::Majority,
+                combine: Default::default(),
+            }),
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+
+        assert_eq!(
+            engine
+                .pick(&mut config, "things".to_string())
+                .unwrap_err()
+                .to_string(),
+            "The `group` setting of the category `things` is invalid: it must have at least one \
+             participant."
+        );
+    }
+
+    #[test]
+    fn test_pick_many_inventory() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_call_display_table().times(1).returning(|| false);
+        ui.expect_prompt_choice().times(3).returning(|_| true);
+        let mut engine = Engine::new(&ui);
+        engine.set_rng(FakeRng(0));
+        let choices = vec![
+            config::InventoryChoice {
+                name: "this".to_string(),
+                tickets: 1,
+                restocked: None,
+                tags: vec![],
+                details: Default::default(),
+                restock: None,
+                availability: Default::default(),
+            },
+            config::InventoryChoice {
+                name: "that".to_string(),
+                tickets: 2,
+                restocked: None,
+                tags: vec![],
+                details: Default::default(),
+                restock: None,
+                availability: Default::default(),
+            },
+            config::InventoryChoice {
+                name: "the other".to_string(),
+                tickets: 3,
+                restocked: None,
+                tags: vec![],
+                details: Default::default(),
+                restock: None,
+                availability: Default::default(),
+            },
+        ];
+        let category = config::ConfigCategory::Inventory {
+            choices,
+            group: None,
+            restock: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+
+        let mut picks = engine
+            .pick_many(&mut config, "things".to_string(), 3, &[])
+            .expect("unexpected");
+
+        // Every choice should have been picked exactly once, and lost exactly one ticket.
+        picks.sort();
+        assert_eq!(picks, vec!["that", "the other", "this"]);
+        assert_eq!(
+            config["things"],
+            config::ConfigCategory::Inventory {
+                choices: vec![
+                    config::InventoryChoice {
+                        name: "this".to_string(),
+                        tickets: 0,
+                        restocked: None,
+                        tags: vec![],
+                        details: Default::default(),
+                        restock: None,
+                        availability: Default::default(),
+                    },
+                    config::InventoryChoice {
+                        name: "that".to_string(),
+                        tickets: 1,
+                        restocked: None,
+                        tags: vec![],
+                        details: Default::default(),
+                        restock: None,
+                        availability: Default::default(),
+                    },
+                    config::InventoryChoice {
+                        name: "the other".to_string(),
+                        tickets: 2,
+                        restocked: None,
+                        tags: vec![],
+                        details: Default::default(),
+                        restock: None,
+                        availability: Default::default(),
+                    },
+                ],
+                group: None,
+                restock: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_pick_many_too_few_choices() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        let choices = vec![config::Choice::from("this"), config::Choice::from("that")];
+        let category = config::ConfigCategory::Even {
+            choices,
+            group: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+
+        match engine.pick_many(&mut config, "things".to_string(), 3, &[]) {
+            Ok(_) => {
+                panic!("Picking more items than there are choices should have returned an error.");
+            }
+            Err(error) => {
+                assert_eq!(
+                    format!("{}", error),
+                    "Cannot pick 3 items from the category `things`, which only has 2 distinct \
+                     choices."
+                );
+            }
+        }
+        assert!(matches!(
+            engine.pick_many(&mut config, "things".to_string(), 0, &[]),
+            Err(PickError::NothingRequested(category)) if category == "things"
+        ));
+    }
+
+    /// A Ui that rejects every choice should end the pick with an error, rather than being offered
+    /// the choices forever.
+    #[test]
+    fn test_pick_all_rejected() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_is_aborted().returning(|| false);
+        ui.expect_call_display_table().returning(|| false);
+        ui.expect_prompt_choice().times(6).returning(|_| false);
+        let mut engine = Engine::new(&ui);
+        let mut config = BTreeMap::new();
+        let choices = vec![config::Choice::from("this"), config::Choice::from("that")];
+        config.insert(
+            "even".to_string(),
+            config::ConfigCategory::Even {
+                choices: choices.clone(),
+                group: None,
+            },
+        );
+        config.insert(
+            "gaussian".to_string(),
+            config::ConfigCategory::Gaussian {
+                choices: choices.clone(),
+                stddev_scaling_factor: 3.0,
+                group: None,
+            },
+        );
+        config.insert(
+            "lru".to_string(),
+            config::ConfigCategory::Lru {
+                choices,
+                group: None,
+            },
+        );
+        let unchanged = config.clone();
+
+        for category in ["even", "gaussian", "lru"] {
+            let error = engine.pick(&mut config, category.to_string()).unwrap_err();
+
+            assert_eq!(
+                error.to_string(),
+                format!("Every choice in the category `{}` was rejected.", category)
+            );
+        }
+        assert_eq!(config, unchanged);
+        assert!(engine.history_entries().is_empty());
+    }
+
+    /// Picking several items should display a single table with every accepted item marked.
+    #[test]
+    fn test_pick_no_choices() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Even {
+                choices: vec![],
+                group: None,
+            },
+        );
+
+        match engine.pick(&mut config, "things".to_string()) {
+            Err(PickError::NoChoices(category)) => assert_eq!(category, "things"),
+            _ => panic!("The wrong value was returned."),
+        }
+    }
+
+    #[test]
+    fn test_pick_all_tickets_exhausted() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Inventory {
+                choices: vec![config::InventoryChoice {
+                    name: "this".to_string(),
+                    tickets: 0,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
+                }],
+                group: None,
+                restock: None,
+            },
+        );
+
+        let error = engine.pick(&mut config, "things".to_string()).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Every choice in the category `things` is out of tickets."
+        );
+    }
+
+    #[test]
+    fn test_pick_all_cooling_down() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        engine.set_now(Local.with_ymd_and_hms(2021, 6, 2, 12, 0, 0).unwrap());
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Recency {
+                choices: vec![config::RecencyChoice {
+                    name: "this".to_string(),
+                    last_picked: Some(Local.with_ymd_and_hms(2021, 6, 1, 12, 0, 1).unwrap()),
+                    cooldown: Some(std::time::Duration::from_secs(24 * 60 * 60)),
+                    tags: vec![],
+                    details: Default::default(),
+                    availability: Default::default(),
+                }],
+                group: None,
+            },
+        );
+
+        let error = engine.pick(&mut config, "things".to_string()).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Every choice in the category `things` is cooling down."
+        );
+    }
+
+    #[test]
+    fn test_pick_all_unavailable() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        engine.set_now(Local.with_ymd_and_hms(2021, 6, 2, 12, 0, 0).unwrap());
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Rotation {
+                choices: vec![config::RotationChoice {
+                    name: "this".to_string(),
+                    assignments: 0,
+                    capacity: 1,
+                    tags: vec![],
+                    details: Default::default(),
+                    availability: availability::Availability {
+                        available: vec![],
+                        unavailable: vec![availability::Window::from(
+                            chrono::NaiveDate::from_ymd_opt(2021, 6, 2).unwrap(),
+                        )],
+                    },
+                }],
+                group: None,
+            },
+        );
+
+        let error = engine.pick(&mut config, "things".to_string()).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Every choice in the category `things` is unavailable."
+        );
+    }
+
+    #[test]
+    fn test_pick_invalid_parameter() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        let mut config = BTreeMap::new();
+        config.insert(
+            "gaussian".to_string(),
+            config::ConfigCategory::Gaussian {
+                choices: vec!["this".into()],
+                stddev_scaling_factor: 0.0,
+                group: None,
+            },
+        );
+        config.insert(
+            "weighted".to_string(),
+            config::ConfigCategory::Weighted {
+                choices: vec![config::WeightedChoice {
+                    name: "this".to_string(),
+                    weight: 0,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
+                }],
+                group: None,
+            },
+        );
+
+        let error = engine
+            .pick(&mut config, "gaussian".to_string())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The `stddev_scaling_factor` setting of the category `gaussian` is invalid: it must be \
+             a number greater than 0."
+        );
+        let error = engine
+            .pick(&mut config, "weighted".to_string())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The `weight` setting of the category `weighted` is invalid: at least one choice must \
+             have a weight greater than 0."
+        );
+    }
+
+    #[test]
+    fn test_pick_many_verbose() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_call_display_table().times(1).returning(|| true);
+        ui.expect_display_table()
+            .withf(|t| {
+                let expected_table = ui::Table {
+                    footer: vec![],
+                    header: vec![ui::Cell::Text("Name")],
+                    rows: vec![
+                        ui::Row {
+                            cells: vec![ui::Cell::Text("the other")],
+                            chosen: false,
+                        },
+                        ui::Row {
+                            cells: vec![ui::Cell::Text("that")],
+                            chosen: true,
+                        },
+                        ui::Row {
+                            cells: vec![ui::Cell::Text("this")],
+                            chosen: true,
+                        },
+                    ],
+                };
+                *t == expected_table
+            })
+            .times(1)
+            .returning(|_| ());
+        ui.expect_prompt_choice().times(2).returning(|_| true);
+        let mut engine = Engine::new(&ui);
+        let choices = vec![
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
+        ];
+        let category = config::ConfigCategory::Lru {
+            choices,
+            group: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+
+        let picks = engine
+            .pick_many(&mut config, "things".to_string(), 2, &[])
+            .expect("unexpected");
+
+        assert_eq!(picks, vec![String::from("this"), String::from("that")]);
+    }
+
+    #[test]
+    fn test_pick_with_details() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_call_display_table().returning(|| true);
+        ui.expect_display_table()
+            .withf(|t| {
+                let expected_table = ui::Table {
+                    footer: vec![],
+                    header: vec![
+                        ui::Cell::Text("Name"),
+                        ui::Cell::Text("Description"),
+                        ui::Cell::Text("phone"),
+                    ],
+                    rows: vec![
+                        ui::Row {
+                            cells: vec![
+                                ui::Cell::Text("that"),
+                                ui::Cell::Text(""),
+                                ui::Cell::Text("555-0100"),
+                            ],
+                            chosen: false,
+                        },
+                        ui::Row {
+                            cells: vec![
+                                ui::Cell::Text("this"),
+                                ui::Cell::Text("The first one"),
+                                ui::Cell::Text(""),
+                            ],
+                            chosen: true,
+                        },
+                    ],
+                };
+                tables_equal(t, &expected_table)
+            })
+            .times(1)
+            .returning(|_| ());
+        ui.expect_prompt_choice_with_details()
+            .withf(|choice, details| {
+                choice == "this" && details.description.as_deref() == Some("The first one")
+            })
+            .times(1)
+            .returning(|_, _| true);
+        let mut engine = Engine::new(&ui);
+        let mut that = config::Choice::from("that");
+        that.details
+            .attributes
+            .insert("phone".to_string(), "555-0100".to_string());
+        let mut this = config::Choice::from("this");
+        this.details.description = Some("The first one".to_string());
+        let category = config::ConfigCategory::Lru {
+            choices: vec![this, that],
+            group: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category);
+
+        let choice = engine
+            .pick(&mut config, "things".to_string())
+            .expect("unexpected");
+
+        assert_eq!(choice, "this");
+        assert_eq!(
+            config["things"].details("this").unwrap().description,
+            Some("The first one".to_string())
+        );
+    }
+
+    #[test]
+    fn test_display_category() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_display_table()
+            .withf(|t| {
+                let expected_table = ui::Table {
+                    footer: vec![
+                        ui::Cell::Text("Total"),
+                        ui::Cell::Unsigned(4),
+                        ui::Cell::Float(100.0),
+                    ],
+                    header: vec![
+                        ui::Cell::Text("Name"),
+                        ui::Cell::Text("Weight"),
+                        ui::Cell::Text("Chance"),
+                    ],
+                    rows: vec![
+                        ui::Row {
+                            cells: vec![
+                                ui::Cell::Text("this"),
+                                ui::Cell::Unsigned(1),
+                                ui::Cell::Float(25.0),
+                            ],
+                            chosen: false,
+                        },
+                        ui::Row {
+                            cells: vec![
+                                ui::Cell::Text("that"),
+                                ui::Cell::Unsigned(3),
+                                ui::Cell::Float(75.0),
+                            ],
+                            chosen: false,
+                        },
+                    ],
+                };
+                tables_equal(t, &expected_table)
+            })
+            .times(1)
+            .returning(|_| ());
+        let engine = Engine::new(&ui);
+        let choices = vec![
+            config::WeightedChoice {
+                name: "this".to_string(),
+                weight: 1,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
+            },
+            config::WeightedChoice {
+                name: "that".to_string(),
+                weight: 3,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
+            },
+        ];
+        let category = config::ConfigCategory::Weighted {
+            choices,
+            group: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category.clone());
+
+        engine
+            .display_category(&config, "things".to_string())
+            .expect("unexpected");
+
+        // Displaying a category must not change it.
+        assert_eq!(config["things"], category);
+        assert!(engine
+            .display_category(&config, "does not exist".to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn test_set_seed() {
+        let choices = vec![
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
+        ];
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Gaussian {
+                choices,
+                stddev_scaling_factor: 3.0,
+                group: None,
+            },
+        );
+        let ui = ui::AutoAcceptUi::new();
+        let mut picks = vec![];
+
+        for _ in 0..2 {
+            let mut config = config.clone();
+            let mut engine = Engine::new(&ui);
+            engine.set_seed(42);
+            picks.push(
+                engine
+                    .pick_many(&mut config, "things".to_string(), 3, &[])
+                    .expect("unexpected"),
+            );
+        }
+
+        // The same seed must lead to the same picks.
+        assert_eq!(picks[0], picks[1]);
+    }
+
+    #[test]
+    fn test_simulate() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        let choices = vec![
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
+        ];
+        let category = config::ConfigCategory::Lru {
+            choices,
+            group: None,
+        };
+        let mut config = BTreeMap::new();
+        config.insert("things".to_string(), category.clone());
+
+        let simulation = engine
+            .simulate(&config, "things".to_string(), 7)
+            .expect("unexpected");
+
+        // The Lru model cycles through the choices in order.
+        assert_eq!(
+            simulation,
+            Simulation {
+                rounds: 7,
+                choices: vec![
+                    SimulatedChoice {
+                        name: "this".to_string(),
+                        picks: 3,
+                        frequency: 3.0 / 7.0,
+                        mean_gap: Some(3.0),
+                        longest_drought: 2,
+                    },
+                    SimulatedChoice {
+                        name: "that".to_string(),
+                        picks: 2,
+                        frequency: 2.0 / 7.0,
+                        mean_gap: Some(3.0),
+                        longest_drought: 2,
+                    },
+                    SimulatedChoice {
+                        name: "the other".to_string(),
+                        picks: 2,
+                        frequency: 2.0 / 7.0,
+                        mean_gap: Some(3.0),
+                        longest_drought: 2,
+                    },
+                ]
+            }
+        );
+        // Simulating must not change the config.
+        assert_eq!(config["things"], category);
+    }
+
+    #[test]
+    /// The simulation should stop early once the inventory runs out.
+    fn test_simulate_inventory() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Inventory {
+                choices: vec![config::InventoryChoice {
+                    name: "this".to_string(),
+                    tickets: 2,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
+                }],
+                group: None,
+                restock: None,
+            },
+        );
+
+        let simulation = engine
+            .simulate(&config, "things".to_string(), 5)
+            .expect("unexpected");
+
+        assert_eq!(simulation.rounds, 2);
+        assert_eq!(simulation.choices[0].picks, 2);
     }
 
     #[test]
-    fn test_get_consent() {
-        let mut ui = ui::MockUi::new();
-        ui.expect_prompt_choice()
-            .with(predicate::in_iter(vec![
-                "you want this",
-                "you don't want this",
-            ]))
-            .times(2)
-            .returning(|x| !x.contains("don't"));
+    /// The simulation should keep going if the inventory is restocked once it runs out.
+    fn test_simulate_restock() {
+        let ui = ui::MockUi::new();
         let mut engine = Engine::new(&ui);
+        let choice = |name: &str| config::InventoryChoice {
+            name: name.to_string(),
+            tickets: 1,
+            restocked: None,
+            tags: vec![],
+            details: Default::default(),
+            restock: None,
+            availability: Default::default(),
+        };
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Inventory {
+                choices: vec![choice("this"), choice("that")],
+                group: None,
+                restock: Some(config::Restock::Reset { tickets: 1 }),
+            },
+        );
+
+        let simulation = engine
+            .simulate(&config, "things".to_string(), 6)
+            .expect("unexpected");
 
-        assert!(engine.get_consent("you want this"));
-        assert!(!engine.get_consent("you don't want this"));
+        assert_eq!(simulation.rounds, 6);
+        assert_eq!(simulation.choices[0].picks, 3);
+        assert_eq!(simulation.choices[1].picks, 3);
     }
 
     #[test]
-    fn test_pick() {
+    /// Picking should restock the inventory first, and the restock should be saved.
+    fn test_pick_restock() {
         let mut ui = ui::MockUi::new();
-        ui.expect_call_display_table().times(2).returning(|| false);
+        ui.expect_call_display_table().times(1).returning(|| false);
         ui.expect_prompt_choice()
-            .with(predicate::in_iter(vec!["that", "this"]))
-            .times(2)
-            .returning(|c| c == "that");
+            .times(1)
+            .with(predicate::eq("this"))
+            .returning(|_| true);
+        let now = Local.with_ymd_and_hms(2021, 6, 1, 12, 0, 0).unwrap();
         let mut engine = Engine::new(&ui);
-        engine.set_rng(FakeRng(0));
-        let choices = vec![
-            String::from("this"),
-            String::from("that"),
-            String::from("the other"),
-        ];
-        let category = config::ConfigCategory::Even { choices };
+        engine.set_now(now);
         let mut config = BTreeMap::new();
-        config.insert("things".to_string(), category);
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Inventory {
+                choices: vec![config::InventoryChoice {
+                    name: "this".to_string(),
+                    tickets: 0,
+                    restocked: Some(now - chrono::Duration::days(8)),
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: Some(config::Restock::TopUp {
+                        max: 3,
+                        every: std::time::Duration::from_secs(7 * 24 * 60 * 60),
+                    }),
+                    availability: Default::default(),
+                }],
+                group: None,
+                restock: None,
+            },
+        );
 
         let choice = engine
             .pick(&mut config, "things".to_string())
             .expect("unexpected");
 
-        assert_eq!(choice, "that");
+        assert_eq!(choice, "this");
+        match &config["things"] {
+            config::ConfigCategory::Inventory { choices, .. } => {
+                assert_eq!(choices[0].tickets, 2);
+                assert_eq!(choices[0].restocked, Some(now - chrono::Duration::days(1)));
+            }
+            category => panic!("Unexpected category: {:?}", category),
+        }
     }
 
     #[test]
-    fn test_pick_nonexistant_category() {
-        let ui = ui::MockUi::new();
-        let mut engine = Engine::new(&ui);
-        let choices = vec![
-            String::from("this"),
-            String::from("that"),
-            String::from("the other"),
-        ];
-        let category = config::ConfigCategory::Even { choices };
-        let mut config = BTreeMap::new();
-        config.insert("things".to_string(), category);
+    fn test_simulated_choice_droughts() {
+        let choice = SimulatedChoice::new("this".to_string(), &[3, 4], 10);
+        assert_eq!(choice.longest_drought, 5);
+        assert_eq!(choice.mean_gap, Some(1.0));
 
-        match engine.pick(&mut config, "does not exist".to_string()) {
-            Ok(_) => {
-                panic!("The non-existant category should have returned an error.");
-            }
-            Err(error) => {
-                assert_eq!(
-                    format!("{}", error),
-                    "The category `does not exist` was not found in the given config."
-                );
-            }
-        }
+        let choice = SimulatedChoice::new("this".to_string(), &[], 10);
+        assert_eq!(choice.longest_drought, 10);
+        assert_eq!(choice.mean_gap, None);
+        assert_eq!(choice.frequency, 0.0);
     }
 
     #[test]
@@ -501,12 +3075,12 @@ mod tests {
         let mut engine = Engine::new(&ui);
         engine.set_rng(FakeRng(0));
         let choices = vec![
-            String::from("this"),
-            String::from("that"),
-            String::from("the other"),
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
         ];
 
-        let result = engine.pick_even(&choices);
+        let result = engine.pick_even(&choices, &[]).unwrap();
 
         assert_eq!(result, "this");
     }
@@ -530,20 +3104,20 @@ mod tests {
         let mut engine = Engine::new(&ui);
         engine.set_rng(rand::rngs::SmallRng::seed_from_u64(555));
         let mut choices = vec![
-            String::from("this"),
-            String::from("that"),
-            String::from("the other"),
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
         ];
 
-        let result = engine.pick_gaussian(&mut choices, 3.0);
+        let result = engine.pick_gaussian(&mut choices, 3.0, &[]).unwrap();
 
         assert_eq!(result, "that");
         assert_eq!(
             choices,
             vec![
-                String::from("this"),
-                String::from("the other"),
-                String::from("that")
+                config::Choice::from("this"),
+                config::Choice::from("the other"),
+                config::Choice::from("that")
             ]
         );
     }
@@ -592,20 +3166,20 @@ mod tests {
         let mut engine = Engine::new(&ui);
         engine.set_rng(rand::rngs::SmallRng::seed_from_u64(555));
         let mut choices = vec![
-            String::from("this"),
-            String::from("that"),
-            String::from("the other"),
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
         ];
 
-        let result = engine.pick_gaussian(&mut choices, 3.0);
+        let result = engine.pick_gaussian(&mut choices, 3.0, &[]).unwrap();
 
         assert_eq!(result, "that");
         assert_eq!(
             choices,
             vec![
-                String::from("this"),
-                String::from("the other"),
-                String::from("that")
+                config::Choice::from("this"),
+                config::Choice::from("the other"),
+                config::Choice::from("that")
             ]
         );
     }
@@ -613,17 +3187,14 @@ mod tests {
     #[test]
     fn test_pick_inventory() {
         let mut ui = ui::MockUi::new();
+        ui.expect_is_aborted().returning(|| false);
         let mut counter = 0;
-        ui.expect_call_display_table().times(4).returning(|| false);
-        ui.expect_info()
-            .times(1)
-            .with(predicate::eq("🤨"))
-            .returning(|_| ());
+        ui.expect_call_display_table().times(2).returning(|| false);
         ui.expect_prompt_choice()
-            .times(4)
+            .times(2)
             .with(predicate::in_iter(vec!["that", "the other"]))
             .returning(move |_| {
-                if counter == 3 {
+                if counter == 1 {
                     true
                 } else {
                     counter += 1;
@@ -636,18 +3207,33 @@ mod tests {
             config::InventoryChoice {
                 name: "this".to_string(),
                 tickets: 0,
+                restocked: None,
+                tags: vec![],
+                details: Default::default(),
+                restock: None,
+                availability: Default::default(),
             },
             config::InventoryChoice {
                 name: "that".to_string(),
                 tickets: 2,
+                restocked: None,
+                tags: vec![],
+                details: Default::default(),
+                restock: None,
+                availability: Default::default(),
             },
             config::InventoryChoice {
                 name: "the other".to_string(),
                 tickets: 3,
+                restocked: None,
+                tags: vec![],
+                details: Default::default(),
+                restock: None,
+                availability: Default::default(),
             },
         ];
 
-        let result = engine.pick_inventory(&mut choices);
+        let result = engine.pick_inventory(&mut choices, &[]).unwrap();
 
         assert_eq!(result, "the other");
         assert_eq!(
@@ -655,15 +3241,30 @@ mod tests {
             vec![
                 config::InventoryChoice {
                     name: "this".to_string(),
-                    tickets: 0
+                    tickets: 0,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
                 },
                 config::InventoryChoice {
                     name: "that".to_string(),
-                    tickets: 2
+                    tickets: 2,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
                 },
                 config::InventoryChoice {
                     name: "the other".to_string(),
-                    tickets: 2
+                    tickets: 2,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
                 }
             ]
         );
@@ -719,18 +3320,33 @@ mod tests {
             config::InventoryChoice {
                 name: "this".to_string(),
                 tickets: 0,
+                restocked: None,
+                tags: vec![],
+                details: Default::default(),
+                restock: None,
+                availability: Default::default(),
             },
             config::InventoryChoice {
                 name: "that".to_string(),
                 tickets: 2,
+                restocked: None,
+                tags: vec![],
+                details: Default::default(),
+                restock: None,
+                availability: Default::default(),
             },
             config::InventoryChoice {
                 name: "the other".to_string(),
                 tickets: 3,
+                restocked: None,
+                tags: vec![],
+                details: Default::default(),
+                restock: None,
+                availability: Default::default(),
             },
         ];
 
-        let result = engine.pick_inventory(&mut choices);
+        let result = engine.pick_inventory(&mut choices, &[]).unwrap();
 
         assert_eq!(result, "that");
         assert_eq!(
@@ -738,15 +3354,30 @@ mod tests {
             vec![
                 config::InventoryChoice {
                     name: "this".to_string(),
-                    tickets: 0
+                    tickets: 0,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
                 },
                 config::InventoryChoice {
                     name: "that".to_string(),
-                    tickets: 1
+                    tickets: 1,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
                 },
                 config::InventoryChoice {
                     name: "the other".to_string(),
-                    tickets: 3
+                    tickets: 3,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
                 }
             ]
         );
@@ -756,6 +3387,7 @@ mod tests {
     fn test_pick_lru() {
         // The user says no to the first one and yes to the second.
         let mut ui = ui::MockUi::new();
+        ui.expect_is_aborted().returning(|| false);
         ui.expect_call_display_table().times(2).returning(|| false);
         ui.expect_prompt_choice()
             .with(predicate::in_iter(vec!["this", "that"]))
@@ -764,24 +3396,44 @@ mod tests {
         let mut engine = Engine::new(&ui);
         engine.set_rng(FakeRng(0));
         let mut choices = vec![
-            String::from("this"),
-            String::from("that"),
-            String::from("the other"),
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
         ];
 
-        let result = engine.pick_lru(&mut choices);
+        let result = engine.pick_lru(&mut choices, &[]).unwrap();
 
         assert_eq!(result, "that");
         assert_eq!(
             choices,
             vec![
-                String::from("this"),
-                String::from("the other"),
-                String::from("that")
+                config::Choice::from("this"),
+                config::Choice::from("the other"),
+                config::Choice::from("that")
             ]
         );
     }
 
+    #[test]
+    /// pick_lru() should give up once every choice has been offered and rejected, rather than
+    /// offering them forever.
+    fn test_pick_lru_all_rejected() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_is_aborted().returning(|| false);
+        ui.expect_call_display_table().times(2).returning(|| false);
+        ui.expect_prompt_choice().times(2).returning(|_| false);
+        let mut engine = Engine::new(&ui);
+        let mut choices = vec![config::Choice::from("this"), config::Choice::from("that")];
+
+        let result = engine.pick_lru(&mut choices, &[]);
+
+        assert!(matches!(result, Err(PickError::AllRejected(_))));
+        assert_eq!(
+            choices,
+            vec![config::Choice::from("this"), config::Choice::from("that")]
+        );
+    }
+
     #[test]
     /// Test pick_lru() with the verbose flag set
     fn test_pick_lru_verbose() {
@@ -818,20 +3470,20 @@ mod tests {
         let mut engine = Engine::new(&ui);
         engine.set_rng(FakeRng(0));
         let mut choices = vec![
-            String::from("this"),
-            String::from("that"),
-            String::from("the other"),
+            config::Choice::from("this"),
+            config::Choice::from("that"),
+            config::Choice::from("the other"),
         ];
 
-        let result = engine.pick_lru(&mut choices);
+        let result = engine.pick_lru(&mut choices, &[]).unwrap();
 
         assert_eq!(result, "this");
         assert_eq!(
             choices,
             vec![
-                String::from("that"),
-                String::from("the other"),
-                String::from("this")
+                config::Choice::from("that"),
+                config::Choice::from("the other"),
+                config::Choice::from("this")
             ]
         );
     }
@@ -851,20 +3503,34 @@ mod tests {
                 name: "this".to_string(),
                 tickets: 1,
                 weight: 1,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
             config::LotteryChoice {
                 name: "that".to_string(),
                 tickets: 2,
                 weight: 4,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
             config::LotteryChoice {
                 name: "the other".to_string(),
                 tickets: 3,
                 weight: 9,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
         ];
 
-        let result = engine.pick_lottery(&mut choices);
+        let result = engine
+            .pick_lottery(&mut choices, group::Combine::Product, &[])
+            .unwrap();
 
         assert_eq!(result, "this");
         assert_eq!(
@@ -873,17 +3539,29 @@ mod tests {
                 config::LotteryChoice {
                     name: "this".to_string(),
                     tickets: 0,
-                    weight: 1
+                    weight: 1,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
                 },
                 config::LotteryChoice {
                     name: "that".to_string(),
                     tickets: 6,
-                    weight: 4
+                    weight: 4,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
                 },
                 config::LotteryChoice {
                     name: "the other".to_string(),
                     tickets: 12,
-                    weight: 9
+                    weight: 9,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
                 }
             ]
         );
@@ -895,17 +3573,14 @@ mod tests {
     #[test]
     fn test_pick_lottery_no_to_all_one_no_chance() {
         let mut ui = ui::MockUi::new();
+        ui.expect_is_aborted().returning(|| false);
         let mut counter = 0;
-        ui.expect_call_display_table().times(4).returning(|| false);
-        ui.expect_info()
-            .times(1)
-            .with(predicate::eq("🤨"))
-            .returning(|_| ());
+        ui.expect_call_display_table().times(2).returning(|| false);
         ui.expect_prompt_choice()
-            .times(4)
+            .times(2)
             .with(predicate::in_iter(vec!["that", "the other"]))
             .returning(move |_| {
-                if counter == 3 {
+                if counter == 1 {
                     true
                 } else {
                     counter += 1;
@@ -919,20 +3594,34 @@ mod tests {
                 name: "this".to_string(),
                 tickets: 0,
                 weight: 1,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
             config::LotteryChoice {
                 name: "that".to_string(),
                 tickets: 2,
                 weight: 4,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
             config::LotteryChoice {
                 name: "the other".to_string(),
                 tickets: 3,
                 weight: 9,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
         ];
 
-        let result = engine.pick_lottery(&mut choices);
+        let result = engine
+            .pick_lottery(&mut choices, group::Combine::Product, &[])
+            .unwrap();
 
         assert_eq!(result, "the other");
         assert_eq!(
@@ -941,22 +3630,336 @@ mod tests {
                 config::LotteryChoice {
                     name: "this".to_string(),
                     tickets: 1,
-                    weight: 1
+                    weight: 1,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
                 },
                 config::LotteryChoice {
                     name: "that".to_string(),
                     tickets: 6,
-                    weight: 4
+                    weight: 4,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
                 },
                 config::LotteryChoice {
                     name: "the other".to_string(),
                     tickets: 0,
-                    weight: 9
+                    weight: 9,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
                 }
             ]
         );
     }
 
+    #[test]
+    fn test_pick_rotation() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_is_aborted().returning(|| false);
+        ui.expect_call_display_table().returning(|| true);
+        let mut offers = mockall::Sequence::new();
+        ui.expect_display_table()
+            .withf(|t| {
+                let row = |name, assignments, capacity, chosen| ui::Row {
+                    cells: vec![
+                        ui::Cell::Text(name),
+                        ui::Cell::Unsigned(assignments),
+                        ui::Cell::Unsigned(capacity),
+                        ui::Cell::Text(""),
+                    ],
+                    chosen,
+                };
+                // The unavailable choice is listed at the bottom, with the reason.
+                let expected_table = ui::Table {
+                    footer: vec![],
+                    header: vec![
+                        ui::Cell::Text("Name"),
+                        ui::Cell::Text("Assignments"),
+                        ui::Cell::Text("Capacity"),
+                        ui::Cell::Text("Unavailable"),
+                    ],
+                    rows: vec![
+                        row("this", 2, 1, false),
+                        row("that", 3, 2, false),
+                        row("the other", 1, 1, true),
+                        ui::Row {
+                            cells: vec![
+                                ui::Cell::Text("away"),
+                                ui::Cell::Text(""),
+                                ui::Cell::Text(""),
+                                ui::Cell::Text("unavailable on 2021-06-02"),
+                            ],
+                            chosen: false,
+                        },
+                    ],
+                };
+                tables_equal(t, &expected_table)
+            })
+            .times(1)
+            .in_sequence(&mut offers)
+            .returning(|_| ());
+        ui.expect_prompt_choice()
+            .with(predicate::eq("the other"))
+            .times(1)
+            .in_sequence(&mut offers)
+            .returning(|_| false);
+        ui.expect_display_table()
+            .times(1)
+            .in_sequence(&mut offers)
+            .returning(|_| ());
+        ui.expect_prompt_choice()
+            .with(predicate::eq("that"))
+            .times(1)
+            .in_sequence(&mut offers)
+            .returning(|_| true);
+        let mut engine = Engine::new(&ui);
+        let now = Local.with_ymd_and_hms(2021, 6, 2, 12, 0, 0).unwrap();
+        engine.set_now(now);
+        let choice = |name: &str, assignments, capacity| config::RotationChoice {
+            name: name.to_string(),
+            assignments,
+            capacity,
+            tags: vec![],
+            details: Default::default(),
+            availability: Default::default(),
+        };
+        let mut away = choice("away", 0, 1);
+        away.availability
+            .unavailable
+            .push(availability::Window::from(now.date_naive()));
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Rotation {
+                choices: vec![
+                    choice("this", 2, 1),
+                    choice("that", 3, 2),
+                    choice("the other", 1, 1),
+                    away,
+                    choice("never", 0, 0),
+                ],
+                group: None,
+            },
+        );
+
+        let picked = engine
+            .pick(&mut config, "things".to_string())
+            .expect("unexpected");
+
+        assert_eq!(picked, "that");
+        match &config["things"] {
+            config::ConfigCategory::Rotation { choices, .. } => {
+                let assignments = choices.iter().map(|c| c.assignments).collect::<Vec<_>>();
+                assert_eq!(assignments, vec![2, 4, 1, 0, 0]);
+            }
+            category => panic!("Unexpected category: {:?}", category),
+        }
+    }
+
+    #[test]
+    // Choices with equal shares of the picks should be offered first equally often.
+    fn test_pick_rotation_ties() {
+        let ui = ui::AutoAcceptUi::new();
+        let mut picks = BTreeMap::new();
+        for seed in 0..100 {
+            let mut engine = Engine::new(&ui);
+            engine.set_seed(seed);
+            let mut config = BTreeMap::new();
+            config.insert(
+                "things".to_string(),
+                config::ConfigCategory::Rotation {
+                    choices: ["this", "that", "the other"]
+                        .iter()
+                        .map(|name| config::RotationChoice {
+                            name: name.to_string(),
+                            assignments: if *name == "the other" { 1 } else { 0 },
+                            capacity: 1,
+                            tags: vec![],
+                            details: Default::default(),
+                            availability: Default::default(),
+                        })
+                        .collect(),
+                    group: None,
+                },
+            );
+
+            let picked = engine
+                .pick(&mut config, "things".to_string())
+                .expect("unexpected");
+
+            *picks.entry(picked).or_insert(0) += 1;
+        }
+
+        assert_eq!(picks.len(), 2);
+        assert!(picks["this"] > 30, "Unexpected picks: {:?}", picks);
+        assert!(picks["that"] > 30, "Unexpected picks: {:?}", picks);
+    }
+
+    #[test]
+    fn test_pick_recency_verbose() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_call_display_table().times(1).returning(|| true);
+        ui.expect_display_table()
+            .withf(|t| {
+                let expected_table = ui::Table {
+                    footer: vec![
+                        ui::Cell::Text("Total"),
+                        ui::Cell::Unsigned(90),
+                        ui::Cell::Float(100.0),
+                    ],
+                    header: vec![
+                        ui::Cell::Text("Name"),
+                        ui::Cell::Text("Weight"),
+                        ui::Cell::Text("Chance"),
+                    ],
+                    rows: vec![
+                        ui::Row {
+                            cells: vec![
+                                ui::Cell::Text("that"),
+                                ui::Cell::Unsigned(16),
+                                ui::Cell::Float(17.778),
+                            ],
+                            chosen: true,
+                        },
+                        ui::Row {
+                            cells: vec![
+                                ui::Cell::Text("old"),
+                                ui::Cell::Unsigned(37),
+                                ui::Cell::Float(41.111),
+                            ],
+                            chosen: false,
+                        },
+                        ui::Row {
+                            cells: vec![
+                                ui::Cell::Text("the other"),
+                                ui::Cell::Unsigned(37),
+                                ui::Cell::Float(41.111),
+                            ],
+                            chosen: false,
+                        },
+                    ],
+                };
+                tables_equal(t, &expected_table)
+            })
+            .times(1)
+            .returning(|_| ());
+        ui.expect_prompt_choice()
+            .with(predicate::eq("that"))
+            .times(1)
+            .returning(|_| true);
+        let mut engine = Engine::new(&ui);
+        engine.set_rng(FakeRng(0));
+        let now = Local.with_ymd_and_hms(2021, 6, 2, 12, 0, 0).unwrap() + Duration::milliseconds(5);
+        engine.set_now(now);
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+        let choice =
+            |name: &str, last_picked: Option<(u32, u32)>, cooldown| config::RecencyChoice {
+                name: name.to_string(),
+                last_picked: last_picked
+                    .map(|(day, hour)| Local.with_ymd_and_hms(2021, 6, day, hour, 0, 0).unwrap()),
+                cooldown,
+                tags: vec![],
+                details: Default::default(),
+                availability: Default::default(),
+            };
+        // This was picked 12 hours ago, but it is cooling down for a day.
+        let mut choices = vec![
+            choice("this", Some((2, 0)), Some(day)),
+            choice("that", Some((1, 21)), Some(day / 2)),
+            choice("old", Some((1, 0)), None),
+            choice("the other", None, None),
+        ];
+
+        let result = engine.pick_recency(&mut choices, &[]).unwrap();
+
+        assert_eq!(result, "that");
+        // The time of the pick is recorded without its fraction of a second.
+        assert_eq!(
+            choices[1].last_picked,
+            Some(Local.with_ymd_and_hms(2021, 6, 2, 12, 0, 0).unwrap())
+        );
+        assert!(choices[1].is_cooling_down(now));
+    }
+
+    #[test]
+    fn test_simulate_recency() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        engine.set_seed(1);
+        let week = std::time::Duration::from_secs(7 * 24 * 60 * 60);
+        let choices = ["this", "that"]
+            .iter()
+            .map(|name| config::RecencyChoice {
+                name: name.to_string(),
+                last_picked: None,
+                cooldown: Some(week),
+                tags: vec![],
+                details: Default::default(),
+                availability: Default::default(),
+            })
+            .collect();
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Recency {
+                choices,
+                group: None,
+            },
+        );
+
+        let simulation = engine
+            .simulate(&config, "things".to_string(), 14)
+            .expect("unexpected");
+
+        // Each choice can only be picked once a week, so most days pass without a pick.
+        assert_eq!(simulation.rounds, 14);
+        assert_eq!(
+            simulation
+                .choices
+                .iter()
+                .map(|c| c.picks)
+                .collect::<Vec<_>>(),
+            vec![2, 2]
+        );
+        assert!(simulation.choices.iter().all(|c| c.mean_gap == Some(7.0)));
+    }
+
+    #[test]
+    // Each round should only pick from the choices that are available on its day.
+    fn test_simulate_availability() {
+        let ui = ui::MockUi::new();
+        let mut engine = Engine::new(&ui);
+        engine.set_seed(1);
+        // 2021-06-07 is a Monday.
+        engine.set_now(Local.with_ymd_and_hms(2021, 6, 7, 12, 0, 0).unwrap());
+        let weekend = serde_yaml::from_str("available:\n  - days: [sat, sun]").unwrap();
+        let mut config = BTreeMap::new();
+        config.insert(
+            "things".to_string(),
+            config::ConfigCategory::Lru {
+                choices: vec![config::Choice {
+                    availability: weekend,
+                    ..config::Choice::from("brunch")
+                }],
+                group: None,
+            },
+        );
+
+        let simulation = engine
+            .simulate(&config, "things".to_string(), 14)
+            .expect("unexpected");
+
+        assert_eq!(simulation.rounds, 14);
+        assert_eq!(simulation.choices[0].picks, 4);
+        assert_eq!(simulation.choices[0].longest_drought, 5);
+    }
+
     #[test]
     fn test_pick_weighted() {
         let mut ui = ui::MockUi::new();
@@ -971,64 +3974,115 @@ mod tests {
             config::WeightedChoice {
                 name: "this".to_string(),
                 weight: 1,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
             config::WeightedChoice {
                 name: "that".to_string(),
                 weight: 4,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
             config::WeightedChoice {
                 name: "the other".to_string(),
                 weight: 9,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
         ];
 
-        let result = engine.pick_weighted(&choices);
+        let result = engine
+            .pick_weighted(&choices, group::Combine::Product, &[])
+            .unwrap();
 
         assert_eq!(result, "this");
     }
 
+    /// Choices with a weight of 0 can never be picked, so they must not be left as the only
+    /// candidates once the user rejects the others.
+    #[test]
+    fn test_pick_weighted_zero_weight() {
+        let mut ui = ui::MockUi::new();
+        ui.expect_is_aborted().returning(|| false);
+        ui.expect_call_display_table().times(1).returning(|| false);
+        ui.expect_prompt_choice()
+            .with(predicate::eq("this"))
+            .times(1)
+            .returning(|_| false);
+        let mut engine = Engine::new(&ui);
+        let choices = vec![
+            config::WeightedChoice {
+                name: "this".to_string(),
+                weight: 1,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
+            },
+            config::WeightedChoice {
+                name: "that".to_string(),
+                weight: 0,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
+            },
+        ];
+
+        let result = engine.pick_weighted(&choices, group::Combine::Product, &[]);
+
+        assert!(matches!(result, Err(PickError::AllRejected(_))));
+    }
+
     /// There was a bug wherein saying no to all weighted options crashed rpick rather than
-    /// expressing disapproval.
+    /// ending the pick with an error.
     #[test]
     fn test_pick_weighted_no_to_all() {
         let mut ui = ui::MockUi::new();
-        let mut counter = 0;
-        ui.expect_call_display_table().times(4).returning(|| false);
-        ui.expect_info()
-            .times(1)
-            .with(predicate::eq("🤨"))
-            .returning(|_| ());
+        ui.expect_is_aborted().returning(|| false);
+        ui.expect_call_display_table().times(3).returning(|| false);
         ui.expect_prompt_choice()
-            .times(4)
+            .times(3)
             .with(predicate::in_iter(vec!["this", "that", "the other"]))
-            .returning(move |_| {
-                if counter == 3 {
-                    true
-                } else {
-                    counter += 1;
-                    false
-                }
-            });
+            .returning(|_| false);
         let mut engine = Engine::new(&ui);
         engine.set_rng(FakeRng(0));
         let choices = vec![
             config::WeightedChoice {
                 name: "this".to_string(),
                 weight: 1,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
             config::WeightedChoice {
                 name: "that".to_string(),
                 weight: 4,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
             config::WeightedChoice {
                 name: "the other".to_string(),
                 weight: 9,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
             },
         ];
 
-        let result = engine.pick_weighted(&choices);
+        let result = engine.pick_weighted(&choices, group::Combine::Product, &[]);
 
-        assert_eq!(result, "this");
+        assert!(matches!(result, Err(PickError::AllRejected(_))));
     }
 
     fn tables_equal(a: &ui::Table, b: &ui::Table) -> bool {
@@ -0,0 +1,174 @@
+/* Copyright © 2021 Randy Barlow
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, version 3 of the License.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
+//! # Groups
+//!
+//! A category can be picked from by a group of people rather than by one person. Each choice that
+//! is offered is put to every participant in turn, who can accept it, abstain, or veto it, and the
+//! group's [`Rule`] decides whether the votes are enough to accept the choice. Participants can
+//! also give each choice a preference, and the preferences of the participants who are present
+//! are combined into a single weight for the choice, as the group's [`Combine`] setting says.
+use serde::{Deserialize, Serialize};
+
+/// The people who decide together whether to accept a choice from a category.
+///
+/// # Attributes
+///
+/// * `participants` - The names of the people in the group, in the order they are asked.
+/// * `rule` - How the participants' votes decide whether a choice is accepted.
+/// * `combine` - How the preferences of the participants who are present are combined.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Group {
+    pub participants: Vec<String>,
+    #[serde(default)]
+    pub rule: Rule,
+    #[serde(default)]
+    pub combine: Combine,
+}
+
+/// How a [`Group`]'s votes decide whether a choice is accepted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    /// Every participant must accept the choice.
+    Unanimous,
+    /// More participants must accept the choice than veto it. Abstentions are not counted, but at
+    /// least one participant must accept.
+    Majority,
+    /// The choice is accepted unless a participant vetoes it.
+    #[default]
+    NoVetoes,
+}
+
+impl Rule {
+    /// Return `true` if the given votes are enough to accept a choice under this rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `votes` - The vote of each participant.
+    pub fn accepts(&self, votes: &[Vote]) -> bool {
+        let count = |vote| votes.iter().filter(|v| **v == vote).count();
+        match self {
+            Rule::Unanimous => votes.iter().all(|vote| *vote == Vote::Accept),
+            Rule::Majority => {
+                let accepts = count(Vote::Accept);
+                accepts > 0 && accepts > count(Vote::Veto)
+            }
+            Rule::NoVetoes => count(Vote::Veto) == 0,
+        }
+    }
+}
+
+/// How the preferences of several participants for a choice are combined into one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Combine {
+    /// Multiply the preferences, so that a choice anyone gives a 0 is never picked.
+    #[default]
+    Product,
+    /// Add the preferences together.
+    Sum,
+    /// Use the lowest preference, so that a choice is only as likely as its least keen
+    /// participant wants.
+    Min,
+}
+
+impl Combine {
+    /// Return the given preferences combined into one, or 1 if there are none.
+    ///
+    /// # Arguments
+    ///
+    /// * `preferences` - The preference of each participant.
+    pub fn apply(&self, preferences: &[u64]) -> u64 {
+        if preferences.is_empty() {
+            return 1;
+        }
+        match self {
+            Combine::Product => preferences
+                .iter()
+                .fold(1, |product, preference| product.saturating_mul(*preference)),
+            Combine::Sum => preferences
+                .iter()
+                .fold(0, |sum, preference| sum.saturating_add(*preference)),
+            Combine::Min => *preferences.iter().min().expect("preferences is not empty"),
+        }
+    }
+}
+
+/// A participant's answer when a choice is offered to a [`Group`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vote {
+    /// The participant accepts the choice.
+    Accept,
+    /// The participant doesn't mind either way.
+    Abstain,
+    /// The participant objects to the choice.
+    Veto,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use Vote::{Abstain, Accept, Veto};
+
+    #[test]
+    fn test_unanimous() {
+        assert!(Rule::Unanimous.accepts(&[Accept, Accept]));
+        assert!(!Rule::Unanimous.accepts(&[Accept, Abstain]));
+        assert!(!Rule::Unanimous.accepts(&[Accept, Veto]));
+    }
+
+    #[test]
+    fn test_majority() {
+        assert!(Rule::Majority.accepts(&[Accept, Accept, Veto]));
+        assert!(Rule::Majority.accepts(&[Accept, Abstain, Abstain]));
+        assert!(!Rule::Majority.accepts(&[Accept, Veto, Abstain]));
+        assert!(!Rule::Majority.accepts(&[Abstain, Abstain]));
+    }
+
+    #[test]
+    fn test_no_vetoes() {
+        assert!(Rule::NoVetoes.accepts(&[Accept, Abstain]));
+        assert!(Rule::NoVetoes.accepts(&[Abstain]));
+        assert!(!Rule::NoVetoes.accepts(&[Accept, Accept, Veto]));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let group: Group = serde_yaml::from_str("participants: [alice, bob]").unwrap();
+        assert_eq!(
+            group,
+            Group {
+                participants: vec!["alice".to_string(), "bob".to_string()],
+                rule: Rule::NoVetoes,
+                combine: Combine::Product,
+            }
+        );
+
+        let group: Group = serde_yaml::from_str("participants: [alice]\nrule: majority").unwrap();
+        assert_eq!(group.rule, Rule::Majority);
+
+        let group: Group = serde_yaml::from_str("participants: [alice]\ncombine: min").unwrap();
+        assert_eq!(group.combine, Combine::Min);
+    }
+
+    #[test]
+    fn test_combine() {
+        assert_eq!(Combine::Product.apply(&[2, 3, 4]), 24);
+        assert_eq!(Combine::Product.apply(&[2, 0]), 0);
+        assert_eq!(Combine::Sum.apply(&[2, 3, 4]), 9);
+        assert_eq!(Combine::Min.apply(&[2, 3, 4]), 2);
+        assert_eq!(Combine::Min.apply(&[]), 1);
+        assert_eq!(Combine::Product.apply(&[u64::MAX, 2]), u64::MAX);
+    }
+}
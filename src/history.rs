@@ -0,0 +1,196 @@
+/* Copyright © 2021 Randy Barlow
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, version 3 of the License.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
+//! # History
+//!
+//! This module defines the pick history, a log of every choice that has been accepted.
+//!
+//! The history is stored as a stream of YAML documents, one per accepted pick, so that new entries
+//! can be appended to the file without rewriting it.
+use std::collections::BTreeMap;
+use std::error;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+/// Append the given entries to the history file, creating it if it does not exist.
+///
+/// # Arguments
+///
+/// * `history_file_path` - A filesystem path to the history file.
+/// * `entries` - The entries to append to the history.
+pub fn append_history(
+    history_file_path: &str,
+    entries: &[HistoryEntry],
+) -> Result<(), Box<dyn error::Error>> {
+    let yaml = serialize_entries(entries)?;
+
+    let mut f = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(history_file_path)?;
+    f.write_all(yaml.as_bytes())?;
+    Ok(())
+}
+
+/// Replace the contents of the history file with the given entries.
+///
+/// # Arguments
+///
+/// * `history_file_path` - A filesystem path to the history file.
+/// * `entries` - The entries the history should contain, oldest first.
+pub fn write_history(
+    history_file_path: &str,
+    entries: &[HistoryEntry],
+) -> Result<(), Box<dyn error::Error>> {
+    let yaml = serialize_entries(entries)?;
+
+    config::write_atomically(history_file_path, &yaml)?;
+    Ok(())
+}
+
+/// Return the entries recorded in the history file, oldest first.
+///
+/// A history file that does not exist yet is treated as an empty history.
+///
+/// # Arguments
+///
+/// * `history_file_path` - A filesystem path to the history file.
+pub fn read_history(history_file_path: &str) -> Result<Vec<HistoryEntry>, Box<dyn error::Error>> {
+    let mut contents = String::new();
+    match File::open(history_file_path) {
+        Ok(mut f) => {
+            f.read_to_string(&mut contents)?;
+        }
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(error) => return Err(error.into()),
+    }
+
+    let mut entries = vec![];
+    for document in serde_yaml::Deserializer::from_str(&contents) {
+        entries.push(HistoryEntry::deserialize(document)?);
+    }
+    Ok(entries)
+}
+
+/// Serialize the given entries as a stream of YAML documents, one per entry.
+fn serialize_entries(entries: &[HistoryEntry]) -> Result<String, serde_yaml::Error> {
+    let mut yaml = String::new();
+    for entry in entries {
+        yaml.push_str(&serde_yaml::to_string(entry)?);
+    }
+    Ok(yaml)
+}
+
+/// A record of a single accepted pick.
+///
+/// # Attributes
+///
+/// * `timestamp` - When the choice was accepted.
+/// * `category` - The name of the category the choice was picked from.
+/// * `model` - The name of the model the category used at the time.
+/// * `choice` - The choice that was accepted.
+/// * `rejected` - The choices that were offered and rejected before this one was accepted, in the
+///   order they were offered.
+/// * `vetoes` - For categories picked by a group, the participants who vetoed each offered choice,
+///   keyed by choice. Choices that nobody vetoed are left out.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Local>,
+    pub category: String,
+    pub model: String,
+    pub choice: String,
+    #[serde(default)]
+    pub rejected: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub vetoes: BTreeMap<String, Vec<String>>,
+}
+
+/// Describes which [`HistoryEntry`]'s should be included when listing the history.
+///
+/// Every criterion that is set must match for an entry to be included.
+///
+/// # Attributes
+///
+/// * `category` - Only include entries from this category.
+/// * `choice` - Only include entries where this choice was accepted.
+/// * `since` - Only include entries accepted at or after this time.
+/// * `until` - Only include entries accepted before this time.
+#[derive(Debug, Default)]
+pub struct HistoryFilter {
+    pub category: Option<String>,
+    pub choice: Option<String>,
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+}
+
+impl HistoryFilter {
+    /// Return `true` if the given entry matches this filter.
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.category.as_ref().is_none_or(|c| *c == entry.category)
+            && self.choice.as_ref().is_none_or(|c| *c == entry.choice)
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp < until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn entry(category: &str, choice: &str, day: u32) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: Local.with_ymd_and_hms(2021, 3, day, 12, 0, 0).unwrap(),
+            category: category.to_string(),
+            model: "lru".to_string(),
+            choice: choice.to_string(),
+            rejected: vec![],
+            vetoes: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_append_and_read_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rpick.history.yml");
+        let path = path.to_str().unwrap();
+        let mut first = entry("restaurant", "Centro", 1);
+        first.rejected = vec!["Sitti".to_string()];
+        let second = entry("restaurant", "Sitti", 2);
+
+        assert_eq!(read_history(path).unwrap(), vec![]);
+        append_history(path, std::slice::from_ref(&first)).unwrap();
+        append_history(path, std::slice::from_ref(&second)).unwrap();
+
+        assert_eq!(read_history(path).unwrap(), vec![first, second]);
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = HistoryFilter {
+            category: Some("restaurant".to_string()),
+            since: Some(Local.with_ymd_and_hms(2021, 3, 2, 0, 0, 0).unwrap()),
+            ..Default::default()
+        };
+
+        assert!(!filter.matches(&entry("restaurant", "Centro", 1)));
+        assert!(filter.matches(&entry("restaurant", "Centro", 2)));
+        assert!(!filter.matches(&entry("on-call", "Alice", 2)));
+        assert!(HistoryFilter::default().matches(&entry("on-call", "Alice", 2)));
+    }
+}
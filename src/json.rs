@@ -0,0 +1,63 @@
+/* Copyright © 2021 Randy Barlow
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, version 3 of the License.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
+//! Define a Ui that reports to scripts with JSON, rather than talking to a person.
+
+use std::cell::RefCell;
+
+use rpick::ui;
+
+/// This implements the Ui trait for ```--output json```.
+///
+/// Json accepts every choice it is offered, since there is nobody to prompt. It keeps the tables
+/// the engine sends it so that they can be printed in a single JSON document once the command is
+/// done, and it writes messages to stderr so that they don't get mixed into that document.
+#[derive(Default)]
+pub struct Json {
+    /// The tables that have been displayed so far, already serialized.
+    tables: RefCell<Vec<serde_json::Value>>,
+}
+
+impl Json {
+    /// Construct a new Json.
+    pub fn new() -> Self {
+        Json::default()
+    }
+
+    /// Remove and return the most recently displayed table, forgetting any others.
+    pub fn take_table(&self) -> Option<serde_json::Value> {
+        std::mem::take(&mut *self.tables.borrow_mut()).pop()
+    }
+}
+
+impl ui::Ui for Json {
+    /// Return `true`, since scripts always get the chance table.
+    fn call_display_table(&self) -> bool {
+        true
+    }
+
+    /// Keep the given table so it can be printed later.
+    fn display_table(&self, table: &ui::Table) {
+        let table = serde_json::to_value(table).expect("Unable to serialize the table.");
+        self.tables.borrow_mut().push(table);
+    }
+
+    /// Print the given message to stderr.
+    fn info(&self, message: &str) {
+        eprintln!("{}", message);
+    }
+
+    /// Accept the given choice.
+    fn prompt_choice(&self, _choice: &str) -> bool {
+        true
+    }
+}
@@ -19,8 +19,6 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
 //! ```
 //! use std::collections::BTreeMap;
 //!
-//! use rand::SeedableRng;
-//!
 //! /// You need to define an interface. rpick will use this interface to interact with you during
 //! /// picks.
 //! struct Interface {};
@@ -40,23 +38,25 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
 //!
 //! let ui = Interface{};
 //! let mut engine = rpick::engine::Engine::new(&ui);
-//! // For the sake of this example, let's override the PRNG with a seeded PRNG so the assertion
-//! // works as expected at the end. You most likely do not want to do this in practice as it takes
-//! // the randomness out of the system.
-//! engine.set_rng(rand::rngs::SmallRng::seed_from_u64(37));
-//! let choices = vec![String::from("this"), String::from("that"),
-//!                    String::from("the other")];
-//! let category = rpick::config::ConfigCategory::Even{choices: choices};
+//! // For the sake of this example, let's seed the PRNG so the assertion works as expected at the
+//! // end. You most likely do not want to do this in practice as it takes the randomness out of the
+//! // system.
+//! engine.set_seed(37);
+//! let choices = vec!["this".into(), "that".into(), "the other".into()];
+//! let category = rpick::config::ConfigCategory::Even{choices: choices, group: None};
 //! let mut config = BTreeMap::new();
 //! config.insert("things".to_string(), category);
 //!
 //! let choice = engine.pick(&mut config, "things".to_string()).expect("unexpected");
 //!
-//! // 32-bit architectures have different PRNG results than 64-bit architectures, so we will
-//! // only run this assertion on 64-bit systems.
-//! #[cfg(target_pointer_width = "64")]
-//! assert_eq!(choice, "that");
+//! assert_eq!(choice, "the other");
 //! ```
+pub mod availability;
 pub mod config;
 pub mod engine;
+pub mod group;
+pub mod history;
+pub mod state;
+pub mod tags;
 pub mod ui;
+pub mod undo;
@@ -14,66 +14,843 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
 //!
 //! ```rpick``` helps pick items from a list of choices, using various algorithms.
 
+use std::error;
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use rpick::config::{ConfigSources, Format};
+use rpick::engine::PickError;
+use rpick::ui::Ui;
 use structopt::StructOpt;
 
 mod cli;
+mod json;
 
-const CONFIG_FILE: &str = "rpick.yml";
+const CONFIG_FILE: &str = "rpick";
 
 #[derive(StructOpt)]
 struct CliArgs {
-    /// The category you wish to pick from.
-    category: String,
-    #[structopt(short, long, env = "RPICK_CONFIG")]
+    /// The category you wish to pick from. Use the pick subcommand for a category that is named
+    /// like a subcommand.
+    category: Option<String>,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+    #[structopt(short = "n", long, default_value = "1", global = true)]
+    /// How many distinct items to pick from the category.
+    count: usize,
+    #[structopt(long, number_of_values = 1, global = true)]
+    /// Do not pick this choice this time, without changing it in the config. May be given more
+    /// than once.
+    exclude: Vec<String>,
+    #[structopt(short, long, env = "RPICK_CONFIG", global = true)]
     /// A path to the config file you wish to use.
     config: Option<String>,
-    #[structopt(short, long)]
+    #[structopt(long, possible_values = &["json", "toml", "yaml"], global = true)]
+    /// The format of the config file. By default, this is chosen by the file's extension.
+    format: Option<Format>,
+    #[structopt(long, env = "RPICK_SEED", global = true)]
+    /// Seed the random number generator, so that a pick can be reproduced exactly.
+    seed: Option<u64>,
+    #[structopt(long = "tag", number_of_values = 1, global = true)]
+    /// Only pick choices whose tags match this expression, such as "lunch and not expensive". May
+    /// be given more than once, in which case every expression must match.
+    tag: Vec<rpick::tags::TagExpression>,
+    #[structopt(long = "exclude-tag", number_of_values = 1, global = true)]
+    /// Never pick choices whose tags match this expression. May be given more than once.
+    exclude_tag: Vec<rpick::tags::TagExpression>,
+    #[structopt(long, use_delimiter = true, global = true)]
+    /// The participants who are present, separated by commas, such as "alice,bob". Their
+    /// preferences are combined to weigh the choices of weighted and lottery categories.
+    with: Vec<String>,
+    #[structopt(long, parse(try_from_str = parse_datetime), global = true)]
+    /// Pick as if it were this local time, such as 2021-03-01T12:30, or this date (meaning
+    /// midnight) or RFC 3339 time. Choices are only offered if they are available then.
+    at: Option<DateTime<Local>>,
+    #[structopt(
+        short,
+        long,
+        default_value = "text",
+        possible_values = &["text", "json"],
+        global = true
+    )]
+    /// The format to print results in. With json, the first choice is accepted without prompting.
+    output: Output,
+    #[structopt(short, long, global = true)]
     /// Print more information about the pick.
     verbose: bool,
+    #[structopt(short, long, visible_alias = "non-interactive", global = true)]
+    /// Accept the first choice without prompting.
+    yes: bool,
+}
+
+/// The formats that rpick can print its results in.
+#[derive(Clone, Copy, PartialEq)]
+enum Output {
+    Text,
+    Json,
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Output::Text),
+            "json" => Ok(Output::Json),
+            _ => Err(format!("Unknown output format `{}`.", s)),
+        }
+    }
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Check the config for problems, exiting with an error if any are serious.
+    Check,
+    /// Copy the config to a new file in another format.
+    Convert {
+        /// The path to write the converted config to. It must not exist yet.
+        destination: String,
+        #[structopt(long, possible_values = &["json", "toml", "yaml"])]
+        /// The format to convert to. By default, this is chosen by the destination's extension.
+        to: Option<Format>,
+    },
+    /// Add a choice to a category.
+    Add {
+        /// The category to add the choice to.
+        category: String,
+        /// The name of the new choice.
+        choice: String,
+        #[structopt(long)]
+        /// The choice's weight, for the lottery and weighted models.
+        weight: Option<u64>,
+        #[structopt(long)]
+        /// The choice's starting number of tickets, for the inventory and lottery models.
+        tickets: Option<u64>,
+    },
+    /// List the picks that have been accepted, oldest first.
+    History {
+        /// Only list picks from this category.
+        category: Option<String>,
+        #[structopt(long)]
+        /// Only list picks where this choice was accepted.
+        choice: Option<String>,
+        #[structopt(long, parse(try_from_str = parse_datetime))]
+        /// Only list picks accepted at or after this date (e.g. 2021-03-01) or RFC 3339 time.
+        since: Option<DateTime<Local>>,
+        #[structopt(long, parse(try_from_str = parse_datetime))]
+        /// Only list picks accepted before this date (e.g. 2021-04-01) or RFC 3339 time.
+        until: Option<DateTime<Local>>,
+        #[structopt(long)]
+        /// Only list this many of the most recent matching picks.
+        limit: Option<usize>,
+    },
+    /// List the categories in the config, with their models and how many choices they have.
+    List,
+    /// Create a new category with no choices.
+    New {
+        /// The name of the new category.
+        category: String,
+        #[structopt(short, long)]
+        /// The model the category should use: even, gaussian, inventory, lottery, lru, recency,
+        /// rotation, or weighted.
+        model: String,
+    },
+    /// Pick from a category. This is the same as giving the category without a subcommand, but it
+    /// also works for a category that is named like a subcommand, such as list.
+    Pick {
+        /// The category you wish to pick from.
+        category: String,
+    },
+    /// Remove a choice from a category.
+    Remove {
+        /// The category to remove the choice from.
+        category: String,
+        /// The name of the choice to remove.
+        choice: String,
+    },
+    /// Rename one of a category's choices, keeping its place and settings.
+    RenameChoice {
+        /// The category the choice belongs to.
+        category: String,
+        /// The current name of the choice.
+        choice: String,
+        /// The new name for the choice.
+        new_name: String,
+    },
+    /// Show a category's choices and their current chances of being picked, without picking.
+    Show {
+        /// The category to show.
+        category: String,
+    },
+    /// Simulate many picks from a category without changing it, and show how often each choice
+    /// would be picked.
+    Simulate {
+        /// The category to simulate.
+        category: String,
+        /// How many picks to simulate.
+        #[structopt(long, default_value = "1000")]
+        rounds: usize,
+    },
+    /// Restore a category to its state from before the most recent pick.
+    Undo {
+        /// Undo the most recent pick from this category, rather than the most recent pick overall.
+        category: Option<String>,
+    },
 }
 
 fn main() {
     let args = CliArgs::from_args();
-    let config_path = get_config_file_path(&args);
-    let config = rpick::config::read_config(&config_path);
-    match config {
-        Ok(config) => {
-            let mut config = config;
-            let ui = cli::Cli::new(args.verbose);
-
-            let mut engine = rpick::engine::Engine::new(&ui);
-            match engine.pick(&mut config, args.category) {
-                Ok(_) => match rpick::config::write_config(&config_path, config) {
-                    Ok(_) => {}
-                    Err(error) => {
-                        println!("{}", error);
-                        std::process::exit(1);
-                    }
-                },
-                Err(error) => {
-                    println!("{}", error);
-                    std::process::exit(1);
-                }
+    let sources = get_config_sources(&args);
+    let cli = cli::Cli::new(args.verbose);
+    let json = json::Json::new();
+    let ui: &dyn Ui = match args.output {
+        Output::Text => &cli,
+        Output::Json => &json,
+    };
+
+    let result = match &args.command {
+        None => pick(&args, args.category.as_deref(), &sources, &cli, &json),
+        Some(Command::Add {
+            category,
+            choice,
+            weight,
+            tickets,
+        }) => edit_category(&sources, category, |c| {
+            c.add_choice(choice, *weight, *tickets)
+        }),
+        Some(Command::Check) => check(&sources, ui),
+        Some(Command::Convert { destination, to }) => convert(&sources, destination, *to, ui),
+        Some(Command::History {
+            category,
+            choice,
+            since,
+            until,
+            limit,
+        }) => {
+            let filter = rpick::history::HistoryFilter {
+                category: category.clone(),
+                choice: choice.clone(),
+                since: *since,
+                until: *until,
+            };
+            show_history(&sources.primary, &filter, *limit, ui)
+        }
+        Some(Command::List) => list(&sources, ui),
+        Some(Command::New { category, model }) => new_category(&sources, category, model),
+        Some(Command::Pick { category }) => pick(&args, Some(category), &sources, &cli, &json),
+        Some(Command::Remove { category, choice }) => {
+            edit_category(&sources, category, |c| c.remove_choice(choice))
+        }
+        Some(Command::RenameChoice {
+            category,
+            choice,
+            new_name,
+        }) => edit_category(&sources, category, |c| c.rename_choice(choice, new_name)),
+        Some(Command::Show { category }) => show(&sources, category, new_engine(&args, ui)),
+        Some(Command::Simulate { category, rounds }) => {
+            simulate(&sources, category, *rounds, new_engine(&args, ui), ui)
+        }
+        Some(Command::Undo { category }) => undo(&sources, category.as_deref(), ui),
+    };
+
+    match (result, args.output) {
+        (Ok(()), Output::Text) => {}
+        (Ok(()), Output::Json) => {
+            if let Some(table) = json.take_table() {
+                println!("{}", table);
             }
         }
-        Err(error) => {
-            println!("Error reading config file at {}: {}", config_path, error);
+        // The Cli has already said why it could not get an answer.
+        (Err(error), Output::Text)
+            if matches!(error.downcast_ref::<PickError>(), Some(PickError::Aborted)) =>
+        {
+            std::process::exit(1);
+        }
+        (Err(error), Output::Text) => {
+            println!("{}", error);
+            std::process::exit(1);
+        }
+        (Err(error), Output::Json) => {
+            // A table that was displayed before the error, such as the problems that check found,
+            // is still part of the result.
+            let mut document = serde_json::json!({ "error": error.to_string() });
+            if let Some(table) = json.take_table() {
+                document["table"] = table;
+            }
+            println!("{}", document);
             std::process::exit(1);
         }
     }
 }
 
-/// Return the path to the user's config file.
+/// Pick from the category given on the command line, then save the config and the history.
 ///
-/// If the config flag is set in the given CLI args, that path is used. Otherwise, the default
-/// config name (CONFIG_FILE) is appended to the user's home config directory to form the path.
-fn get_config_file_path(args: &CliArgs) -> String {
-    match &args.config {
-        Some(config) => config.clone(),
+/// # Arguments
+///
+/// * `args` - The command line arguments.
+/// * `category` - The category to pick from, or `None` if none was given.
+/// * `sources` - Where to find the config files. The history and undo stack live next to the main
+///   config file.
+/// * `cli` - The Cli to interact with the user through, for text output.
+/// * `json` - The Ui to pick with for JSON output. The result of the pick is printed as JSON.
+fn pick(
+    args: &CliArgs,
+    category: Option<&str>,
+    sources: &ConfigSources,
+    cli: &cli::Cli,
+    json: &json::Json,
+) -> Result<(), Box<dyn error::Error>> {
+    let category = match category {
+        Some(category) => category.to_string(),
+        None => structopt::clap::Error::with_description(
+            "The <category> argument is required unless a subcommand is given.",
+            structopt::clap::ErrorKind::MissingRequiredArgument,
+        )
+        .exit(),
+    };
+    let (mut config_file, mut config) = lock_config(sources)?;
+    // Errors are only reported here, since the engine refuses to pick if it can't. They go to
+    // stderr, where they don't get in the way of the pick's output.
+    if let Some(config_category) = config.get(&category) {
+        for diagnostic in config_category
+            .validate(&category)
+            .iter()
+            .filter(|d| d.severity == rpick::config::Severity::Error)
+        {
+            eprintln!(
+                "{}: {} Run `rpick check` for details.",
+                diagnostic.severity, diagnostic
+            );
+        }
+    }
+    let auto_accept = rpick::ui::AutoAcceptUi::wrap(cli);
+    let ui: &dyn Ui = match (args.output, args.yes) {
+        (Output::Json, _) => json,
+        (Output::Text, true) => &auto_accept,
+        (Output::Text, false) => cli,
+    };
+    let mut engine = new_engine(args, ui);
+    let timestamp = engine.now();
+    let state = config.get(&category).cloned();
+    let choices = engine.pick_many(&mut config, category.clone(), args.count, &args.exclude)?;
+    let undo_entry = state.map(|state| rpick::undo::UndoEntry {
+        timestamp,
+        category: category.clone(),
+        state,
+        picks: engine.history_entries().len(),
+    });
+    // The document is only printed once everything is saved, so that a failure is the only JSON
+    // value on stdout.
+    let document = (args.output == Output::Json).then(|| {
+        serde_json::json!({
+            "category": category,
+            "model": config[&category].model(),
+            "choices": choices,
+            "details": choices
+                .iter()
+                .map(|choice| config[&category].details(choice))
+                .collect::<Vec<_>>(),
+            "table": json.take_table(),
+        })
+    });
+    config_file.write(config)?;
+    rpick::history::append_history(
+        &get_data_file_path(&sources.primary, "history"),
+        engine.history_entries(),
+    )?;
+    if let Some(undo_entry) = undo_entry {
+        let undo_file_path = get_data_file_path(&sources.primary, "undo");
+        let mut undo_stack = rpick::undo::read_undo_stack(&undo_file_path)?;
+        undo_stack.push(undo_entry);
+        rpick::undo::write_undo_stack(&undo_file_path, &undo_stack)?;
+    }
+    if let Some(document) = document {
+        println!("{}", document);
+    }
+    Ok(())
+}
+
+/// Apply the given change to the named category, then save the config.
+///
+/// # Arguments
+///
+/// * `sources` - Where to find the config files.
+/// * `category` - The name of the category to change.
+/// * `edit` - A function that makes the change to the category.
+fn edit_category<F>(
+    sources: &ConfigSources,
+    category: &str,
+    edit: F,
+) -> Result<(), Box<dyn error::Error>>
+where
+    F: FnOnce(&mut rpick::config::ConfigCategory) -> Result<(), rpick::config::ConfigError>,
+{
+    let (mut config_file, mut config) = lock_config(sources)?;
+    let config_category = config
+        .get_mut(category)
+        .ok_or_else(|| rpick::config::ConfigError::CategoryNotFound(category.to_string()))?;
+    edit(config_category)?;
+    config_file.write(config)
+}
+
+/// Add a new, empty category to the config.
+///
+/// # Arguments
+///
+/// * `sources` - Where to find the config files. The new category is added to the main config
+///   file, which is created if it does not exist yet.
+/// * `category` - The name of the new category.
+/// * `model` - The name of the model the new category should use.
+fn new_category(
+    sources: &ConfigSources,
+    category: &str,
+    model: &str,
+) -> Result<(), Box<dyn error::Error>> {
+    let (mut config_file, mut config) = lock_config(&ConfigSources {
+        require_primary: false,
+        ..sources.clone()
+    })?;
+    if config.contains_key(category) {
+        return Err(rpick::config::ConfigError::CategoryExists(category.to_string()).into());
+    }
+    config.insert(
+        category.to_string(),
+        rpick::config::ConfigCategory::new(model)?,
+    );
+    config_file.write(config)
+}
+
+/// Restore a category to its state from before the most recent pick, and remove that pick from
+/// the history.
+///
+/// # Arguments
+///
+/// * `sources` - Where to find the config files. The undo stack and history live next to the main
+///   config file.
+/// * `category` - If given, undo the most recent pick from this category.
+/// * `ui` - The Ui to report what was undone with.
+fn undo(
+    sources: &ConfigSources,
+    category: Option<&str>,
+    ui: &dyn Ui,
+) -> Result<(), Box<dyn error::Error>> {
+    let (mut config_file, mut config) = lock_config(sources)?;
+    let undo_file_path = get_data_file_path(&sources.primary, "undo");
+    let mut undo_stack = rpick::undo::read_undo_stack(&undo_file_path)?;
+    let entry = match undo_stack.pop(category) {
+        Some(entry) => entry,
         None => {
-            let config_dir = dirs_next::config_dir().expect("Unable to find config dir.");
-            let config_file = config_dir.join(CONFIG_FILE);
-            String::from(config_file.to_str().expect("Unable to determine config."))
+            return Err(match category {
+                Some(category) => format!("There is nothing to undo for `{}`.", category).into(),
+                None => "There is nothing to undo.".into(),
+            })
         }
+    };
+
+    let config_category = config
+        .get_mut(&entry.category)
+        .ok_or_else(|| rpick::config::ConfigError::CategoryNotFound(entry.category.to_string()))?;
+    entry.restore(config_category)?;
+    config_file.write(config)?;
+    rpick::undo::write_undo_stack(&undo_file_path, &undo_stack)?;
+
+    let history_file_path = get_data_file_path(&sources.primary, "history");
+    let mut history = rpick::history::read_history(&history_file_path)?;
+    entry.remove_picks(&mut history);
+    rpick::history::write_history(&history_file_path, &history)?;
+
+    ui.info(&format!(
+        "Restored `{}` to its state from before the pick made at {}.",
+        entry.category,
+        entry.timestamp.format("%Y-%m-%d %H:%M")
+    ));
+    Ok(())
+}
+
+/// Display a table of the problems found in the config.
+///
+/// # Arguments
+///
+/// * `sources` - Where to find the config files.
+/// * `ui` - The Ui to display the problems with.
+///
+/// # Returns
+///
+/// Returns an error if any of the problems are errors, so that rpick exits with a failure.
+fn check(sources: &ConfigSources, ui: &dyn Ui) -> Result<(), Box<dyn error::Error>> {
+    let config = read_config(sources)?;
+    let diagnostics = rpick::config::validate(&config);
+    if diagnostics.is_empty() {
+        ui.info("No problems were found.");
+        return Ok(());
+    }
+
+    let cells = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            vec![
+                diagnostic.severity.to_string(),
+                diagnostic.category.clone(),
+                diagnostic.choice.clone().unwrap_or_default(),
+                diagnostic.problem.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    ui.display_table(&rpick::ui::Table {
+        footer: vec![],
+        header: vec![
+            "Severity".into(),
+            "Category".into(),
+            "Choice".into(),
+            "Problem".into(),
+        ],
+        rows: cells
+            .iter()
+            .map(|row| rpick::ui::Row {
+                cells: row.iter().map(|cell| cell.as_str().into()).collect(),
+                chosen: false,
+            })
+            .collect(),
+    });
+
+    match diagnostics
+        .iter()
+        .filter(|d| d.severity == rpick::config::Severity::Error)
+        .count()
+    {
+        0 => Ok(()),
+        1 => Err("Found 1 error in the config.".into()),
+        errors => Err(format!("Found {} errors in the config.", errors).into()),
+    }
+}
+
+/// Write a copy of the main config file in another format.
+///
+/// # Arguments
+///
+/// * `sources` - Where to find the config files.
+/// * `destination` - The path to write the copy to. It must not exist yet.
+/// * `to` - The format to write the copy in. If `None`, it is chosen by the destination's
+///   extension.
+/// * `ui` - The Ui to report the conversion with.
+fn convert(
+    sources: &ConfigSources,
+    destination: &str,
+    to: Option<Format>,
+    ui: &dyn Ui,
+) -> Result<(), Box<dyn error::Error>> {
+    let format = sources
+        .format
+        .unwrap_or_else(|| Format::from_path(&sources.primary));
+    let to = to.unwrap_or_else(|| Format::from_path(destination));
+    rpick::config::convert_config(&sources.primary, format, destination, to)?;
+
+    ui.info(&format!(
+        "Converted the {} config at {} to {} at {}.",
+        format, sources.primary, to, destination
+    ));
+    Ok(())
+}
+
+/// Display a table of the categories in the config, with their models and number of choices.
+fn list(sources: &ConfigSources, ui: &dyn Ui) -> Result<(), Box<dyn error::Error>> {
+    let config = read_config(sources)?;
+
+    ui.display_table(&rpick::ui::Table {
+        footer: vec![],
+        header: vec!["Category".into(), "Model".into(), "Choices".into()],
+        rows: config
+            .iter()
+            .map(|(name, category)| rpick::ui::Row {
+                cells: vec![
+                    name.as_str().into(),
+                    category.model().into(),
+                    (category.choice_names().len() as u64).into(),
+                ],
+                chosen: false,
+            })
+            .collect(),
+    });
+    Ok(())
+}
+
+/// Display the chance table for the given category, without picking from it.
+///
+/// # Arguments
+///
+/// * `sources` - Where to find the config files.
+/// * `category` - The category to display.
+/// * `engine` - The Engine to display the table with.
+fn show(
+    sources: &ConfigSources,
+    category: &str,
+    engine: rpick::engine::Engine<'_, dyn Ui + '_>,
+) -> Result<(), Box<dyn error::Error>> {
+    let config = read_config(sources)?;
+    engine.display_category(&config, category.to_string())?;
+    Ok(())
+}
+
+/// Simulate picks from the given category, and display how often each choice was picked.
+///
+/// # Arguments
+///
+/// * `sources` - Where to find the config files.
+/// * `category` - The category to simulate.
+/// * `rounds` - How many picks to simulate.
+/// * `engine` - The Engine to simulate with.
+/// * `ui` - The Ui to display the results with.
+fn simulate(
+    sources: &ConfigSources,
+    category: &str,
+    rounds: usize,
+    mut engine: rpick::engine::Engine<'_, dyn Ui + '_>,
+    ui: &dyn Ui,
+) -> Result<(), Box<dyn error::Error>> {
+    let config = read_config(sources)?;
+    let simulation = engine.simulate(&config, category.to_string(), rounds)?;
+
+    let mean_gaps: Vec<String> = simulation
+        .choices
+        .iter()
+        .map(|choice| match choice.mean_gap {
+            Some(mean_gap) => format!("{:.2}", mean_gap),
+            None => String::from("-"),
+        })
+        .collect();
+    ui.display_table(&rpick::ui::Table {
+        footer: vec![],
+        header: vec![
+            "Name".into(),
+            "Picks".into(),
+            "Frequency".into(),
+            "Mean gap".into(),
+            "Longest drought".into(),
+        ],
+        rows: simulation
+            .choices
+            .iter()
+            .zip(mean_gaps.iter())
+            .map(|(choice, mean_gap)| rpick::ui::Row {
+                cells: vec![
+                    choice.name.as_str().into(),
+                    (choice.picks as u64).into(),
+                    (choice.frequency * 100.).into(),
+                    mean_gap.as_str().into(),
+                    (choice.longest_drought as u64).into(),
+                ],
+                chosen: false,
+            })
+            .collect(),
+    });
+    if simulation.rounds < rounds {
+        ui.info(&format!(
+            "Every choice in `{}` ran out of tickets after {} rounds.",
+            category, simulation.rounds
+        ));
+    }
+    Ok(())
+}
+
+/// Display the history entries that match the given filter.
+///
+/// # Arguments
+///
+/// * `config_path` - The path to the config file. The history lives next to it.
+/// * `filter` - Describes which entries should be displayed.
+/// * `limit` - If given, only display this many of the most recent matching entries.
+/// * `ui` - The Ui to display the entries with.
+fn show_history(
+    config_path: &str,
+    filter: &rpick::history::HistoryFilter,
+    limit: Option<usize>,
+    ui: &dyn Ui,
+) -> Result<(), Box<dyn error::Error>> {
+    let entries = rpick::history::read_history(&get_data_file_path(config_path, "history"))?;
+    let mut entries = entries
+        .iter()
+        .filter(|entry| filter.matches(entry))
+        .collect::<Vec<_>>();
+    // Picks made with --at can be recorded out of order. The sort is stable, so picks made at the
+    // same time keep the order they were made in.
+    entries.sort_by_key(|entry| entry.timestamp);
+    if let Some(limit) = limit {
+        entries.drain(..entries.len().saturating_sub(limit));
+    }
+
+    if entries.is_empty() {
+        ui.info("No matching picks have been recorded.");
+        return Ok(());
+    }
+
+    let cells = entries
+        .iter()
+        .map(|entry| {
+            // Note who vetoed each choice, for categories that are picked by a group.
+            let describe = |choice: &String| match entry.vetoes.get(choice) {
+                Some(vetoed_by) => format!("{} (vetoed by {})", choice, vetoed_by.join(", ")),
+                None => choice.clone(),
+            };
+            vec![
+                entry.timestamp.format("%Y-%m-%d %H:%M").to_string(),
+                entry.category.clone(),
+                describe(&entry.choice),
+                entry
+                    .rejected
+                    .iter()
+                    .map(describe)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ]
+        })
+        .collect::<Vec<_>>();
+    ui.display_table(&rpick::ui::Table {
+        footer: vec![],
+        header: vec![
+            "When".into(),
+            "Category".into(),
+            "Choice".into(),
+            "Rejected".into(),
+        ],
+        rows: cells
+            .iter()
+            .map(|row| rpick::ui::Row {
+                cells: row.iter().map(|cell| cell.as_str().into()).collect(),
+                chosen: false,
+            })
+            .collect(),
+    });
+    Ok(())
+}
+
+/// Read the config from the given sources.
+fn read_config(
+    sources: &ConfigSources,
+) -> Result<std::collections::BTreeMap<String, rpick::config::ConfigCategory>, String> {
+    rpick::config::read_layered(sources).map_err(|error| error.to_string())
+}
+
+/// Lock the config files so that other rpick processes leave them alone, and read them.
+///
+/// The config files stay locked until the returned [`rpick::config::LayeredConfig`] is dropped,
+/// and saving through it fails if something else changed one of the files in the meantime.
+fn lock_config(
+    sources: &ConfigSources,
+) -> Result<
+    (
+        rpick::config::LayeredConfig,
+        std::collections::BTreeMap<String, rpick::config::ConfigCategory>,
+    ),
+    String,
+> {
+    rpick::config::LayeredConfig::lock(sources).map_err(|error| error.to_string())
+}
+
+/// Parse a date such as 2021-03-01 (meaning midnight, local time), a local time such as
+/// 2021-03-01T12:30, or an RFC 3339 timestamp.
+fn parse_datetime(value: &str) -> Result<DateTime<Local>, String> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.with_timezone(&Local));
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .and_then(|datetime| Local.from_local_datetime(&datetime).earliest())
+        .ok_or_else(|| format!("Unable to parse `{}` as a date or time.", value))
+}
+
+/// Return the tag filter given by the tag and exclude-tag flags in the given CLI args.
+fn tag_filter(args: &CliArgs) -> rpick::tags::TagFilter {
+    rpick::tags::TagFilter {
+        include: args.tag.clone(),
+        exclude: args.exclude_tag.clone(),
+    }
+}
+
+/// Return an Engine that picks as the given CLI args say: with their seed, tag filter,
+/// participants, and time.
+///
+/// # Arguments
+///
+/// * `args` - The command line arguments.
+/// * `ui` - The Ui for the Engine to interact with the user through.
+fn new_engine<'ui>(args: &CliArgs, ui: &'ui dyn Ui) -> rpick::engine::Engine<'ui, dyn Ui + 'ui> {
+    let mut engine = rpick::engine::Engine::new(ui);
+    if let Some(seed) = args.seed {
+        engine.set_seed(seed);
+    }
+    engine.set_tag_filter(tag_filter(args));
+    engine.set_participants(args.with.clone());
+    if let Some(at) = args.at {
+        engine.set_now(at);
+    }
+    engine
+}
+
+/// Return the path to one of the data files that rpick keeps next to the config file.
+///
+/// For example, the history for `rpick.yml` is kept in `rpick.history.yml`.
+///
+/// # Arguments
+///
+/// * `config_path` - The path to the config file.
+/// * `name` - The name of the data file, such as `history` or `undo`.
+fn get_data_file_path(config_path: &str, name: &str) -> String {
+    let data_file = Path::new(config_path).with_extension(format!("{}.yml", name));
+    String::from(data_file.to_str().expect("Unable to determine data file."))
+}
+
+/// Return where to find the user's config files.
+///
+/// If the config flag is set in the given CLI args, only that file and the files it includes are
+/// read, and the file must exist. Otherwise, the main config file (see [`get_config_file_path`])
+/// is followed by the files in the ```rpick``` directory of the user's home config directory, and
+/// then by the project config for the current directory. The main config file does not have to
+/// exist in that case.
+fn get_config_sources(args: &CliArgs) -> ConfigSources {
+    let mut sources = ConfigSources {
+        format: args.format,
+        ..ConfigSources::new(&get_config_file_path(args))
+    };
+    if args.config.is_none() {
+        let config_dir = dirs_next::config_dir().expect("Unable to find config dir.");
+        sources.require_primary = false;
+        sources.directory = Some(config_dir.join(CONFIG_FILE));
+        sources.project = std::env::current_dir()
+            .ok()
+            .and_then(|dir| rpick::config::find_project_config(&dir));
+    }
+    sources
+}
+
+/// Return the path to the user's main config file.
+///
+/// If the config flag is set in the given CLI args, that path is used. Otherwise, the default
+/// config name (CONFIG_FILE) is appended to the user's home config directory to form the path,
+/// with the extension for the format flag if it is set. Without the format flag, the first of
+/// ```rpick.yml```, ```rpick.toml```, and ```rpick.json``` that exists is used, falling back to
+/// ```rpick.yml```.
+fn get_config_file_path(args: &CliArgs) -> String {
+    if let Some(config) = &args.config {
+        return config.clone();
+    }
+
+    let config_dir = dirs_next::config_dir().expect("Unable to find config dir.");
+    let path_for = |format: Format| {
+        let config_file = config_dir.join(format!("{}.{}", CONFIG_FILE, format.extension()));
+        String::from(config_file.to_str().expect("Unable to determine config."))
+    };
+    match args.format {
+        Some(format) => path_for(format),
+        None => [Format::Yaml, Format::Toml, Format::Json]
+            .iter()
+            .map(|format| path_for(*format))
+            .find(|path| Path::new(path).exists())
+            .unwrap_or_else(|| path_for(Format::Yaml)),
     }
 }
@@ -0,0 +1,484 @@
+/* Copyright © 2021 Randy Barlow
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, version 3 of the License.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
+//! # State
+//!
+//! Picking changes some categories: the lru and gaussian models reorder their choices, the
+//! inventory and lottery models change their tickets, the inventory model records when its choices
+//! were restocked, the recency model records when its choices were picked, and the rotation model
+//! counts how many times its choices were picked. Config files with the ```separate_state```
+//! setting keep those changes out of the config file, in a state file instead. This module defines
+//! that state, and how it is taken from and given back to a category.
+use std::collections::BTreeMap;
+use std::error;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, ConfigCategory};
+
+/// Return the state stored in the given file, keyed by category name.
+///
+/// A file that does not exist yet is treated as empty.
+///
+/// # Arguments
+///
+/// * `state_file_path` - A filesystem path to the YAML file holding the state.
+pub fn read_state(
+    state_file_path: &str,
+) -> Result<BTreeMap<String, CategoryState>, Box<dyn error::Error>> {
+    let f = match File::open(state_file_path) {
+        Ok(f) => f,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(error) => return Err(error.into()),
+    };
+    let reader = BufReader::new(f);
+
+    let state: BTreeMap<String, CategoryState> = serde_yaml::from_reader(reader)?;
+    Ok(state)
+}
+
+/// Save the given state to the given file.
+///
+/// # Arguments
+///
+/// * `state_file_path` - A filesystem path that the state should be written to.
+/// * `state` - The state to save, keyed by category name.
+pub fn write_state(
+    state_file_path: &str,
+    state: &BTreeMap<String, CategoryState>,
+) -> Result<(), Box<dyn error::Error>> {
+    let yaml = serde_yaml::to_string(state)?;
+    config::write_atomically(state_file_path, &yaml)?;
+    Ok(())
+}
+
+/// The parts of a category that change when it is picked from, keyed by choice name.
+///
+/// # Attributes
+///
+/// * `order` - The order of the choices, for the gaussian and lru models.
+/// * `tickets` - How many tickets each choice has, for the inventory and lottery models.
+/// * `restocked` - When each choice was last restocked, or `None` if it never has been, for the
+///   inventory model. This is left out of the state file if no choice has been restocked.
+/// * `last_picked` - When each choice was last picked, or `None` if it never has been, for the
+///   recency model.
+/// * `assignments` - How many times each choice has been picked, for the rotation model.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CategoryState {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tickets: BTreeMap<String, u64>,
+    #[serde(default, skip_serializing_if = "never_restocked")]
+    pub restocked: BTreeMap<String, Option<DateTime<Local>>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_picked: BTreeMap<String, Option<DateTime<Local>>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub assignments: BTreeMap<String, u64>,
+}
+
+impl CategoryState {
+    /// Return the state of the given category.
+    ///
+    /// # Arguments
+    ///
+    /// * `category` - The category to take the state from.
+    pub fn of(category: &ConfigCategory) -> CategoryState {
+        match category {
+            ConfigCategory::Gaussian { choices, .. } | ConfigCategory::Lru { choices, .. } => {
+                CategoryState {
+                    order: choices.iter().map(|c| c.name.clone()).collect(),
+                    ..CategoryState::default()
+                }
+            }
+            ConfigCategory::Inventory { choices, .. } => CategoryState {
+                tickets: choices
+                    .iter()
+                    .map(|c| (c.name.clone(), c.tickets))
+                    .collect(),
+                restocked: choices
+                    .iter()
+                    .map(|c| (c.name.clone(), c.restocked))
+                    .collect(),
+                ..CategoryState::default()
+            },
+            ConfigCategory::Lottery { choices, .. } => CategoryState {
+                tickets: choices
+                    .iter()
+                    .map(|c| (c.name.clone(), c.tickets))
+                    .collect(),
+                ..CategoryState::default()
+            },
+            ConfigCategory::Recency { choices, .. } => CategoryState {
+                last_picked: choices
+                    .iter()
+                    .map(|c| (c.name.clone(), c.last_picked))
+                    .collect(),
+                ..CategoryState::default()
+            },
+            ConfigCategory::Rotation { choices, .. } => CategoryState {
+                assignments: choices
+                    .iter()
+                    .map(|c| (c.name.clone(), c.assignments))
+                    .collect(),
+                ..CategoryState::default()
+            },
+            ConfigCategory::Even { .. } | ConfigCategory::Weighted { .. } => {
+                CategoryState::default()
+            }
+        }
+    }
+
+    /// Return `true` if there is no state to store.
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+            && self.tickets.is_empty()
+            && never_restocked(&self.restocked)
+            && self.last_picked.is_empty()
+            && self.assignments.is_empty()
+    }
+
+    /// Give this state to the given category, reconciling it with the category's choices.
+    ///
+    /// Choices are matched up by name. Choices in the state that the category no longer has are
+    /// ignored, and choices that the state does not mention keep the order, tickets, restocked
+    /// time, last picked time, or assignments the category gave them. For the gaussian and lru
+    /// models, those choices have never been picked, so they go to the top of the list.
+    ///
+    /// # Arguments
+    ///
+    /// * `category` - The category to give the state to.
+    pub fn apply(&self, category: &mut ConfigCategory) {
+        match category {
+            ConfigCategory::Gaussian { choices, .. } | ConfigCategory::Lru { choices, .. } => {
+                if self.order.is_empty() {
+                    return;
+                }
+                let mut remaining = choices.drain(..).map(Some).collect::<Vec<_>>();
+                let mut ordered = vec![];
+                for name in &self.order {
+                    if let Some(choice) = remaining
+                        .iter_mut()
+                        .find(|choice| choice.as_ref().map(|c| &c.name) == Some(name))
+                    {
+                        ordered.extend(choice.take());
+                    }
+                }
+                *choices = remaining.into_iter().flatten().chain(ordered).collect();
+            }
+            ConfigCategory::Inventory { choices, .. } => {
+                for choice in choices {
+                    if let Some(tickets) = self.tickets.get(&choice.name) {
+                        choice.tickets = *tickets;
+                    }
+                    if let Some(restocked) = self.restocked.get(&choice.name) {
+                        choice.restocked = *restocked;
+                    }
+                }
+            }
+            ConfigCategory::Lottery { choices, .. } => {
+                for choice in choices {
+                    if let Some(tickets) = self.tickets.get(&choice.name) {
+                        choice.tickets = *tickets;
+                    }
+                }
+            }
+            ConfigCategory::Recency { choices, .. } => {
+                for choice in choices {
+                    if let Some(last_picked) = self.last_picked.get(&choice.name) {
+                        choice.last_picked = *last_picked;
+                    }
+                }
+            }
+            ConfigCategory::Rotation { choices, .. } => {
+                for choice in choices {
+                    if let Some(assignments) = self.assignments.get(&choice.name) {
+                        choice.assignments = *assignments;
+                    }
+                }
+            }
+            ConfigCategory::Even { .. } | ConfigCategory::Weighted { .. } => {}
+        }
+    }
+}
+
+/// Return `true` if none of the choices in the given restocked times has been restocked.
+fn never_restocked(restocked: &BTreeMap<String, Option<DateTime<Local>>>) -> bool {
+    restocked.values().all(Option::is_none)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    use crate::config::{Choice, InventoryChoice, LotteryChoice, RecencyChoice, RotationChoice};
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn choices(names: &[&str]) -> Vec<Choice> {
+        names.iter().map(|name| Choice::from(*name)).collect()
+    }
+
+    #[test]
+    fn test_apply_order() {
+        let state = CategoryState {
+            order: strings(&["c", "gone", "a", "b"]),
+            ..CategoryState::default()
+        };
+        let mut category = ConfigCategory::Lru {
+            choices: choices(&["a", "b", "c", "new"]),
+            group: None,
+        };
+
+        state.apply(&mut category);
+
+        assert_eq!(
+            category,
+            ConfigCategory::Lru {
+                choices: choices(&["new", "c", "a", "b"]),
+                group: None,
+            }
+        );
+        assert_eq!(
+            CategoryState::of(&category).order,
+            strings(&["new", "c", "a", "b"])
+        );
+    }
+
+    #[test]
+    fn test_apply_tickets() {
+        let mut tickets = BTreeMap::new();
+        tickets.insert("a".to_string(), 0);
+        tickets.insert("gone".to_string(), 7);
+        let state = CategoryState {
+            tickets,
+            ..CategoryState::default()
+        };
+        let mut inventory = ConfigCategory::Inventory {
+            choices: vec![
+                InventoryChoice {
+                    name: "a".to_string(),
+                    tickets: 3,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
+                },
+                InventoryChoice {
+                    name: "new".to_string(),
+                    tickets: 2,
+                    restocked: None,
+                    tags: vec![],
+                    details: Default::default(),
+                    restock: None,
+                    availability: Default::default(),
+                },
+            ],
+            group: None,
+            restock: None,
+        };
+        let mut lottery = ConfigCategory::Lottery {
+            choices: vec![LotteryChoice {
+                name: "a".to_string(),
+                tickets: 1,
+                weight: 5,
+                tags: vec![],
+                details: Default::default(),
+                preferences: Default::default(),
+                availability: Default::default(),
+            }],
+            group: None,
+        };
+
+        state.apply(&mut inventory);
+        state.apply(&mut lottery);
+
+        let state = CategoryState::of(&inventory);
+        assert_eq!(state.tickets.get("a"), Some(&0));
+        assert_eq!(state.tickets.get("new"), Some(&2));
+        assert_eq!(state.tickets.len(), 2);
+        assert_eq!(
+            lottery,
+            ConfigCategory::Lottery {
+                choices: vec![LotteryChoice {
+                    name: "a".to_string(),
+                    tickets: 0,
+                    weight: 5,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
+                }],
+                group: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_apply_restocked() {
+        let restocked = Local.with_ymd_and_hms(2021, 6, 1, 12, 0, 0).unwrap();
+        let choice = |name: &str, restocked| InventoryChoice {
+            name: name.to_string(),
+            tickets: 1,
+            restocked,
+            tags: vec![],
+            details: Default::default(),
+            restock: None,
+            availability: Default::default(),
+        };
+        let mut category = ConfigCategory::Inventory {
+            choices: vec![choice("a", Some(restocked)), choice("b", None)],
+            group: None,
+            restock: None,
+        };
+        let state = CategoryState::of(&category);
+        if let ConfigCategory::Inventory { choices, .. } = &mut category {
+            choices[0].restocked = None;
+            choices[1].restocked = Some(restocked);
+        }
+
+        state.apply(&mut category);
+
+        assert_eq!(
+            category,
+            ConfigCategory::Inventory {
+                choices: vec![choice("a", Some(restocked)), choice("b", None)],
+                group: None,
+                restock: None,
+            }
+        );
+        let never = CategoryState {
+            restocked: vec![("b".to_string(), None)].into_iter().collect(),
+            ..CategoryState::default()
+        };
+        assert!(never.is_empty());
+        assert_eq!(serde_yaml::to_string(&never).unwrap(), "---\n{}\n");
+    }
+
+    #[test]
+    fn test_apply_last_picked() {
+        let picked = Local.with_ymd_and_hms(2021, 6, 1, 12, 0, 0).unwrap();
+        let choice = |name: &str, last_picked| RecencyChoice {
+            name: name.to_string(),
+            last_picked,
+            cooldown: None,
+            tags: vec![],
+            details: Default::default(),
+            availability: Default::default(),
+        };
+        let mut last_picked = BTreeMap::new();
+        last_picked.insert("a".to_string(), Some(picked));
+        last_picked.insert("b".to_string(), None);
+        let state = CategoryState {
+            last_picked,
+            ..CategoryState::default()
+        };
+        let mut category = ConfigCategory::Recency {
+            choices: vec![
+                choice("a", None),
+                choice("b", Some(picked)),
+                choice("new", Some(picked)),
+            ],
+            group: None,
+        };
+
+        state.apply(&mut category);
+
+        assert_eq!(
+            category,
+            ConfigCategory::Recency {
+                choices: vec![
+                    choice("a", Some(picked)),
+                    choice("b", None),
+                    choice("new", Some(picked))
+                ],
+                group: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_apply_assignments() {
+        let choice = |name: &str, assignments| RotationChoice {
+            name: name.to_string(),
+            assignments,
+            capacity: 1,
+            tags: vec![],
+            details: Default::default(),
+            availability: Default::default(),
+        };
+        let mut assignments = BTreeMap::new();
+        assignments.insert("a".to_string(), 3);
+        assignments.insert("gone".to_string(), 7);
+        let state = CategoryState {
+            assignments,
+            ..CategoryState::default()
+        };
+        let mut category = ConfigCategory::Rotation {
+            choices: vec![choice("a", 0), choice("new", 1)],
+            group: None,
+        };
+
+        state.apply(&mut category);
+
+        assert_eq!(
+            category,
+            ConfigCategory::Rotation {
+                choices: vec![choice("a", 3), choice("new", 1)],
+                group: None,
+            }
+        );
+        assert_eq!(CategoryState::of(&category).assignments.len(), 2);
+    }
+
+    #[test]
+    fn test_stateless_models() {
+        let category = ConfigCategory::Even {
+            choices: choices(&["a"]),
+            group: None,
+        };
+
+        assert!(CategoryState::of(&category).is_empty());
+    }
+
+    #[test]
+    fn test_read_and_write_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rpick.state.yml");
+        let path = path.to_str().unwrap();
+        let mut state = BTreeMap::new();
+        state.insert(
+            "code".to_string(),
+            CategoryState {
+                order: strings(&["b", "a"]),
+                ..CategoryState::default()
+            },
+        );
+
+        assert!(read_state(path).unwrap().is_empty());
+        write_state(path, &state).unwrap();
+
+        assert_eq!(read_state(path).unwrap(), state);
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            "---\ncode:\n  order:\n    - b\n    - a\n"
+        );
+    }
+}
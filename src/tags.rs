@@ -0,0 +1,318 @@
+/* Copyright © 2021 Randy Barlow
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, version 3 of the License.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
+//! # Tags
+//!
+//! Choices can be given tags, such as ```lunch``` or ```cheap```, so that a pick can be limited to
+//! the choices with the right tags. This module defines the boolean expressions that tags are
+//! matched with, and the [`TagFilter`] that the [`crate::engine::Engine`] uses to decide which
+//! choices it may pick.
+use std::iter::Peekable;
+use std::str::FromStr;
+use std::vec::IntoIter;
+
+use thiserror::Error;
+
+/// A boolean expression over a choice's tags, such as ```lunch and (cheap or not expensive)```.
+///
+/// Expressions are parsed from strings. The operators are ```and``` (or ```&```), ```or``` (or
+/// ```|```), and ```not``` (or ```!```), from highest precedence to lowest: ```not``` binds the
+/// tightest, and ```and``` binds tighter than ```or```. Parentheses can be used to group. Any other
+/// word is the name of a tag.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TagExpression {
+    /// Matches choices that have the named tag.
+    Tag(String),
+    /// Matches choices that do not match the inner expression.
+    Not(Box<TagExpression>),
+    /// Matches choices that match both expressions.
+    And(Box<TagExpression>, Box<TagExpression>),
+    /// Matches choices that match either expression.
+    Or(Box<TagExpression>, Box<TagExpression>),
+}
+
+impl TagExpression {
+    /// Return `true` if a choice with the given tags matches this expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - The choice's tags.
+    pub fn matches(&self, tags: &[String]) -> bool {
+        match self {
+            TagExpression::Tag(tag) => tags.contains(tag),
+            TagExpression::Not(inner) => !inner.matches(tags),
+            TagExpression::And(left, right) => left.matches(tags) && right.matches(tags),
+            TagExpression::Or(left, right) => left.matches(tags) || right.matches(tags),
+        }
+    }
+}
+
+impl FromStr for TagExpression {
+    type Err = TagError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            expression,
+            tokens: tokenize(expression).into_iter().peekable(),
+        };
+        let parsed = parser.or()?;
+        match parser.tokens.next() {
+            None => Ok(parsed),
+            Some(token) => Err(parser.unexpected(token)),
+        }
+    }
+}
+
+/// Decides which choices may be picked, by their tags.
+///
+/// # Attributes
+///
+/// * `include` - A choice must match every one of these expressions.
+/// * `exclude` - A choice must not match any of these expressions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TagFilter {
+    pub include: Vec<TagExpression>,
+    pub exclude: Vec<TagExpression>,
+}
+
+impl TagFilter {
+    /// Return `true` if this filter lets every choice through.
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Return `true` if a choice with the given tags may be picked.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - The choice's tags.
+    pub fn matches(&self, tags: &[String]) -> bool {
+        self.include
+            .iter()
+            .all(|expression| expression.matches(tags))
+            && !self
+                .exclude
+                .iter()
+                .any(|expression| expression.matches(tags))
+    }
+}
+
+/// Define the errors that can be returned when parsing a [`TagExpression`].
+#[derive(Debug, Error, PartialEq)]
+pub enum TagError {
+    #[error("The tag expression `{0}` ended unexpectedly.")]
+    UnexpectedEnd(String),
+    #[error("Unexpected `{token}` in the tag expression `{expression}`.")]
+    UnexpectedToken { expression: String, token: String },
+}
+
+/// The pieces that a tag expression is made of.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Tag(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Token {
+    /// Return the token as it would be written in an expression.
+    fn text(&self) -> &str {
+        match self {
+            Token::Tag(tag) => tag,
+            Token::And => "and",
+            Token::Or => "or",
+            Token::Not => "not",
+            Token::Open => "(",
+            Token::Close => ")",
+        }
+    }
+}
+
+/// Split the given expression into tokens. Whitespace separates words, and the symbols
+/// ```()&|!``` are tokens of their own.
+fn tokenize(expression: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut word = String::new();
+    let end_word = |word: &mut String, tokens: &mut Vec<Token>| {
+        if !word.is_empty() {
+            tokens.push(match word.as_str() {
+                "and" => Token::And,
+                "or" => Token::Or,
+                "not" => Token::Not,
+                _ => Token::Tag(word.clone()),
+            });
+            word.clear();
+        }
+    };
+    for c in expression.chars() {
+        let symbol = match c {
+            '(' => Some(Token::Open),
+            ')' => Some(Token::Close),
+            '&' => Some(Token::And),
+            '|' => Some(Token::Or),
+            '!' => Some(Token::Not),
+            _ => None,
+        };
+        if symbol.is_some() || c.is_whitespace() {
+            end_word(&mut word, &mut tokens);
+            tokens.extend(symbol);
+        } else {
+            word.push(c);
+        }
+    }
+    end_word(&mut word, &mut tokens);
+    tokens
+}
+
+/// A recursive descent parser for tag expressions.
+struct Parser<'a> {
+    /// The expression being parsed, which is used in errors.
+    expression: &'a str,
+    /// The tokens that have not been parsed yet.
+    tokens: Peekable<IntoIter<Token>>,
+}
+
+impl Parser<'_> {
+    /// Parse one or more ```and``` expressions joined by ```or```.
+    fn or(&mut self) -> Result<TagExpression, TagError> {
+        let mut expression = self.and()?;
+        while self.tokens.next_if_eq(&Token::Or).is_some() {
+            expression = TagExpression::Or(Box::new(expression), Box::new(self.and()?));
+        }
+        Ok(expression)
+    }
+
+    /// Parse one or more ```not``` expressions joined by ```and```.
+    fn and(&mut self) -> Result<TagExpression, TagError> {
+        let mut expression = self.not()?;
+        while self.tokens.next_if_eq(&Token::And).is_some() {
+            expression = TagExpression::And(Box::new(expression), Box::new(self.not()?));
+        }
+        Ok(expression)
+    }
+
+    /// Parse a tag or a parenthesized expression, with any number of ```not```'s in front of it.
+    fn not(&mut self) -> Result<TagExpression, TagError> {
+        match self.tokens.next() {
+            Some(Token::Not) => Ok(TagExpression::Not(Box::new(self.not()?))),
+            Some(Token::Tag(tag)) => Ok(TagExpression::Tag(tag)),
+            Some(Token::Open) => {
+                let expression = self.or()?;
+                match self.tokens.next() {
+                    Some(Token::Close) => Ok(expression),
+                    Some(token) => Err(self.unexpected(token)),
+                    None => Err(TagError::UnexpectedEnd(self.expression.to_string())),
+                }
+            }
+            Some(token) => Err(self.unexpected(token)),
+            None => Err(TagError::UnexpectedEnd(self.expression.to_string())),
+        }
+    }
+
+    /// Return the error for finding the given token where it doesn't belong.
+    fn unexpected(&self, token: Token) -> TagError {
+        TagError::UnexpectedToken {
+            expression: self.expression.to_string(),
+            token: token.text().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    fn tag(name: &str) -> Box<TagExpression> {
+        Box::new(TagExpression::Tag(name.to_string()))
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let expression: TagExpression = "!a & b | c and not (d or e)".parse().unwrap();
+
+        assert_eq!(
+            expression,
+            TagExpression::Or(
+                Box::new(TagExpression::And(
+                    Box::new(TagExpression::Not(tag("a"))),
+                    tag("b")
+                )),
+                Box::new(TagExpression::And(
+                    tag("c"),
+                    Box::new(TagExpression::Not(Box::new(TagExpression::Or(
+                        tag("d"),
+                        tag("e")
+                    ))))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "".parse::<TagExpression>(),
+            Err(TagError::UnexpectedEnd("".to_string()))
+        );
+        assert_eq!(
+            "(lunch or dinner".parse::<TagExpression>(),
+            Err(TagError::UnexpectedEnd("(lunch or dinner".to_string()))
+        );
+        assert_eq!(
+            "lunch dinner".parse::<TagExpression>(),
+            Err(TagError::UnexpectedToken {
+                expression: "lunch dinner".to_string(),
+                token: "dinner".to_string()
+            })
+        );
+        assert_eq!(
+            "lunch and or dinner"
+                .parse::<TagExpression>()
+                .unwrap_err()
+                .to_string(),
+            "Unexpected `or` in the tag expression `lunch and or dinner`."
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        let expression: TagExpression = "lunch and (cheap or not expensive)".parse().unwrap();
+
+        assert!(expression.matches(&tags(&["lunch", "cheap", "expensive"])));
+        assert!(expression.matches(&tags(&["lunch"])));
+        assert!(!expression.matches(&tags(&["lunch", "expensive"])));
+        assert!(!expression.matches(&tags(&["dinner", "cheap"])));
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = TagFilter {
+            include: vec!["lunch".parse().unwrap(), "cheap | quick".parse().unwrap()],
+            exclude: vec!["meat".parse().unwrap()],
+        };
+
+        assert!(TagFilter::default().is_empty());
+        assert!(TagFilter::default().matches(&[]));
+        assert!(!filter.is_empty());
+        assert!(filter.matches(&tags(&["lunch", "quick"])));
+        assert!(!filter.matches(&tags(&["lunch", "quick", "meat"])));
+        assert!(!filter.matches(&tags(&["lunch"])));
+        assert!(!filter.matches(&tags(&["cheap"])));
+    }
+}
@@ -16,12 +16,18 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
 
 #[cfg(test)]
 use mockall::automock;
+use serde::Serialize;
+
+use crate::config::ChoiceDetails;
+use crate::group::Vote;
 
 /// An individual cell within rpick's chance tables.
 ///
-/// Each of the variants expresses its contained type, and should be fairly obvious.
+/// Each of the variants expresses its contained type, and should be fairly obvious. Cells serialize
+/// as their contained value, without naming the variant.
 #[non_exhaustive]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(untagged)]
 pub enum Cell<'a> {
     Boolean(bool),
     Text(&'a str),
@@ -30,7 +36,7 @@ pub enum Cell<'a> {
     Unsigned(u64),
 }
 
-impl<'a> From<f64> for Cell<'_> {
+impl From<f64> for Cell<'_> {
     fn from(f: f64) -> Self {
         Self::Float(f)
     }
@@ -42,13 +48,13 @@ impl<'a> From<&'a str> for Cell<'a> {
     }
 }
 
-impl<'a> From<u64> for Cell<'_> {
+impl From<u64> for Cell<'_> {
     fn from(u: u64) -> Self {
         Self::Unsigned(u)
     }
 }
 
-impl<'a> From<&Cell<'_>> for String {
+impl From<&Cell<'_>> for String {
     fn from(c: &Cell) -> String {
         match c {
             Cell::Boolean(value) => value.to_string(),
@@ -61,7 +67,7 @@ impl<'a> From<&Cell<'_>> for String {
 }
 
 /// Represents a row in the [`Table`] struct.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
 pub struct Row<'a> {
     /// The row's individual cells.
     pub cells: Vec<Cell<'a>>,
@@ -70,7 +76,7 @@ pub struct Row<'a> {
 }
 
 /// rpick uses this to send a chance table to the user.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
 pub struct Table<'a> {
     /// The Table's footer.
     pub footer: Vec<Cell<'a>>,
@@ -102,4 +108,163 @@ pub trait Ui {
     ///
     /// Return `true` if the user accepts the choice.
     fn prompt_choice(&self, choice: &str) -> bool;
+
+    /// Prompt the user if they wish to accept the given choice, showing them its details.
+    ///
+    /// The engine calls this rather than [`Ui::prompt_choice`] for choices that have details. By
+    /// default, the details are ignored and [`Ui::prompt_choice`] is called.
+    ///
+    /// Return `true` if the user accepts the choice.
+    fn prompt_choice_with_details(&self, choice: &str, _details: &ChoiceDetails) -> bool {
+        self.prompt_choice(choice)
+    }
+
+    /// Return `true` if the Ui can no longer answer prompts, such as when its input has been
+    /// closed.
+    ///
+    /// The engine calls this after a choice is declined, and gives up on the pick with
+    /// [`crate::engine::PickError::Aborted`] rather than offering more choices. By default, this
+    /// returns `false`.
+    fn is_aborted(&self) -> bool {
+        false
+    }
+
+    /// Ask the named participant of a group how they vote on the given choice. See
+    /// [`crate::group`].
+    ///
+    /// The engine calls this rather than [`Ui::prompt_choice`] for categories that are picked by a
+    /// group, once for each participant. By default, [`Ui::prompt_choice`] is called, and a
+    /// rejection counts as a veto.
+    fn prompt_vote(&self, _participant: &str, choice: &str) -> Vote {
+        if self.prompt_choice(choice) {
+            Vote::Accept
+        } else {
+            Vote::Veto
+        }
+    }
+}
+
+/// A [`Ui`] that accepts the first choice it is offered, without ever asking anybody.
+///
+/// This is useful when rpick is driven by a script or a scheduled job where there is nobody around
+/// to answer prompts. Tables and messages can optionally be forwarded to another [`Ui`], which is
+/// also informed of each choice that gets accepted.
+#[derive(Default)]
+pub struct AutoAcceptUi<'a> {
+    /// If set, tables and messages are forwarded to this Ui.
+    inner: Option<&'a dyn Ui>,
+}
+
+impl<'a> AutoAcceptUi<'a> {
+    /// Construct an AutoAcceptUi that silently accepts every choice.
+    pub fn new() -> Self {
+        AutoAcceptUi { inner: None }
+    }
+
+    /// Construct an AutoAcceptUi that forwards tables and messages to the given Ui.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`: The Ui to forward tables and messages to. Its [`Ui::prompt_choice`] is never
+    ///   called; instead, it is sent an [`Ui::info`] message naming each accepted choice.
+    pub fn wrap(inner: &'a dyn Ui) -> Self {
+        AutoAcceptUi { inner: Some(inner) }
+    }
+}
+
+impl Ui for AutoAcceptUi<'_> {
+    /// Defer to the inner Ui, or return `false` if there is not one.
+    fn call_display_table(&self) -> bool {
+        self.inner.is_some_and(|ui| ui.call_display_table())
+    }
+
+    /// Forward the table to the inner Ui, if there is one.
+    fn display_table(&self, table: &Table) {
+        if let Some(ui) = self.inner {
+            ui.display_table(table);
+        }
+    }
+
+    /// Forward the message to the inner Ui, if there is one.
+    fn info(&self, message: &str) {
+        if let Some(ui) = self.inner {
+            ui.info(message);
+        }
+    }
+
+    /// Accept the given choice, letting the inner Ui know about it if there is one.
+    fn prompt_choice(&self, choice: &str) -> bool {
+        if let Some(ui) = self.inner {
+            ui.info(&format!("Choice is {}.", choice));
+        }
+        true
+    }
+
+    /// Accept the given choice on the participant's behalf, letting the inner Ui know about it if
+    /// there is one.
+    fn prompt_vote(&self, participant: &str, choice: &str) -> Vote {
+        if let Some(ui) = self.inner {
+            ui.info(&format!("{} accepts {}.", participant, choice));
+        }
+        Vote::Accept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate;
+
+    use super::*;
+
+    #[test]
+    fn test_serialize_table() {
+        let table = Table {
+            footer: vec!["Total".into(), 100.0.into()],
+            header: vec!["Name".into(), "Chance".into()],
+            rows: vec![
+                Row {
+                    cells: vec!["this".into(), 75.0.into()],
+                    chosen: true,
+                },
+                Row {
+                    cells: vec![Cell::Boolean(false), Cell::Integer(-1)],
+                    chosen: false,
+                },
+            ],
+        };
+
+        assert_eq!(
+            serde_json::to_string(&table).unwrap(),
+            "{\"footer\":[\"Total\",100.0],\"header\":[\"Name\",\"Chance\"],\"rows\":[\
+             {\"cells\":[\"this\",75.0],\"chosen\":true},\
+             {\"cells\":[false,-1],\"chosen\":false}]}"
+        );
+    }
+
+    #[test]
+    fn test_auto_accept_ui() {
+        let ui = AutoAcceptUi::new();
+
+        assert!(!ui.call_display_table());
+        assert!(ui.prompt_choice("this"));
+    }
+
+    #[test]
+    fn test_auto_accept_ui_wrap() {
+        let mut inner = MockUi::new();
+        inner
+            .expect_call_display_table()
+            .times(1)
+            .returning(|| true);
+        inner
+            .expect_info()
+            .with(predicate::eq("Choice is this."))
+            .times(1)
+            .returning(|_| ());
+        inner.expect_prompt_choice().never();
+        let ui = AutoAcceptUi::wrap(&inner);
+
+        assert!(ui.call_display_table());
+        assert!(ui.prompt_choice("this"));
+    }
 }
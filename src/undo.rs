@@ -0,0 +1,316 @@
+/* Copyright © 2021 Randy Barlow
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, version 3 of the License.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.*/
+//! # Undo
+//!
+//! This module defines the undo stack, which remembers what categories looked like before recent
+//! picks so that accidentally accepted choices can be reverted.
+use std::error;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config;
+use crate::history;
+use crate::state::CategoryState;
+
+/// The maximum number of states kept on the undo stack. Once the stack is full, the oldest state
+/// is forgotten each time a new one is pushed.
+pub const MAX_UNDO_DEPTH: usize = 10;
+
+/// Return the undo stack stored in the given file.
+///
+/// A file that does not exist yet is treated as an empty stack.
+///
+/// # Arguments
+///
+/// * `undo_file_path` - A filesystem path to the YAML file holding the undo stack.
+pub fn read_undo_stack(undo_file_path: &str) -> Result<UndoStack, Box<dyn error::Error>> {
+    let f = match File::open(undo_file_path) {
+        Ok(f) => f,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(UndoStack::default()),
+        Err(error) => return Err(error.into()),
+    };
+    let reader = BufReader::new(f);
+
+    let stack: UndoStack = serde_yaml::from_reader(reader)?;
+    Ok(stack)
+}
+
+/// Save the given undo stack to the given file.
+///
+/// # Arguments
+///
+/// * `undo_file_path` - A filesystem path that the undo stack should be written to.
+/// * `stack` - The undo stack to save.
+pub fn write_undo_stack(
+    undo_file_path: &str,
+    stack: &UndoStack,
+) -> Result<(), Box<dyn error::Error>> {
+    let yaml = serde_yaml::to_string(stack)?;
+    config::write_atomically(undo_file_path, &yaml)?;
+    Ok(())
+}
+
+/// A category's state from before a pick.
+///
+/// # Attributes
+///
+/// * `timestamp` - When the pick that changed the category was made.
+/// * `category` - The name of the category.
+/// * `state` - The category as it was before the pick.
+/// * `picks` - How many entries the pick added to the history.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UndoEntry {
+    pub timestamp: DateTime<Local>,
+    pub category: String,
+    pub state: config::ConfigCategory,
+    #[serde(default)]
+    pub picks: usize,
+}
+
+impl UndoEntry {
+    /// Undo the pick in the given category, which must be the category this entry was taken from.
+    ///
+    /// Only the parts of the category that picking changes, as described by [`CategoryState`],
+    /// are restored. Anything else that was changed since the pick, such as a choice's weight, is
+    /// kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `category` - The category as it is now.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`UndoError::ChoicesChanged`] without changing the category if its model or choices
+    /// are not the ones it had before the pick, since the pick could not be undone faithfully.
+    pub fn restore(&self, category: &mut config::ConfigCategory) -> Result<(), UndoError> {
+        let mut before = self.state.choice_names();
+        let mut now = category.choice_names();
+        before.sort_unstable();
+        now.sort_unstable();
+        if self.state.model() != category.model() || before != now {
+            return Err(UndoError::ChoicesChanged(self.category.clone()));
+        }
+        CategoryState::of(&self.state).apply(category);
+        Ok(())
+    }
+
+    /// Remove the entries that the pick added from the given history.
+    ///
+    /// Those are the last `picks` entries for the category, since any later picks from it were
+    /// undone first. Picks are not matched by their times, which can be out of order when picks
+    /// were made with [`crate::engine::Engine::set_now`].
+    ///
+    /// # Arguments
+    ///
+    /// * `history` - The history, in the order its entries were recorded.
+    pub fn remove_picks(&self, history: &mut Vec<history::HistoryEntry>) {
+        let mut remaining = self.picks;
+        for index in (0..history.len()).rev() {
+            if remaining == 0 {
+                break;
+            }
+            if history[index].category == self.category {
+                history.remove(index);
+                remaining -= 1;
+            }
+        }
+    }
+}
+
+/// Define the errors that can be returned when undoing a pick.
+#[derive(Debug, Error, PartialEq)]
+pub enum UndoError {
+    #[error(
+        "The model or choices of the category `{0}` have changed since the pick, so it can't be \
+         undone."
+    )]
+    ChoicesChanged(String),
+}
+
+/// A bounded stack of [`UndoEntry`]'s, with the most recent entry on top.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UndoStack {
+    entries: Vec<UndoEntry>,
+}
+
+impl UndoStack {
+    /// Push an entry onto the stack, forgetting the oldest entry if the stack is full.
+    pub fn push(&mut self, entry: UndoEntry) {
+        self.entries.push(entry);
+        if self.entries.len() > MAX_UNDO_DEPTH {
+            self.entries.remove(0);
+        }
+    }
+
+    /// Remove and return the most recent entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `category` - If given, return the most recent entry for this category, leaving any more
+    ///   recent entries for other categories on the stack.
+    pub fn pop(&mut self, category: Option<&str>) -> Option<UndoEntry> {
+        let index = self
+            .entries
+            .iter()
+            .rposition(|entry| category.is_none_or(|c| c == entry.category))?;
+        Some(self.entries.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(category: &str) -> UndoEntry {
+        UndoEntry {
+            timestamp: Local::now(),
+            category: category.to_string(),
+            state: config::ConfigCategory::Lru {
+                choices: vec![category.into()],
+                group: None,
+            },
+            picks: 1,
+        }
+    }
+
+    #[test]
+    fn test_remove_picks() {
+        let history_entry = |category: &str, choice: &str, day: u32| history::HistoryEntry {
+            timestamp: Local.with_ymd_and_hms(2021, 1, day, 12, 0, 0).unwrap(),
+            category: category.to_string(),
+            model: "lru".to_string(),
+            choice: choice.to_string(),
+            rejected: vec![],
+            vetoes: Default::default(),
+        };
+        let mut history = vec![
+            history_entry("food", "a", 5),
+            history_entry("food", "b", 6),
+            history_entry("drinks", "c", 7),
+            // A back dated pick is removed even though older picks were recorded before it.
+            history_entry("food", "d", 1),
+            history_entry("drinks", "e", 8),
+        ];
+        let mut entry = entry("food");
+        entry.picks = 2;
+
+        entry.remove_picks(&mut history);
+
+        assert_eq!(
+            history
+                .iter()
+                .map(|h| h.choice.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "c", "e"]
+        );
+    }
+
+    #[test]
+    fn test_restore() {
+        let lottery = |choices: &[(&str, u64)]| config::ConfigCategory::Lottery {
+            choices: choices
+                .iter()
+                .map(|(name, tickets)| config::LotteryChoice {
+                    name: name.to_string(),
+                    tickets: *tickets,
+                    weight: 1,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
+                })
+                .collect(),
+            group: None,
+        };
+        let entry = UndoEntry {
+            timestamp: Local::now(),
+            category: "prizes".to_string(),
+            state: lottery(&[("a", 1), ("b", 2)]),
+            picks: 1,
+        };
+
+        // The tickets are restored, but the hand edited weight is kept.
+        let mut category = lottery(&[("b", 3), ("a", 0)]);
+        if let config::ConfigCategory::Lottery { choices, .. } = &mut category {
+            choices[0].weight = 5;
+        }
+        entry.restore(&mut category).unwrap();
+        match &category {
+            config::ConfigCategory::Lottery { choices, .. } => {
+                assert_eq!(
+                    choices
+                        .iter()
+                        .map(|c| (c.name.as_str(), c.tickets, c.weight))
+                        .collect::<Vec<_>>(),
+                    vec![("b", 2, 5), ("a", 1, 1)]
+                );
+            }
+            category => panic!("Unexpected category: {:?}", category),
+        }
+
+        let mut category = lottery(&[("a", 0), ("b", 2), ("c", 1)]);
+        let unchanged = category.clone();
+        assert_eq!(
+            entry.restore(&mut category),
+            Err(UndoError::ChoicesChanged("prizes".to_string()))
+        );
+        assert_eq!(category, unchanged);
+    }
+
+    #[test]
+    fn test_pop() {
+        let mut stack = UndoStack::default();
+        stack.push(entry("a"));
+        stack.push(entry("b"));
+        stack.push(entry("a"));
+        stack.push(entry("c"));
+
+        assert_eq!(stack.pop(Some("a")).unwrap().category, "a");
+        assert_eq!(stack.pop(None).unwrap().category, "c");
+        assert_eq!(stack.pop(Some("c")), None);
+        assert_eq!(stack.pop(None).unwrap().category, "b");
+        assert_eq!(stack.pop(Some("a")).unwrap().category, "a");
+        assert_eq!(stack.pop(None), None);
+    }
+
+    #[test]
+    fn test_push_is_bounded() {
+        let mut stack = UndoStack::default();
+        for i in 0..MAX_UNDO_DEPTH + 2 {
+            stack.push(entry(&i.to_string()));
+        }
+
+        assert_eq!(stack.entries.len(), MAX_UNDO_DEPTH);
+        assert_eq!(stack.entries[0].category, "2");
+    }
+
+    #[test]
+    fn test_read_and_write_undo_stack() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rpick.undo.yml");
+        let path = path.to_str().unwrap();
+        let mut stack = read_undo_stack(path).unwrap();
+        assert_eq!(stack, UndoStack::default());
+
+        stack.push(entry("a"));
+        write_undo_stack(path, &stack).unwrap();
+
+        assert_eq!(read_undo_stack(path).unwrap(), stack);
+    }
+}
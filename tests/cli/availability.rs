@@ -0,0 +1,140 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of choices that are only available at some times.
+use serde_json::{json, Value};
+
+const CONFIG: &str = "
+---
+lunch:
+  model: weighted
+  choices:
+    - name: Centro
+      available:
+        - days: [mon, tue, wed, thu, fri]
+          times: [\"11:30-14:00\"]
+    - name: Sitti
+      weight: 2
+      unavailable:
+        - from: 2021-06-01
+          until: 2021-06-13
+    - name: Beasley's
+      available:
+        - rrule: FREQ=MONTHLY;BYDAY=-1FR
+";
+
+#[test]
+// The chance table should list the unavailable choices at the bottom, with the reasons.
+fn show() {
+    // 2021-06-07 is a Monday.
+    let (stdout, _) = super::test_rpick_with_config(
+        CONFIG,
+        &[
+            "show",
+            "lunch",
+            "--at",
+            "2021-06-07T12:30",
+            "--output",
+            "json",
+        ],
+        "",
+        true,
+    );
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+    assert_eq!(
+        document["header"],
+        json!(["Name", "Weight", "Chance", "Unavailable"])
+    );
+    assert_eq!(
+        document["rows"],
+        json!([
+            {"cells": ["Centro", 1, 100.0, ""], "chosen": false},
+            {
+                "cells": ["Beasley's", "", "", "only available repeating FREQ=MONTHLY;BYDAY=-1FR"],
+                "chosen": false
+            },
+            {
+                "cells": ["Sitti", "", "", "unavailable from 2021-06-01 until 2021-06-13"],
+                "chosen": false
+            },
+        ])
+    );
+    assert_eq!(document["footer"], json!(["Total", 1, 100.0, ""]));
+}
+
+#[test]
+// Only the choices that are available at the time of the pick should be offered.
+fn pick() {
+    // 2021-06-25 is the last Friday of June, and Centro is closed in the evening.
+    let (stdout, _) = super::test_rpick_with_config(
+        CONFIG,
+        &[
+            "lunch",
+            "--at",
+            "2021-06-25T19:00",
+            "--seed",
+            "1",
+            "--verbose",
+        ],
+        "n\ny\n",
+        true,
+    );
+
+    assert!(
+        !stdout.contains("Choice is Centro."),
+        "Unexpected output: {}",
+        stdout
+    );
+    assert!(
+        stdout.contains("only available on mon, tue, wed, thu, fri at 11:30-14:00"),
+        "Unexpected output: {}",
+        stdout
+    );
+}
+
+#[test]
+// Picking should fail if every choice is unavailable.
+fn all_unavailable() {
+    // 2021-06-12 is a Saturday in the middle of Sitti's closure.
+    let (stdout, _) =
+        super::test_rpick_with_config(CONFIG, &["lunch", "--at", "2021-06-12T12:00"], "", false);
+
+    assert_eq!(
+        stdout,
+        "Every choice in the category `lunch` is unavailable.\n"
+    );
+}
+
+#[test]
+// A recurrence rule that needs a start date should be rejected without one.
+fn missing_start() {
+    let config = "
+---
+lunch:
+  model: even
+  choices:
+    - name: Centro
+      available:
+        - rrule: FREQ=WEEKLY;INTERVAL=2
+";
+    let (stdout, _) = super::test_rpick_with_config(config, &["lunch"], "", false);
+
+    assert!(
+        stdout.contains("The recurrence rule `FREQ=WEEKLY;INTERVAL=2` needs a `from` date"),
+        "Unexpected output: {}",
+        stdout
+    );
+}
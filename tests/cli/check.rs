@@ -0,0 +1,145 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of the check subcommand.
+use regex::Regex;
+
+#[test]
+fn check_errors() {
+    let config = "
+---
+lunch:
+  model: weighted
+  choices:
+    - name: Centro
+    - name: Sitti
+      weight: 0
+    - name: Centro
+tea:
+  model: inventory
+  choices:
+    - name: Black
+      tickets: 0
+";
+
+    let (stdout, config_contents) = super::test_rpick_with_config(config, &["check"], "", false);
+
+    let re = Regex::new(concat!(
+        r"error[ |]+lunch[ |]+Centro[ |]+The choice appears more than once\.[ |]*\n *",
+        r"warning[ |]+lunch[ |]+Sitti[ |]+The choice has a weight of 0, so it can never be ",
+        r"picked\.[ |]*\n *",
+        r"warning[ |]+tea[ |]+\|[ |]+Every choice is out of tickets\.",
+    ))
+    .unwrap();
+    assert!(re.is_match(&stdout), "Unexpected output: {}", stdout);
+    assert!(stdout.ends_with("Found 1 error in the config.\n"));
+    assert_eq!(config_contents, config);
+}
+
+#[test]
+// Warnings alone should not make check fail.
+fn check_warnings() {
+    let config = "
+---
+tea:
+  model: inventory
+  choices:
+    - name: Black
+      tickets: 0
+";
+
+    let (stdout, _) = super::test_rpick_with_config(config, &["check"], "", true);
+
+    assert!(
+        stdout.contains("Every choice is out of tickets."),
+        "Unexpected output: {}",
+        stdout
+    );
+}
+
+#[test]
+// Misspelled choice fields are ignored, so check should point them out.
+fn check_unknown_fields() {
+    let config = "
+---
+lunch:
+  model: weighted
+  choices:
+    - name: Centro
+      wieght: 3
+      attributes:
+        weight: '2'
+";
+
+    let (stdout, config_contents) = super::test_rpick_with_config(config, &["check"], "", true);
+
+    let re = Regex::new(concat!(
+        r"warning[ |]+lunch[ |]+Centro[ |]+The choice has a field `wieght` that is not used, so ",
+        r"it is ignored\. Did you mean `weight`\?[ |]*\n *",
+        r"warning[ |]+lunch[ |]+Centro[ |]+The attribute `weight` has the name of a choice ",
+        r"field, so it is only shown and not used to pick\.",
+    ))
+    .unwrap();
+    assert!(re.is_match(&stdout), "Unexpected output: {}", stdout);
+    assert_eq!(config_contents, config);
+}
+
+#[test]
+fn check_no_problems() {
+    let config = "
+---
+code:
+  model: lru
+  choices:
+    - Add a feature
+";
+
+    let (stdout, _) = super::test_rpick_with_config(config, &["check"], "", true);
+
+    assert_eq!(stdout, "No problems were found.\n");
+}
+
+#[test]
+// Categories with errors should still be picked from, with a warning about the errors.
+fn pick_duplicate_choice() {
+    let config = "
+---
+code:
+  model: lru
+  choices:
+    - Add a feature
+    - Add a feature
+";
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, config);
+
+    let assert = assert_cmd::Command::cargo_bin("rpick")
+        .unwrap()
+        .args(["code", "-c", config_path.to_str().unwrap()])
+        .write_stdin("y\n")
+        .assert()
+        .success();
+
+    let output = assert.get_output();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Choice is Add a feature. Accept? (Y/n) "
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "error: Problem with the choice `Add a feature` in the category `code`: The choice \
+         appears more than once. Run `rpick check` for details.\n"
+    );
+}
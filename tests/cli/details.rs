@@ -0,0 +1,92 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of choice descriptions, URLs, and attributes.
+use serde_json::{json, Value};
+
+const CONFIG: &str = "
+---
+lunch:
+  model: weighted
+  choices:
+    - name: Centro
+      weight: 3
+      description: Italian
+      url: https://centro.example
+      attributes:
+        phone: 555-0100
+    - name: Sitti
+      weight: 1
+";
+
+#[test]
+// The prompt should show the offered choice's details.
+fn prompt() {
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["lunch", "--seed", "1"], "n\ny\n", true);
+
+    assert_eq!(
+        stdout,
+        "Centro - Italian\n  https://centro.example\n  phone: 555-0100\n\
+         Choice is Centro. Accept? (Y/n) Choice is Sitti. Accept? (Y/n) "
+    );
+    // The details should be left alone when the config is saved.
+    assert_eq!(config_contents, CONFIG);
+}
+
+#[test]
+// JSON output should include the details of each picked choice.
+fn pick_json() {
+    let (stdout, _) = super::test_rpick_with_config(
+        CONFIG,
+        &["lunch", "--seed", "1", "--output", "json"],
+        "",
+        true,
+    );
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+    assert_eq!(document["choices"], json!(["Centro"]));
+    assert_eq!(
+        document["details"],
+        json!([{
+            "description": "Italian",
+            "url": "https://centro.example",
+            "attributes": {"phone": "555-0100"},
+        }])
+    );
+}
+
+#[test]
+// The chance table should have a column for each kind of detail.
+fn show() {
+    let (stdout, _) =
+        super::test_rpick_with_config(CONFIG, &["show", "lunch", "--output", "json"], "", true);
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+    assert_eq!(
+        document["header"],
+        json!(["Name", "Weight", "Chance", "Description", "URL", "phone"])
+    );
+    assert_eq!(
+        document["rows"],
+        json!([
+            {"cells": ["Sitti", 1, 25.0, "", "", ""], "chosen": false},
+            {
+                "cells": ["Centro", 3, 75.0, "Italian", "https://centro.example", "555-0100"],
+                "chosen": false,
+            },
+        ])
+    );
+}
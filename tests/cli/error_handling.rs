@@ -13,7 +13,7 @@
  * You should have received a copy of the GNU General Public License
  * along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
-/// The tests in this module assert correct error handling.
+//! The tests in this module assert correct error handling.
 
 const CATEGORY_NOT_FOUND_CONFIG: &str = "
 ---
@@ -30,12 +30,8 @@ test:
 fn category_not_found() {
     let expected_output = "The category `does_not_exist` was not found in the given config.\n";
 
-    let (stdout, config_contents) = super::test_rpick_with_config(
-        CATEGORY_NOT_FOUND_CONFIG,
-        &mut vec!["does_not_exist"],
-        "",
-        false,
-    );
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CATEGORY_NOT_FOUND_CONFIG, &["does_not_exist"], "", false);
 
     assert_eq!(stdout, expected_output);
     // Since the category didn't exist, rpick should not have changed the file.
@@ -52,3 +48,127 @@ fn config_not_found() {
 
     assert_eq!(stdout, expected_output);
 }
+
+#[test]
+// The user should get a useful error message if they ask for more items than the category has.
+fn count_too_large() {
+    let expected_output = "Cannot pick 4 items from the category `test`, which only has 3 \
+                           distinct choices.\n";
+
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CATEGORY_NOT_FOUND_CONFIG, &["test", "-n", "4"], "", false);
+
+    assert_eq!(stdout, expected_output);
+    assert_eq!(config_contents, CATEGORY_NOT_FOUND_CONFIG);
+}
+
+#[test]
+// Asking for no items at all should be an error, and should leave the config alone.
+fn count_zero() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CATEGORY_NOT_FOUND_CONFIG);
+    let config_path = config_path.to_str().expect("t");
+
+    let stdout = super::test_rpick(&["test", "-n", "0", "-c", config_path], "", false);
+
+    assert_eq!(
+        stdout,
+        "Cannot pick 0 items from the category `test`. Ask for at least 1.\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(config_path).expect("Could not read config"),
+        CATEGORY_NOT_FOUND_CONFIG
+    );
+    assert!(!config_dir.path().join("rpick.undo.yml").exists());
+}
+
+#[test]
+// rpick should exit with an error rather than panicking if stdin is closed before it gets an answer.
+fn stdin_closed() {
+    let expected_output = "Choice is option 1. Accept? (Y/n) \nUnable to read an answer from \
+                           stdin. Use --yes to accept the first choice without prompting.\n";
+    let config = "
+---
+test:
+  model: lru
+  choices:
+    - option 1
+    - option 2
+";
+
+    let (stdout, config_contents) = super::test_rpick_with_config(config, &["test"], "", false);
+
+    assert_eq!(stdout, expected_output);
+    // Since nothing was accepted, rpick should not have changed the file.
+    assert_eq!(config_contents, config);
+}
+
+const DEGENERATE_CONFIG: &str = "
+---
+empty:
+  model: even
+  choices: []
+exhausted:
+  model: inventory
+  choices:
+    - name: option 1
+      tickets: 0
+flat:
+  model: gaussian
+  stddev_scaling_factor: 0
+  choices:
+    - option 1
+";
+
+#[test]
+// Categories that cannot be picked from should produce a useful error rather than a panic. Problems
+// with the config itself are reported before picking.
+fn degenerate_categories() {
+    let cases = [
+        (
+            "empty",
+            "The category `empty` has no choices to pick from.\n",
+        ),
+        (
+            "exhausted",
+            "Every choice in the category `exhausted` is out of tickets.\n",
+        ),
+        (
+            "flat",
+            "The `stddev_scaling_factor` setting of the category `flat` is invalid: it must be a \
+             number greater than 0.\n",
+        ),
+    ];
+
+    for (category, expected_output) in cases {
+        let (stdout, config_contents) =
+            super::test_rpick_with_config(DEGENERATE_CONFIG, &[category], "", false);
+
+        assert_eq!(stdout, expected_output);
+        assert_eq!(config_contents, DEGENERATE_CONFIG);
+    }
+}
+
+#[test]
+// rpick should refuse to touch a config that another rpick process is using.
+fn config_locked() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CATEGORY_NOT_FOUND_CONFIG);
+    let config_path = config_path.to_str().expect("t");
+    let _lock = rpick::config::ConfigFile::lock(config_path).expect("Could not lock the config");
+
+    let stdout = super::test_rpick(&["test", "--yes", "-c", config_path], "", false);
+
+    assert_eq!(
+        stdout,
+        format!(
+            "Error reading config file at {}: The config file is in use by another rpick \
+             process. Please try again once that process is done.\n",
+            config_path
+        )
+    );
+    assert_eq!(
+        std::fs::read_to_string(config_path).expect("Could not read config"),
+        CATEGORY_NOT_FOUND_CONFIG
+    );
+}
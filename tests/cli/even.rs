@@ -31,8 +31,7 @@ even:
 // Assert correct behavior with an even distribution model config
 #[test]
 fn pick() {
-    let (stdout, config_contents) =
-        super::test_rpick_with_config(CONFIG, &mut vec!["even"], "y\n", true);
+    let (stdout, config_contents) = super::test_rpick_with_config(CONFIG, &["even"], "y\n", true);
 
     let expected_config: BTreeMap<String, ConfigCategory> =
         serde_yaml::from_str(CONFIG).expect("Could not parse yaml");
@@ -0,0 +1,124 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of the --exclude flag.
+use serde_json::{json, Value};
+
+const CONFIG: &str = "
+---
+lunch:
+  model: lru
+  choices:
+    - Centro
+    - Sitti
+    - Los Tacos
+    - Beasley's
+";
+
+#[test]
+// Excluded choices should not be offered, or shown in the table, and keep their place in the
+// config.
+fn pick_exclude() {
+    let (stdout, config_contents) = super::test_rpick_with_config(
+        CONFIG,
+        &[
+            "lunch",
+            "--exclude",
+            "Centro",
+            "--exclude",
+            "Los Tacos",
+            "--output",
+            "json",
+        ],
+        "",
+        true,
+    );
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+    assert_eq!(document["choices"], json!(["Sitti"]));
+    assert_eq!(
+        document["table"]["rows"],
+        json!([
+            {"cells": ["Beasley's"], "chosen": false},
+            {"cells": ["Sitti"], "chosen": true},
+        ])
+    );
+    assert_eq!(
+        config_contents,
+        "\n---\nlunch:\n  model: lru\n  choices:\n    - Centro\n    - Los Tacos\n    - Beasley's\n    \
+         - Sitti\n"
+    );
+}
+
+#[test]
+// Excluded choices should be left out of the combined table when picking several items.
+fn pick_count_exclude() {
+    let (stdout, _) = super::test_rpick_with_config(
+        CONFIG,
+        &["lunch", "-n", "2", "--exclude", "Sitti", "--output", "json"],
+        "",
+        true,
+    );
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+    assert_eq!(document["choices"], json!(["Centro", "Los Tacos"]));
+    assert_eq!(
+        document["table"]["rows"],
+        json!([
+            {"cells": ["Beasley's"], "chosen": false},
+            {"cells": ["Los Tacos"], "chosen": true},
+            {"cells": ["Centro"], "chosen": true},
+        ])
+    );
+}
+
+#[test]
+// Excluding a choice that the category doesn't have is most likely a typo, so it is an error.
+fn unknown_choice() {
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["lunch", "--exclude", "Centor"], "", false);
+
+    assert_eq!(
+        stdout,
+        "The category `lunch` has no choice named `Centor`.\n"
+    );
+    assert_eq!(config_contents, CONFIG);
+}
+
+#[test]
+// Excluding every choice should be reported.
+fn all_excluded() {
+    let (stdout, _) = super::test_rpick_with_config(
+        CONFIG,
+        &[
+            "lunch",
+            "--exclude",
+            "Centro",
+            "--exclude",
+            "Sitti",
+            "--exclude",
+            "Los Tacos",
+            "--exclude",
+            "Beasley's",
+        ],
+        "",
+        false,
+    );
+
+    assert_eq!(
+        stdout,
+        "Every choice in the category `lunch` was excluded.\n"
+    );
+}
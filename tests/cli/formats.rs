@@ -0,0 +1,135 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of TOML and JSON configs, and the convert subcommand.
+use std::fs;
+
+use rpick::config::{read_config, Format};
+
+const YAML_CONFIG: &str = "
+---
+code:
+  model: lru
+  choices:
+    - Add a feature
+    - Fix a bug
+tea:
+  model: lottery
+  choices:
+    - name: Black
+      weight: 3
+    - name: Green
+      tickets: 0
+";
+
+const TOML_CONFIG: &str = "
+[code]
+model = \"lru\"
+choices = [\"Add a feature\", \"Fix a bug\"]
+";
+
+#[test]
+// A config ending in .toml should be read and saved as TOML.
+fn pick_toml() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = config_dir.path().join("rpick.toml");
+    fs::write(&config_path, TOML_CONFIG).expect("Could not write config");
+    let config_path = config_path.to_str().unwrap();
+
+    let stdout = super::test_rpick(&["code", "--yes", "-c", config_path], "", true);
+
+    assert_eq!(stdout, "Choice is Add a feature.\n");
+    assert_eq!(
+        fs::read_to_string(config_path).unwrap(),
+        "[code]\nmodel = \"lru\"\nchoices = [\"Fix a bug\", \"Add a feature\"]\n"
+    );
+}
+
+#[test]
+// The --format flag should override the config's extension.
+fn pick_format_flag() {
+    let config =
+        "{\"code\": {\"model\": \"lru\", \"choices\": [\"Add a feature\", \"Fix a bug\"]}}";
+
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(config, &["code", "--yes", "--format", "json"], "", true);
+
+    assert_eq!(stdout, "Choice is Add a feature.\n");
+    assert_eq!(
+        config_contents,
+        "{\n  \"code\": {\n    \"model\": \"lru\",\n    \"choices\": [\n      \"Fix a bug\",\n      \
+         \"Add a feature\"\n    ]\n  }\n}\n"
+    );
+}
+
+#[test]
+// Converting from YAML to TOML to JSON and back should keep every setting.
+fn convert_round_trip() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let yaml_path = super::write_config(&config_dir, YAML_CONFIG);
+    let yaml_path = yaml_path.to_str().unwrap();
+    let path = |name: &str| config_dir.path().join(name).to_str().unwrap().to_string();
+    let (toml_path, json_path, yaml_again_path) =
+        (path("rpick.toml"), path("rpick.json"), path("again.yml"));
+
+    let stdout = super::test_rpick(&["convert", &toml_path, "-c", yaml_path], "", true);
+    super::test_rpick(&["convert", &json_path, "-c", &toml_path], "", true);
+    super::test_rpick(&["convert", &yaml_again_path, "-c", &json_path], "", true);
+
+    assert_eq!(
+        stdout,
+        format!(
+            "Converted the yaml config at {} to toml at {}.\n",
+            yaml_path, toml_path
+        )
+    );
+    let original = read_config(yaml_path).unwrap();
+    assert_eq!(Format::from_path(&toml_path), Format::Toml);
+    assert!(fs::read_to_string(&toml_path)
+        .unwrap()
+        .contains("[[tea.choices]]"));
+    assert_eq!(read_config(&toml_path).unwrap(), original);
+    assert_eq!(read_config(&json_path).unwrap(), original);
+    assert_eq!(read_config(&yaml_again_path).unwrap(), original);
+}
+
+#[test]
+// The --to flag should choose the format, and existing files should not be overwritten.
+fn convert_to_flag_and_existing_file() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let yaml_path = super::write_config(&config_dir, YAML_CONFIG);
+    let yaml_path = yaml_path.to_str().unwrap();
+    let out_path = config_dir.path().join("out.conf");
+    let out_path = out_path.to_str().unwrap();
+
+    super::test_rpick(
+        &["convert", out_path, "--to", "json", "-c", yaml_path],
+        "",
+        true,
+    );
+    let stdout = super::test_rpick(&["convert", yaml_path, "-c", out_path], "", false);
+
+    assert_eq!(
+        Format::Json
+            .parse(&fs::read_to_string(out_path).unwrap())
+            .unwrap(),
+        read_config(yaml_path).unwrap()
+    );
+    assert_eq!(
+        stdout,
+        format!("Will not overwrite the existing file at {}.\n", yaml_path)
+    );
+    assert_eq!(fs::read_to_string(yaml_path).unwrap(), YAML_CONFIG);
+}
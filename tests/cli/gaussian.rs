@@ -32,7 +32,7 @@ gaussian:
 // Assert correct behavior with an gaussian model config
 fn pick() {
     let (stdout, config_contents) =
-        super::test_rpick_with_config(CONFIG, &mut vec!["gaussian"], "y\n", true);
+        super::test_rpick_with_config(CONFIG, &["gaussian"], "y\n", true);
 
     // Assert that the chosen item was a member of the config
     let expected_values: HashSet<&'static str> = ["option 1", "option 2", "option 3"]
@@ -47,11 +47,12 @@ fn pick() {
     if let ConfigCategory::Gaussian {
         choices,
         stddev_scaling_factor: _,
+        ..
     } = &mut expected_config.get_mut("gaussian").unwrap()
     {
-        let index = choices.iter().position(|x| x == pick.as_str()).unwrap();
-        choices.remove(index);
-        choices.push(pick);
+        let index = choices.iter().position(|x| x.name == pick).unwrap();
+        let choice = choices.remove(index);
+        choices.push(choice);
     }
     let parsed_config: BTreeMap<String, ConfigCategory> =
         serde_yaml::from_str(&config_contents).expect("Could not parse yaml");
@@ -0,0 +1,107 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of categories that are picked by a group.
+use regex::Regex;
+
+const CONFIG: &str = "
+---
+lunch:
+  model: lru
+  choices:
+    - Centro
+    - Sitti
+    - Beasley's
+  group:
+    participants: [alice, bob, carol]
+    rule: majority
+";
+
+#[test]
+// Each participant should vote on each offered choice, and the rule should decide.
+fn pick() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+    let config_path = config_path.to_str().unwrap();
+
+    // Centro gets one accept and two vetoes, and Sitti gets one accept, one abstention, and one
+    // veto, so neither has a majority. Beasley's gets two accepts, which outweigh carol's veto.
+    let stdout = super::test_rpick(
+        &["lunch", "-c", config_path],
+        "y\nv\nn\ny\na\nv\ny\ny\nv\n",
+        true,
+    );
+
+    assert_eq!(
+        stdout,
+        "alice: Choice is Centro. Accept, abstain, or veto? (Y/a/v) \
+         bob: Choice is Centro. Accept, abstain, or veto? (Y/a/v) \
+         carol: Choice is Centro. Accept, abstain, or veto? (Y/a/v) \
+         alice: Choice is Sitti. Accept, abstain, or veto? (Y/a/v) \
+         bob: Choice is Sitti. Accept, abstain, or veto? (Y/a/v) \
+         carol: Choice is Sitti. Accept, abstain, or veto? (Y/a/v) \
+         alice: Choice is Beasley's. Accept, abstain, or veto? (Y/a/v) \
+         bob: Choice is Beasley's. Accept, abstain, or veto? (Y/a/v) \
+         carol: Choice is Beasley's. Accept, abstain, or veto? (Y/a/v) "
+    );
+    let history = std::fs::read_to_string(config_dir.path().join("rpick.history.yml"))
+        .expect("The history was not written");
+    assert!(
+        history.contains(
+            "choice: \"Beasley's\"\nrejected:\n  - Centro\n  - Sitti\nvetoes:\n  \
+             \"Beasley's\":\n    - carol\n  Centro:\n    - bob\n    - carol\n  Sitti:\n    - carol\n"
+        ),
+        "Unexpected history: {}",
+        history
+    );
+
+    let stdout = super::test_rpick(&["history", "-c", config_path], "", true);
+    let re = Regex::new(
+        r"Beasley's \(vetoed by carol\)[ |]+Centro \(vetoed by bob, carol\), Sitti \(vetoed by carol\)",
+    )
+    .unwrap();
+    assert!(re.is_match(&stdout), "Unexpected history: {}", stdout);
+}
+
+#[test]
+// With --yes, every participant should accept the first choice.
+fn pick_yes() {
+    let (stdout, _) = super::test_rpick_with_config(CONFIG, &["lunch", "--yes"], "", true);
+
+    assert_eq!(
+        stdout,
+        "alice accepts Centro.\nbob accepts Centro.\ncarol accepts Centro.\n"
+    );
+}
+
+#[test]
+// The check subcommand should report a group without participants.
+fn check_no_participants() {
+    let config = "
+---
+lunch:
+  model: even
+  choices: [Centro]
+  group:
+    participants: []
+";
+    let (stdout, _) = super::test_rpick_with_config(config, &["check"], "", false);
+
+    assert!(
+        stdout.contains("The group has no participants to decide."),
+        "Unexpected output: {}",
+        stdout
+    );
+}
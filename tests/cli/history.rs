@@ -0,0 +1,90 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of the pick history.
+use regex::Regex;
+
+const CONFIG: &str = "
+---
+lru:
+  model: lru
+  choices:
+    - option 1
+    - option 2
+    - option 3
+even:
+  model: even
+  choices:
+    - option 1
+";
+
+#[test]
+// Accepted picks should be appended to the history, along with the choices that were rejected.
+fn pick_and_list() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+    let config_path = config_path.to_str().unwrap();
+
+    super::test_rpick(&["lru", "-c", config_path], "n\ny\n", true);
+    super::test_rpick(&["even", "-c", config_path], "y\n", true);
+
+    let history = std::fs::read_to_string(config_dir.path().join("rpick.history.yml"))
+        .expect("The history was not written");
+    assert_eq!(history.matches("category:").count(), 2);
+
+    let stdout = super::test_rpick(&["history", "lru", "-c", config_path], "", true);
+    let re =
+        Regex::new(r"\d{4}-\d{2}-\d{2} \d{2}:\d{2}[ |]+lru[ |]+option 2[ |]+option 1").unwrap();
+    assert!(re.is_match(&stdout), "Unexpected history: {}", stdout);
+    assert!(!stdout.contains("even"));
+
+    let stdout = super::test_rpick(&["history", "-c", config_path, "--limit", "1"], "", true);
+    assert!(stdout.contains("even"));
+    assert!(!stdout.contains("lru"));
+}
+
+#[test]
+// Picks made with --at should be listed by when they were made, not when they were recorded.
+fn back_dated() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+    let config_path = config_path.to_str().unwrap();
+    super::test_rpick(&["even", "-c", config_path], "y\n", true);
+    super::test_rpick(
+        &["lru", "--at", "2021-01-01T12:00", "-c", config_path],
+        "y\n",
+        true,
+    );
+
+    let stdout = super::test_rpick(&["history", "-c", config_path], "", true);
+
+    let lru = stdout.find("lru").expect("The lru pick was not listed");
+    let even = stdout.find("even").expect("The even pick was not listed");
+    assert!(lru < even, "Unexpected history: {}", stdout);
+    let stdout = super::test_rpick(&["history", "-c", config_path, "--limit", "1"], "", true);
+    assert!(stdout.contains("even"));
+    assert!(!stdout.contains("lru"));
+}
+
+#[test]
+// Listing the history before any picks have been made should say so.
+fn empty() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+
+    let stdout = super::test_rpick(&["history", "-c", config_path.to_str().unwrap()], "", true);
+
+    assert_eq!(stdout, "No matching picks have been recorded.\n");
+}
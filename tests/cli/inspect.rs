@@ -0,0 +1,64 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of the list and show subcommands.
+use regex::Regex;
+
+const CONFIG: &str = "
+---
+tea:
+  model: inventory
+  choices:
+    - name: Earl Grey
+      tickets: 3
+    - name: Black
+      tickets: 1
+code:
+  model: lru
+  choices:
+    - Add a feature
+    - Fix a bug
+";
+
+#[test]
+fn list() {
+    let (stdout, config_contents) = super::test_rpick_with_config(CONFIG, &["list"], "", true);
+
+    let re = Regex::new(r"code[ |]+lru[ |]+2 *\n *tea[ |]+inventory[ |]+2").unwrap();
+    assert!(re.is_match(&stdout), "Unexpected output: {}", stdout);
+    assert_eq!(config_contents, CONFIG);
+}
+
+#[test]
+// Show should display the chance table without prompting or changing the config.
+fn show() {
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["show", "tea"], "", true);
+
+    let re = Regex::new(r"Black[ |]+1[ |]+25.00%[ |]*\n *Earl Grey[ |]+3[ |]+75.00%").unwrap();
+    assert!(re.is_match(&stdout), "Unexpected output: {}", stdout);
+    assert!(!stdout.contains("Accept?"));
+    assert_eq!(config_contents, CONFIG);
+}
+
+#[test]
+fn show_category_not_found() {
+    let (stdout, _) = super::test_rpick_with_config(CONFIG, &["show", "nope"], "", false);
+
+    assert_eq!(
+        stdout,
+        "The category `nope` was not found in the given config.\n"
+    );
+}
@@ -38,7 +38,7 @@ inventory:
 // Assert correct behavior with an inventory model config
 fn pick() {
     let (stdout, config_contents) =
-        super::test_rpick_with_config(CONFIG, &mut vec!["inventory"], "y\n", true);
+        super::test_rpick_with_config(CONFIG, &["inventory"], "y\n", true);
 
     // Assert that the chosen item was a member of the config. Note that "option 4" is not listed
     // here, though it is in the config, since it has 0 tickets and should never be chosen.
@@ -51,7 +51,7 @@ fn pick() {
     // Assert that the inventory model reduces the tickets on the picked item
     let mut expected_config: BTreeMap<String, ConfigCategory> =
         serde_yaml::from_str(CONFIG).expect("Could not parse yaml");
-    if let ConfigCategory::Inventory { choices } =
+    if let ConfigCategory::Inventory { choices, .. } =
         &mut expected_config.get_mut("inventory").unwrap()
     {
         let index = choices.iter().position(|x| x.name == pick).unwrap();
@@ -61,3 +61,37 @@ fn pick() {
         serde_yaml::from_str(&config_contents).expect("Could not parse yaml");
     assert_eq!(parsed_config, expected_config);
 }
+
+#[test]
+// An inventory that has run out should be restocked before picking, and the restock saved.
+fn restock() {
+    let config = "
+---
+snacks:
+  model: inventory
+  restock:
+    policy: reset
+    tickets: 2
+  choices:
+    - name: chips
+      tickets: 0
+    - name: pretzels
+      tickets: 0
+";
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(config, &["snacks", "--seed", "1"], "y\n", true);
+
+    let pick = super::get_pick(&stdout);
+    let parsed_config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(&config_contents).expect("Could not parse yaml");
+    match &parsed_config["snacks"] {
+        ConfigCategory::Inventory { choices, .. } => {
+            for choice in choices {
+                let expected = if choice.name == pick { 1 } else { 2 };
+                assert_eq!(choice.tickets, expected, "{:?}", choice);
+                assert!(choice.restocked.is_some());
+            }
+        }
+        category => panic!("Unexpected category: {:?}", category),
+    }
+}
@@ -0,0 +1,147 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of --output json.
+use serde_json::{json, Value};
+
+const CONFIG: &str = "
+---
+code:
+  model: lru
+  choices:
+    - Add a feature
+    - Fix a bug
+tea:
+  model: inventory
+  choices:
+    - name: Earl Grey
+      tickets: 3
+    - name: Black
+      tickets: 1
+";
+
+#[test]
+// JSON output should accept the first choice without prompting, and describe the pick.
+fn pick() {
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["code", "--output", "json"], "", true);
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+    assert_eq!(
+        document,
+        json!({
+            "category": "code",
+            "model": "lru",
+            "choices": ["Add a feature"],
+            "details": [{}],
+            "table": {
+                "footer": [],
+                "header": ["Name"],
+                "rows": [
+                    {"cells": ["Fix a bug"], "chosen": false},
+                    {"cells": ["Add a feature"], "chosen": true},
+                ],
+            },
+        })
+    );
+    assert_eq!(
+        config_contents,
+        "\n---\ncode:\n  model: lru\n  choices:\n    - Fix a bug\n    - Add a feature\ntea:\n  \
+         model: inventory\n  choices:\n    - name: Earl Grey\n      tickets: 3\n    - name: Black\n      \
+         tickets: 1\n"
+    );
+}
+
+#[test]
+fn show() {
+    let (stdout, _) =
+        super::test_rpick_with_config(CONFIG, &["show", "tea", "--output", "json"], "", true);
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+    assert_eq!(
+        document["header"],
+        json!(["Name", "Weight", "Chance"]),
+        "Unexpected output: {}",
+        stdout
+    );
+    assert_eq!(document["rows"][0]["cells"], json!(["Black", 1, 25.0]));
+}
+
+#[test]
+fn error() {
+    let (stdout, _) =
+        super::test_rpick_with_config(CONFIG, &["nope", "--output", "json"], "", false);
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+    assert_eq!(
+        document,
+        json!({"error": "The category `nope` was not found in the given config."})
+    );
+}
+
+#[test]
+// A failing check should still include the problems it found.
+fn check_error() {
+    let config = "
+---
+code:
+  model: lru
+  choices:
+    - Add a feature
+    - Add a feature
+";
+    let (stdout, _) =
+        super::test_rpick_with_config(config, &["check", "--output", "json"], "", false);
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+    assert_eq!(document["error"], json!("Found 1 error in the config."));
+    assert_eq!(
+        document["table"]["rows"][0]["cells"],
+        json!([
+            "error",
+            "code",
+            "Add a feature",
+            "The choice appears more than once."
+        ])
+    );
+}
+
+#[test]
+// If the pick can't be saved, only the error should be printed, so that stdout is one JSON value.
+fn save_error() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+    // A directory where the history file should be keeps the history from being written.
+    std::fs::create_dir(config_dir.path().join("rpick.history.yml")).unwrap();
+
+    let stdout = super::test_rpick(
+        &[
+            "code",
+            "--output",
+            "json",
+            "-c",
+            config_path.to_str().unwrap(),
+        ],
+        "",
+        false,
+    );
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not one JSON value");
+    assert!(
+        document["error"].is_string(),
+        "Unexpected output: {}",
+        stdout
+    );
+}
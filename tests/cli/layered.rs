@@ -0,0 +1,187 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert that configs are gathered from the config directory, includes, and the project config.
+//!
+//! These tests point rpick's home config directory at a temporary directory with
+//! ```XDG_CONFIG_HOME```, which is only used on Linux.
+#![cfg(target_os = "linux")]
+use std::fs;
+use std::path::Path;
+
+use assert_cmd::Command;
+use regex::Regex;
+
+const MAIN_CONFIG: &str = "# Personal categories.
+include:
+  - ../team/rpick.yml
+code:
+  model: lru
+  choices:
+    - Add a feature
+    - Fix a bug
+";
+
+const TEAM_CONFIG: &str = "# Team categories.
+lunch:
+  model: lru
+  choices:
+    - Centro
+    - Sitti
+";
+
+const EXTRA_CONFIG: &str = "[tea]\nmodel = \"even\"\nchoices = [\"Black\"]\n";
+
+const PROJECT_CONFIG: &str = "{\"code\": {\"model\": \"lru\", \"choices\": [\"Write docs\"]}}";
+
+/// Write the given files into the given directory.
+fn write_files(dir: &Path, files: &[(&str, &str)]) {
+    for (name, contents) in files {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+}
+
+/// Run rpick without ```--config```, with the given home config directory and current directory.
+fn rpick(config_home: &Path, cwd: &Path, args: &[&str], expected_success: bool) -> String {
+    let mut rpick = Command::cargo_bin("rpick").unwrap();
+    let assert = rpick
+        .args(args)
+        .env("XDG_CONFIG_HOME", config_home)
+        .env_remove("RPICK_CONFIG")
+        .current_dir(cwd)
+        .assert();
+    let assert = if expected_success {
+        assert.success()
+    } else {
+        assert.failure()
+    };
+    String::from_utf8(assert.get_output().stdout.clone()).unwrap()
+}
+
+#[test]
+// Categories should be read from every layer, with later layers taking precedence.
+fn list_layers() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    write_files(
+        dir.path(),
+        &[
+            ("config/rpick.yml", MAIN_CONFIG),
+            ("team/rpick.yml", TEAM_CONFIG),
+            ("config/rpick/extra.toml", EXTRA_CONFIG),
+            ("project/rpick.json", PROJECT_CONFIG),
+            ("project/src/main.rs", ""),
+        ],
+    );
+
+    let stdout = rpick(
+        &dir.path().join("config"),
+        &dir.path().join("project/src"),
+        &["list"],
+        true,
+    );
+
+    let re = Regex::new(r"code[ |]+lru[ |]+1 *\n *lunch[ |]+lru[ |]+2 *\n *tea[ |]+even[ |]+1 *\n")
+        .unwrap();
+    assert!(re.is_match(&stdout), "Unexpected output: {}", stdout);
+}
+
+#[test]
+// A pick should be saved to the file its category came from, leaving the other files alone.
+fn pick_writes_to_source() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    write_files(
+        dir.path(),
+        &[
+            ("config/rpick.yml", MAIN_CONFIG),
+            ("team/rpick.yml", TEAM_CONFIG),
+            ("project/rpick.json", PROJECT_CONFIG),
+        ],
+    );
+    let (config_home, project) = (dir.path().join("config"), dir.path().join("project"));
+
+    let lunch = rpick(&config_home, &project, &["lunch", "--yes"], true);
+    let code = rpick(&config_home, &project, &["code", "--yes"], true);
+
+    assert_eq!(lunch, "Choice is Centro.\n");
+    assert_eq!(code, "Choice is Write docs.\n");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("team/rpick.yml")).unwrap(),
+        "# Team categories.\nlunch:\n  model: lru\n  choices:\n    - Sitti\n    - Centro\n"
+    );
+    assert_eq!(
+        fs::read_to_string(config_home.join("rpick.yml")).unwrap(),
+        MAIN_CONFIG
+    );
+    // The history is kept next to the main config.
+    assert!(config_home.join("rpick.history.yml").exists());
+}
+
+#[test]
+// New categories should go to the main config, which is created if needed.
+fn new_category_without_main_config() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    write_files(dir.path(), &[("project/rpick.json", PROJECT_CONFIG)]);
+    let config_home = dir.path().join("config");
+    fs::create_dir(&config_home).unwrap();
+
+    rpick(
+        &config_home,
+        &dir.path().join("project"),
+        &["new", "tea", "--model", "even"],
+        true,
+    );
+    let stdout = rpick(
+        &config_home,
+        &dir.path().join("project"),
+        &["new", "code", "--model", "even"],
+        false,
+    );
+
+    assert_eq!(
+        fs::read_to_string(config_home.join("rpick.yml")).unwrap(),
+        "---\ntea:\n  model: even\n  choices: []\n"
+    );
+    assert_eq!(stdout, "The category `code` already exists.\n");
+}
+
+#[test]
+// With --config, only that file and its includes should be read.
+fn config_flag_skips_layers() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    write_files(
+        dir.path(),
+        &[
+            ("config/rpick.yml", MAIN_CONFIG),
+            ("team/rpick.yml", TEAM_CONFIG),
+            ("config/rpick/extra.toml", EXTRA_CONFIG),
+            ("project/rpick.json", PROJECT_CONFIG),
+        ],
+    );
+    let config_path = dir.path().join("config/rpick.yml");
+
+    let stdout = rpick(
+        &dir.path().join("config"),
+        &dir.path().join("project"),
+        &["show", "tea", "-c", config_path.to_str().unwrap()],
+        false,
+    );
+
+    assert_eq!(
+        stdout,
+        "The category `tea` was not found in the given config.\n"
+    );
+}
@@ -42,7 +42,7 @@ lottery:
 // Assert correct behavior with an lottery model config
 fn pick() {
     let (stdout, config_contents) =
-        super::test_rpick_with_config(CONFIG, &mut vec!["lottery"], "y\n", true);
+        super::test_rpick_with_config(CONFIG, &["lottery"], "y\n", true);
 
     // Assert that the chosen item was a member of the config. Note that "option 4" is not listed
     // here, though it is in the config, since it has 0 tickets and should never be chosen.
@@ -56,7 +56,9 @@ fn pick() {
     // to the ones that weren't picked.
     let mut expected_config: BTreeMap<String, ConfigCategory> =
         serde_yaml::from_str(CONFIG).expect("Could not parse yaml");
-    if let ConfigCategory::Lottery { choices } = &mut expected_config.get_mut("lottery").unwrap() {
+    if let ConfigCategory::Lottery { choices, .. } =
+        &mut expected_config.get_mut("lottery").unwrap()
+    {
         for choice in choices.iter_mut() {
             if choice.name == pick {
                 choice.tickets = 0;
@@ -31,15 +31,14 @@ lru:
 #[test]
 // Assert correct behavior with an lru model config
 fn pick() {
-    let (stdout, config_contents) =
-        super::test_rpick_with_config(CONFIG, &mut vec!["lru"], "y\n", true);
+    let (stdout, config_contents) = super::test_rpick_with_config(CONFIG, &["lru"], "y\n", true);
 
     // Assert that the chosen item was a member of the config
     assert_eq!(super::get_pick(&stdout), "option 1");
     // Assert that the lru model moves the picked item into last place
     let mut expected_config: BTreeMap<String, ConfigCategory> =
         serde_yaml::from_str(CONFIG).expect("Could not parse yaml");
-    if let ConfigCategory::Lru { choices } = &mut expected_config.get_mut("lru").unwrap() {
+    if let ConfigCategory::Lru { choices, .. } = &mut expected_config.get_mut("lru").unwrap() {
         let pick = choices.remove(0);
         choices.push(pick);
     }
@@ -47,3 +46,100 @@ fn pick() {
         serde_yaml::from_str(&config_contents).expect("Could not parse yaml");
     assert_eq!(parsed_config, expected_config);
 }
+
+#[test]
+// Assert that --count picks several distinct items, moving each to the end in acceptance order
+fn pick_count() {
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["lru", "--count", "2"], "y\nn\ny\n", true);
+
+    assert_eq!(
+        stdout,
+        "Choice is option 1. Accept? (Y/n) Choice is option 2. Accept? (Y/n) \
+         Choice is option 3. Accept? (Y/n) "
+    );
+    let mut expected_config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(CONFIG).expect("Could not parse yaml");
+    if let ConfigCategory::Lru { choices, .. } = &mut expected_config.get_mut("lru").unwrap() {
+        *choices = vec!["option 2".into(), "option 1".into(), "option 3".into()];
+    }
+    let parsed_config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(&config_contents).expect("Could not parse yaml");
+    assert_eq!(parsed_config, expected_config);
+}
+
+#[test]
+// Assert that --yes accepts the first choice without reading stdin
+fn pick_yes() {
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["lru", "--yes"], "", true);
+
+    assert_eq!(stdout, "Choice is option 1.\n");
+    let parsed_config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(&config_contents).expect("Could not parse yaml");
+    assert_eq!(
+        parsed_config["lru"],
+        ConfigCategory::Lru {
+            choices: vec!["option 2".into(), "option 3".into(), "option 1".into()],
+            group: None,
+        }
+    );
+}
+
+#[test]
+// Assert that saving the pick keeps the config's comments, category order, and formatting
+fn pick_keeps_comments() {
+    let config = "# Things to do.
+lru:
+  model: lru
+  choices:
+  - option 1  # The first one.
+  - option 2
+# Things to eat.
+lunch:
+  model: gaussian
+  choices: [Centro, Sitti]
+";
+
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(config, &["lru", "--yes"], "", true);
+
+    assert_eq!(stdout, "Choice is option 1.\n");
+    assert_eq!(
+        config_contents,
+        "# Things to do.
+lru:
+  model: lru
+  choices:
+  - option 2
+  - option 1  # The first one.
+# Things to eat.
+lunch:
+  model: gaussian
+  choices: [Centro, Sitti]
+"
+    );
+}
+
+#[test]
+// Assert that the pick subcommand can pick from a category that is named like a subcommand
+fn pick_subcommand() {
+    let config = CONFIG.replace("lru:\n  model", "list:\n  model");
+
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(&config, &["pick", "list", "--count", "2"], "y\ny\n", true);
+
+    assert_eq!(
+        stdout,
+        "Choice is option 1. Accept? (Y/n) Choice is option 2. Accept? (Y/n) "
+    );
+    let parsed_config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(&config_contents).expect("Could not parse yaml");
+    assert_eq!(
+        parsed_config["list"],
+        ConfigCategory::Lru {
+            choices: vec!["option 3".into(), "option 1".into(), "option 2".into()],
+            group: None,
+        }
+    );
+}
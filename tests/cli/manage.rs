@@ -0,0 +1,136 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of the subcommands that manage categories and choices.
+use std::collections::BTreeMap;
+use std::fs;
+
+use rpick::config::{ConfigCategory, LotteryChoice};
+
+#[test]
+// Build a category from scratch, starting without a config file.
+fn new_add_rename_remove() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = config_dir.path().join("rpick.yml");
+    let c = config_path.to_str().unwrap();
+
+    super::test_rpick(
+        &["new", "activity", "--model", "lottery", "-c", c],
+        "",
+        true,
+    );
+    super::test_rpick(&["add", "activity", "exercise", "-c", c], "", true);
+    super::test_rpick(
+        &[
+            "add",
+            "activity",
+            "tv",
+            "--weight",
+            "5",
+            "--tickets",
+            "0",
+            "-c",
+            c,
+        ],
+        "",
+        true,
+    );
+    super::test_rpick(&["add", "activity", "read", "-c", c], "", true);
+    super::test_rpick(
+        &["rename-choice", "activity", "tv", "watch tv", "-c", c],
+        "",
+        true,
+    );
+    super::test_rpick(&["remove", "activity", "read", "-c", c], "", true);
+
+    let parsed_config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    let mut expected_config = BTreeMap::new();
+    expected_config.insert(
+        "activity".to_string(),
+        ConfigCategory::Lottery {
+            choices: vec![
+                LotteryChoice {
+                    name: "exercise".to_string(),
+                    tickets: 1,
+                    weight: 1,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
+                },
+                LotteryChoice {
+                    name: "watch tv".to_string(),
+                    tickets: 0,
+                    weight: 5,
+                    tags: vec![],
+                    details: Default::default(),
+                    preferences: Default::default(),
+                    availability: Default::default(),
+                },
+            ],
+            group: None,
+        },
+    );
+    assert_eq!(parsed_config, expected_config);
+}
+
+const CONFIG: &str = "
+---
+things:
+  model: even
+  choices:
+    - this
+";
+
+#[test]
+// The user should get useful errors when a change doesn't make sense, and the config should be left
+// alone.
+fn errors() {
+    let cases: Vec<(Vec<&str>, &str)> = vec![
+        (
+            vec!["add", "nope", "that"],
+            "The category `nope` was not found in the given config.\n",
+        ),
+        (
+            vec!["add", "things", "this"],
+            "The category already has a choice named `this`.\n",
+        ),
+        (
+            vec!["add", "things", "that", "--tickets", "3"],
+            "The even model does not support the `tickets` setting.\n",
+        ),
+        (
+            vec!["remove", "things", "that"],
+            "The category has no choice named `that`.\n",
+        ),
+        (
+            vec!["new", "things", "--model", "lru"],
+            "The category `things` already exists.\n",
+        ),
+        (
+            vec!["new", "stuff", "--model", "bogus"],
+            "Unknown model `bogus`. The available models are even, gaussian, inventory, lottery, \
+             lru, recency, rotation, and weighted.\n",
+        ),
+    ];
+
+    for (args, expected_output) in cases {
+        let (stdout, config_contents) = super::test_rpick_with_config(CONFIG, &args, "", false);
+
+        assert_eq!(stdout, expected_output);
+        assert_eq!(config_contents, CONFIG);
+    }
+}
@@ -16,18 +16,38 @@
 /// This module tests the CLI by running it as a subprocess and inspecting its outputs and
 /// resulting config file. This file includes tests from submodules, and also defines a few utility
 /// functions that they all use.
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::fs;
+use std::path::PathBuf;
 
 use assert_cmd::Command;
 use regex::Regex;
-use tempfile::NamedTempFile;
+use tempfile::TempDir;
 
+mod availability;
+mod check;
+mod details;
 mod error_handling;
 mod even;
+mod exclude;
+mod formats;
 mod gaussian;
+mod group;
+mod history;
+mod inspect;
 mod inventory;
+mod json;
+mod layered;
 mod lottery;
 mod lru;
+mod manage;
+mod preferences;
+mod recency;
+mod rotation;
+mod seed;
+mod simulate;
+mod state;
+mod tags;
+mod undo;
 mod weighted;
 
 // Return which item rpick chose in the given stdout.
@@ -60,28 +80,35 @@ fn get_pick(stdout: &str) -> String {
 // further assertions.
 fn test_rpick_with_config(
     config: &str,
-    args: &mut Vec<&str>,
+    args: &[&str],
     stdin: &str,
     expected_success: bool,
 ) -> (String, String) {
-    let mut args = args.clone();
-    let mut config_f = NamedTempFile::new().expect("Failed to open temp file");
-    write!(config_f, "{}", config).expect("Could not write config");
-    config_f.as_file_mut().sync_all().unwrap();
-    args.append(&mut vec!["-c", config_f.path().to_str().expect("t")]);
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = write_config(&config_dir, config);
+    let mut args = args.to_vec();
+    args.extend_from_slice(&["-c", config_path.to_str().expect("t")]);
 
     let stdout = test_rpick(&args, stdin, expected_success);
 
-    let mut config_contents = String::new();
-    config_f
-        .seek(SeekFrom::Start(0))
-        .expect("Could not seek file");
-    config_f
-        .read_to_string(&mut config_contents)
-        .expect("Could not read config");
+    let config_contents = fs::read_to_string(&config_path).expect("Could not read config");
     (stdout, config_contents)
 }
 
+// Write the given config to rpick.yml in the given directory.
+//
+// Tests should keep their config in a temporary directory, so that any files rpick writes next to
+// the config get cleaned up along with it.
+//
+// # Returns
+//
+// Return the path to the config file.
+fn write_config(config_dir: &TempDir, config: &str) -> PathBuf {
+    let config_path = config_dir.path().join("rpick.yml");
+    fs::write(&config_path, config).expect("Could not write config");
+    config_path
+}
+
 // Run rpick with the given arguments and stdin.
 //
 // # Arguments
@@ -0,0 +1,128 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of per-participant preferences.
+use serde_json::{json, Value};
+
+const CONFIG: &str = "
+---
+lunch:
+  model: weighted
+  choices:
+    - name: Centro
+      weight: 2
+      preferences:
+        alice: 3
+        bob: 1
+    - name: Sitti
+      preferences:
+        alice: 1
+        bob: 4
+    - name: Beasley's
+      preferences:
+        bob: 0
+";
+
+#[test]
+// The chance table should show each present participant's preferences, and weigh the choices by
+// their product.
+fn show() {
+    let (stdout, _) = super::test_rpick_with_config(
+        CONFIG,
+        &["show", "lunch", "--with", "alice,bob", "--output", "json"],
+        "",
+        true,
+    );
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+    assert_eq!(
+        document["header"],
+        json!(["Name", "alice", "bob", "Weight", "Chance"])
+    );
+    assert_eq!(
+        document["rows"],
+        json!([
+            {"cells": ["Beasley's", 1, 0, 0, 0.0], "chosen": false},
+            {"cells": ["Sitti", 1, 4, 4, 40.0], "chosen": false},
+            {"cells": ["Centro", 3, 1, 6, 60.0], "chosen": false},
+        ])
+    );
+}
+
+#[test]
+// The group's combine setting should decide how the preferences are combined.
+fn show_combine() {
+    for (combine, weights) in [("sum", json!([1, 5, 8])), ("min", json!([0, 1, 2]))] {
+        let config = format!(
+            "{}  group:\n    participants: [alice, bob]\n    combine: {}\n",
+            CONFIG, combine
+        );
+        let (stdout, _) = super::test_rpick_with_config(
+            &config,
+            &["show", "lunch", "--with", "alice,bob", "--output", "json"],
+            "",
+            true,
+        );
+
+        let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+        let rows = document["rows"]
+            .as_array()
+            .expect("The rows are not an array");
+        assert_eq!(
+            rows.iter()
+                .map(|row| row["cells"][3].clone())
+                .collect::<Value>(),
+            weights,
+            "Unexpected weights when combining with {}",
+            combine
+        );
+    }
+}
+
+#[test]
+// Without --with, the preferences should be ignored.
+fn show_without_participants() {
+    let (stdout, _) =
+        super::test_rpick_with_config(CONFIG, &["show", "lunch", "--output", "json"], "", true);
+
+    let document: Value = serde_json::from_str(&stdout).expect("Output is not JSON");
+    assert_eq!(document["header"], json!(["Name", "Weight", "Chance"]));
+}
+
+#[test]
+// A choice that a present participant gives a preference of 0 should never be offered.
+fn pick() {
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["lunch", "--with", "bob"], "n\ny\n", true);
+
+    assert!(
+        !stdout.contains("Beasley's"),
+        "Unexpected output: {}",
+        stdout
+    );
+    assert_eq!(config_contents, CONFIG);
+}
+
+#[test]
+// A participant that the category doesn't know should be reported.
+fn unknown_participant() {
+    let (stdout, _) =
+        super::test_rpick_with_config(CONFIG, &["lunch", "--with", "alice,dave"], "", false);
+
+    assert_eq!(
+        stdout,
+        "The category `lunch` doesn't know the participant `dave`.\n"
+    );
+}
@@ -0,0 +1,77 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+/// Assert correct operation of the recency model.
+use std::collections::BTreeMap;
+
+use rpick::config::ConfigCategory;
+
+const CONFIG: &str = "
+---
+recency:
+  model: recency
+  choices:
+    - name: option 1
+    - name: option 2
+      # This one should never get picked, since it is cooling down for a long time.
+      last_picked: 2999-01-01T00:00:00Z
+      cooldown: 1 week
+";
+
+#[test]
+// Assert correct behavior with a recency model config
+fn pick() {
+    let before = chrono::Local::now() - chrono::Duration::seconds(1);
+
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["recency"], "y\n", true);
+
+    assert_eq!(super::get_pick(&stdout), "option 1");
+    // Assert that the recency model records when the pick was made
+    let parsed_config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(&config_contents).expect("Could not parse yaml");
+    let mut expected_config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(CONFIG).expect("Could not parse yaml");
+    if let (
+        ConfigCategory::Recency { choices, .. },
+        ConfigCategory::Recency {
+            choices: parsed_choices,
+            ..
+        },
+    ) = (
+        expected_config.get_mut("recency").unwrap(),
+        &parsed_config["recency"],
+    ) {
+        let last_picked = parsed_choices[0].last_picked.unwrap();
+        assert!(before <= last_picked && last_picked <= chrono::Local::now());
+        choices[0].last_picked = Some(last_picked);
+    }
+    assert_eq!(parsed_config, expected_config);
+    assert!(config_contents.contains("      cooldown: 1 week\n"));
+}
+
+#[test]
+// Picking should fail if every choice is cooling down
+fn all_cooling_down() {
+    let config = CONFIG.replace("    - name: option 1\n", "");
+
+    let (stdout, config_contents) = super::test_rpick_with_config(&config, &["recency"], "", false);
+
+    assert_eq!(
+        stdout,
+        "Every choice in the category `recency` is cooling down.\n"
+    );
+    assert_eq!(config_contents, config);
+}
@@ -0,0 +1,81 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of the rotation model.
+
+const CONFIG: &str = "
+---
+duty:
+  model: rotation
+  choices:
+    - name: alice
+      assignments: 3
+    - name: bob
+      assignments: 4
+      capacity: 2
+    - name: carol
+      assignments: 1
+      # carol should never be picked, since she is away for a long time.
+      unavailable:
+        - from: 2000-01-01
+          until: 2999-12-31
+";
+
+#[test]
+// The choice with the smallest share of the picks should be offered first, and picking it should
+// count the assignment.
+fn pick() {
+    let (stdout, config_contents) = super::test_rpick_with_config(CONFIG, &["duty"], "y\n", true);
+
+    assert_eq!(stdout, "Choice is bob. Accept? (Y/n) ");
+    assert_eq!(
+        config_contents,
+        CONFIG.replace("assignments: 4", "assignments: 5")
+    );
+}
+
+#[test]
+// Rejecting a choice should offer the choice with the next smallest share.
+fn pick_reject() {
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["duty"], "n\ny\n", true);
+
+    assert_eq!(
+        stdout,
+        "Choice is bob. Accept? (Y/n) Choice is alice. Accept? (Y/n) "
+    );
+    assert_eq!(
+        config_contents,
+        CONFIG.replace("assignments: 3", "assignments: 4")
+    );
+}
+
+#[test]
+// Picking should fail if every choice is unavailable
+fn all_unavailable() {
+    let config = CONFIG.replace("    - name: alice\n      assignments: 3\n", "");
+    let config = config.replace(
+        "    - name: bob\n      assignments: 4\n      capacity: 2\n",
+        "",
+    );
+
+    let (stdout, config_contents) = super::test_rpick_with_config(&config, &["duty"], "", false);
+
+    assert_eq!(
+        stdout,
+        "Every choice in the category `duty` is unavailable.\n"
+    );
+    assert_eq!(config_contents, config);
+}
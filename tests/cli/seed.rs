@@ -0,0 +1,53 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert that seeded picks can be reproduced.
+use assert_cmd::Command;
+
+const CONFIG: &str = "
+---
+lunch:
+  model: even
+  choices:
+    - Centro
+    - Sitti
+    - Spirits
+    - Cookout
+";
+
+#[test]
+// A seeded pick should be the same on every machine and architecture.
+fn seed() {
+    let (stdout, _) =
+        super::test_rpick_with_config(CONFIG, &["lunch", "--seed", "1", "--yes"], "", true);
+
+    assert_eq!(super::get_pick(&stdout), "Sitti");
+}
+
+#[test]
+fn seed_from_env() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+
+    let assert = Command::cargo_bin("rpick")
+        .unwrap()
+        .args(["lunch", "--yes", "-c", config_path.to_str().expect("t")])
+        .env("RPICK_SEED", "1")
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert_eq!(super::get_pick(&stdout), "Sitti");
+}
@@ -0,0 +1,88 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of the simulate subcommand.
+use regex::Regex;
+
+const CONFIG: &str = "
+---
+code:
+  model: lru
+  choices:
+    - Add a feature
+    - Fix a bug
+    - Write docs
+lunch:
+  model: weighted
+  choices:
+    - name: Centro
+      weight: 3
+    - name: Sitti
+";
+
+#[test]
+// The lru model cycles through its choices, so its simulation is predictable. The config must not
+// be changed.
+fn simulate_lru() {
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["simulate", "code", "--rounds", "6"], "", true);
+
+    for name in ["Add a feature", "Fix a bug", "Write docs"] {
+        let re = Regex::new(&format!(r"{}[ |]+2[ |]+33.33%[ |]+3.00[ |]+2", name)).unwrap();
+        assert!(re.is_match(&stdout), "Unexpected output: {}", stdout);
+    }
+    assert!(!stdout.contains("Accept?"));
+    assert_eq!(config_contents, CONFIG);
+}
+
+#[test]
+// Simulations with the same seed should produce the same results.
+fn simulate_seed() {
+    let args = &["simulate", "lunch", "--rounds", "100", "--seed", "42"];
+    let (first, _) = super::test_rpick_with_config(CONFIG, args, "", true);
+    let (second, _) = super::test_rpick_with_config(CONFIG, args, "", true);
+
+    assert!(first.contains("Centro"), "Unexpected output: {}", first);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn simulate_category_not_found() {
+    let (stdout, _) = super::test_rpick_with_config(CONFIG, &["simulate", "nope"], "", false);
+
+    assert_eq!(
+        stdout,
+        "The category `nope` was not found in the given config.\n"
+    );
+}
+
+#[test]
+// The simulation should stop and say so once an inventory runs out of tickets.
+fn simulate_inventory_runs_out() {
+    let config = "
+---
+tea:
+  model: inventory
+  choices:
+    - name: Earl Grey
+      tickets: 3
+";
+
+    let (stdout, _) = super::test_rpick_with_config(config, &["simulate", "tea"], "", true);
+
+    let re = Regex::new(r"Earl Grey[ |]+3[ |]+100.00%").unwrap();
+    assert!(re.is_match(&stdout), "Unexpected output: {}", stdout);
+    assert!(stdout.ends_with("Every choice in `tea` ran out of tickets after 3 rounds.\n"));
+}
@@ -0,0 +1,79 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert that configs with the separate_state setting keep their pick state in the state file.
+use std::fs;
+
+const CONFIG: &str = "# Shared with the team, so rpick should not change it.
+separate_state: true
+code:
+  model: lru
+  choices:
+    - Add a feature
+    - Fix a bug
+    - Write docs
+tea:
+  model: inventory
+  choices:
+    - name: Black
+      tickets: 1
+";
+
+#[test]
+// Picking should save the new order to the state file and leave the config alone.
+fn pick_writes_state_file() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+    let config_path = config_path.to_str().unwrap();
+    let state_path = config_dir.path().join("rpick.state.yml");
+
+    let stdout = super::test_rpick(&["code", "--yes", "-c", config_path], "", true);
+    assert_eq!(stdout, "Choice is Add a feature.\n");
+    let stdout = super::test_rpick(&["code", "--yes", "-c", config_path], "", true);
+    assert_eq!(stdout, "Choice is Fix a bug.\n");
+    let stdout = super::test_rpick(&["tea", "--yes", "-c", config_path], "", true);
+    assert_eq!(stdout, "Choice is Black.\n");
+
+    assert_eq!(fs::read_to_string(config_path).unwrap(), CONFIG);
+    assert_eq!(
+        fs::read_to_string(&state_path).unwrap(),
+        "---\ncode:\n  order:\n    - Write docs\n    - Add a feature\n    - Fix a bug\ntea:\n  \
+         tickets:\n    Black: 0\n"
+    );
+}
+
+#[test]
+// Choices added to and removed from the config should be reconciled with the state file.
+fn reconcile_edited_config() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+    let config_path = config_path.to_str().unwrap();
+    super::test_rpick(&["code", "--yes", "-c", config_path], "", true);
+    let edited = CONFIG.replace("    - Fix a bug\n", "    - Review a PR\n");
+    fs::write(config_path, &edited).unwrap();
+
+    // Review a PR has never been picked, so it goes first, followed by the state's order.
+    let stdout = super::test_rpick(&["code", "--yes", "-c", config_path], "", true);
+    assert_eq!(stdout, "Choice is Review a PR.\n");
+    let stdout = super::test_rpick(&["code", "--yes", "-c", config_path], "", true);
+    assert_eq!(stdout, "Choice is Write docs.\n");
+
+    // The order is back to the one in the config, so there is no state left to keep.
+    assert_eq!(fs::read_to_string(config_path).unwrap(), edited);
+    assert_eq!(
+        fs::read_to_string(config_dir.path().join("rpick.state.yml")).unwrap(),
+        "---\n{}\n"
+    );
+}
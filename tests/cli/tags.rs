@@ -0,0 +1,154 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of choice tags and the --tag and --exclude-tag flags.
+use std::collections::BTreeMap;
+use std::fs;
+
+use rpick::config::ConfigCategory;
+
+const CONFIG: &str = "
+---
+food:
+  model: lru
+  choices:
+    - Leftovers
+    - name: Tacos
+      tags: [lunch, cheap]
+    - name: Steak
+      tags: [dinner, expensive]
+    - name: Soup
+      tags: [lunch, dinner]
+";
+
+/// Return the names of the choices in the food category, in order.
+fn food_order(config_contents: &str) -> Vec<String> {
+    let config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(config_contents).expect("Could not parse yaml");
+    config["food"]
+        .choice_names()
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+// Only choices matching the tag expression should be offered, but the whole lru order is kept.
+fn pick_tag() {
+    let (stdout, config_contents) = super::test_rpick_with_config(
+        CONFIG,
+        &["food", "--tag", "lunch and not expensive"],
+        "y\n",
+        true,
+    );
+
+    assert_eq!(stdout, "Choice is Tacos. Accept? (Y/n) ");
+    assert_eq!(
+        food_order(&config_contents),
+        vec!["Leftovers", "Steak", "Soup", "Tacos"]
+    );
+    let config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(&config_contents).expect("Could not parse yaml");
+    assert_eq!(
+        config["food"].choice_tags()[3],
+        ("Tacos", &["lunch".to_string(), "cheap".to_string()][..])
+    );
+}
+
+#[test]
+// Choices matching an excluded tag expression should never be offered.
+fn pick_exclude_tag() {
+    let (stdout, config_contents) = super::test_rpick_with_config(
+        CONFIG,
+        &["food", "--exclude-tag", "cheap | !lunch", "-n", "1"],
+        "y\n",
+        true,
+    );
+
+    assert_eq!(stdout, "Choice is Soup. Accept? (Y/n) ");
+    assert_eq!(
+        food_order(&config_contents),
+        vec!["Leftovers", "Tacos", "Steak", "Soup"]
+    );
+}
+
+#[test]
+// Several tag flags should all have to match, and --count only counts matching choices.
+fn pick_tags_count() {
+    let (stdout, _) = super::test_rpick_with_config(
+        CONFIG,
+        &["food", "--tag", "lunch", "--tag", "cheap", "-n", "2"],
+        "",
+        false,
+    );
+
+    assert_eq!(
+        stdout,
+        "Cannot pick 2 items from the category `food`, which only has 1 distinct choices.\n"
+    );
+}
+
+#[test]
+// A filter that nothing matches should be reported.
+fn no_matching_choices() {
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["food", "--tag", "breakfast"], "", false);
+
+    assert_eq!(
+        stdout,
+        "No choices in the category `food` match the tag filter.\n"
+    );
+    assert_eq!(config_contents, CONFIG);
+}
+
+#[test]
+// An invalid tag expression should be rejected before anything is picked.
+fn invalid_expression() {
+    let (stdout, config_contents) =
+        super::test_rpick_with_config(CONFIG, &["food", "--tag", "(lunch"], "", false);
+
+    assert_eq!(stdout, "");
+    assert_eq!(config_contents, CONFIG);
+}
+
+#[test]
+// Tagged choices should be read from and saved back to TOML.
+fn pick_tag_toml() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = config_dir.path().join("rpick.toml");
+    fs::write(
+        &config_path,
+        "[code]\nmodel = \"lru\"\nchoices = [\"Fix a bug\", { name = \"Add a feature\", tags = [\"big\"] }]\n",
+    )
+    .expect("Could not write config");
+    let config_path = config_path.to_str().unwrap();
+
+    let stdout = super::test_rpick(
+        &["code", "--yes", "--tag", "big", "-c", config_path],
+        "",
+        true,
+    );
+
+    assert_eq!(stdout, "Choice is Add a feature.\n");
+    let contents = fs::read_to_string(config_path).unwrap();
+    let config: BTreeMap<String, ConfigCategory> = toml::from_str(&contents).unwrap();
+    assert_eq!(
+        config["code"].choice_tags(),
+        vec![
+            ("Fix a bug", &[][..]),
+            ("Add a feature", &["big".to_string()][..])
+        ]
+    );
+}
@@ -0,0 +1,171 @@
+/*
+ * Copyright © 2021 Randy Barlow
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Assert correct operation of the undo subcommand.
+use std::collections::BTreeMap;
+use std::fs;
+
+use rpick::config::ConfigCategory;
+
+const CONFIG: &str = "
+---
+lottery:
+  model: lottery
+  choices:
+    - name: option 1
+      tickets: 1
+      weight: 1
+    - name: option 2
+      tickets: 2
+      weight: 2
+inventory:
+  model: inventory
+  choices:
+    - name: option 1
+      tickets: 3
+";
+
+#[test]
+// Undo should restore the category from before the most recent pick, and drop it from the history.
+fn undo() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+    let config_path_str = config_path.to_str().unwrap();
+    super::test_rpick(&["inventory", "-c", config_path_str], "y\n", true);
+    super::test_rpick(&["lottery", "-c", config_path_str], "y\n", true);
+
+    let stdout = super::test_rpick(&["undo", "inventory", "-c", config_path_str], "", true);
+
+    assert!(stdout.starts_with("Restored `inventory` to its state from before the pick made at "));
+    let parsed_config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    let expected_config: BTreeMap<String, ConfigCategory> = serde_yaml::from_str(CONFIG).unwrap();
+    // The inventory should be back where it started, but the lottery pick should remain.
+    assert_eq!(parsed_config["inventory"], expected_config["inventory"]);
+    assert_ne!(parsed_config["lottery"], expected_config["lottery"]);
+    let history = super::test_rpick(&["history", "-c", config_path_str], "", true);
+    assert!(history.contains("lottery"));
+    assert!(!history.contains("inventory"));
+
+    super::test_rpick(&["undo", "-c", config_path_str], "", true);
+
+    let parsed_config: BTreeMap<String, ConfigCategory> =
+        serde_yaml::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(parsed_config, expected_config);
+}
+
+#[test]
+// A pick made with --at should be dropped from the history when it is undone.
+fn undo_at() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+    let config_path_str = config_path.to_str().unwrap();
+    super::test_rpick(
+        &[
+            "inventory",
+            "--at",
+            "2021-06-12T12:00",
+            "-c",
+            config_path_str,
+        ],
+        "y\n",
+        true,
+    );
+
+    let stdout = super::test_rpick(&["undo", "-c", config_path_str], "", true);
+
+    assert_eq!(
+        stdout,
+        "Restored `inventory` to its state from before the pick made at 2021-06-12 12:00.\n"
+    );
+    let history = super::test_rpick(&["history", "-c", config_path_str], "", true);
+    assert!(
+        !history.contains("inventory"),
+        "Unexpected history: {}",
+        history
+    );
+}
+
+#[test]
+// Undoing a back dated pick should drop only its own history entry, not the picks recorded before
+// it that have later times.
+fn undo_back_dated() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+    let config_path_str = config_path.to_str().unwrap();
+    super::test_rpick(&["inventory", "-c", config_path_str], "y\n", true);
+    super::test_rpick(
+        &[
+            "inventory",
+            "--at",
+            "2021-06-12T12:00",
+            "-c",
+            config_path_str,
+        ],
+        "y\n",
+        true,
+    );
+
+    super::test_rpick(&["undo", "-c", config_path_str], "", true);
+
+    let history = super::test_rpick(&["history", "-c", config_path_str], "", true);
+    assert_eq!(
+        history.matches("inventory").count(),
+        1,
+        "Unexpected history: {}",
+        history
+    );
+    assert!(
+        !history.contains("2021-06-12"),
+        "Unexpected history: {}",
+        history
+    );
+}
+
+#[test]
+// Undo should refuse to restore a category whose choices changed after the pick, rather than
+// dropping the changes.
+fn undo_after_add() {
+    let config_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = super::write_config(&config_dir, CONFIG);
+    let config_path_str = config_path.to_str().unwrap();
+    super::test_rpick(&["inventory", "-c", config_path_str], "y\n", true);
+    super::test_rpick(
+        &["add", "inventory", "option 2", "-c", config_path_str],
+        "",
+        true,
+    );
+    let config_contents = fs::read_to_string(&config_path).unwrap();
+
+    let stdout = super::test_rpick(&["undo", "-c", config_path_str], "", false);
+
+    assert_eq!(
+        stdout,
+        "The model or choices of the category `inventory` have changed since the pick, so it \
+         can't be undone.\n"
+    );
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), config_contents);
+    let history = super::test_rpick(&["history", "-c", config_path_str], "", true);
+    assert!(history.contains("inventory"));
+}
+
+#[test]
+// The user should get a useful error if there is nothing to undo.
+fn nothing_to_undo() {
+    let (stdout, config_contents) = super::test_rpick_with_config(CONFIG, &["undo"], "", false);
+
+    assert_eq!(stdout, "There is nothing to undo.\n");
+    assert_eq!(config_contents, CONFIG);
+}